@@ -0,0 +1,126 @@
+//! Pure accrual math shared by the contract entrypoints.
+
+/// Amount accrued to the recipient at `current_time` for the given schedule.
+///
+/// Returns 0 before the cliff or `start_time`; otherwise
+/// `min((min(current_time, end_time) - start_time) * rate_per_second, deposit_amount)`.
+/// Multiplication overflow saturates to `deposit_amount`, and the result is
+/// never negative.
+pub fn calculate_accrued_amount(
+    start_time: u64,
+    cliff_time: u64,
+    end_time: u64,
+    rate_per_second: i128,
+    deposit_amount: i128,
+    current_time: u64,
+) -> i128 {
+    calculate_accrued_with_pauses(
+        start_time,
+        cliff_time,
+        end_time,
+        rate_per_second,
+        deposit_amount,
+        current_time,
+        0,
+        None,
+    )
+}
+
+/// [`calculate_accrued_amount`] for a schedule that does not accrue while
+/// paused.
+///
+/// `total_paused_seconds` is the length of the pauses that have ended and
+/// `paused_since` the start of one still in progress, which counts up to
+/// `min(current_time, end_time)`. Paused time is taken off the elapsed
+/// window, saturating at zero, so a pause never moves the cliff or the end
+/// and the result stays within `[0, deposit_amount]`.
+#[allow(clippy::too_many_arguments)]
+pub fn calculate_accrued_with_pauses(
+    start_time: u64,
+    cliff_time: u64,
+    end_time: u64,
+    rate_per_second: i128,
+    deposit_amount: i128,
+    current_time: u64,
+    total_paused_seconds: u64,
+    paused_since: Option<u64>,
+) -> i128 {
+    if current_time < cliff_time {
+        return 0;
+    }
+
+    // Checked separately from the cliff, which callers may set before start
+    if current_time < start_time {
+        return 0;
+    }
+
+    if start_time >= end_time || rate_per_second < 0 {
+        return 0;
+    }
+
+    let elapsed_now = current_time.min(end_time);
+    let ongoing_pause =
+        paused_since.map_or(0, |since| elapsed_now.saturating_sub(since.max(start_time)));
+    let paused = total_paused_seconds.saturating_add(ongoing_pause);
+    let elapsed = (elapsed_now - start_time).saturating_sub(paused) as i128;
+
+    let accrued = match elapsed.checked_mul(rate_per_second) {
+        Some(accrued) => accrued,
+        None => deposit_amount,
+    };
+
+    accrued.min(deposit_amount).max(0) // ensures result >= 0
+}
+
+/// Amount accrued at `current_time` for a schedule that unlocks in equal
+/// tranches every `step_seconds` from `start_time` to `end_time`.
+///
+/// Returns `deposit_amount / steps * whole_steps_elapsed`, where
+/// `steps = (end_time - start_time) / step_seconds`. Nothing accrues between
+/// boundaries, and the full deposit is released at `end_time` when it divides
+/// evenly into the steps.
+pub fn calculate_stepped_accrued_amount(
+    start_time: u64,
+    end_time: u64,
+    step_seconds: u64,
+    deposit_amount: i128,
+    current_time: u64,
+) -> i128 {
+    if current_time < start_time || start_time >= end_time || step_seconds == 0 {
+        return 0;
+    }
+
+    let steps = (end_time - start_time) / step_seconds;
+    if steps == 0 {
+        return 0;
+    }
+    let elapsed_steps = (current_time.min(end_time) - start_time) / step_seconds;
+
+    // elapsed_steps <= steps, so this never exceeds deposit_amount
+    (deposit_amount / steps as i128) * elapsed_steps as i128
+}
+
+/// How a cancellation divides a stream's deposit. Returned by
+/// [`settlement_amounts`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Settlement {
+    /// Part of the deposit returned to the sender's refund address.
+    pub refund_to_sender: i128,
+    /// Part left for the recipient to withdraw.
+    pub claimable_by_recipient: i128,
+}
+
+/// Split `deposit` when a stream is cancelled with `accrued` vested and
+/// `withdrawn` already paid out.
+///
+/// The recipient keeps everything accrued and the rest is refunded, so the
+/// two parts plus `withdrawn` add up to `deposit`. `accrued` is capped at
+/// `deposit`, and when `withdrawn` exceeds it (as after an advance) the
+/// excess counts as vested rather than being refunded a second time.
+pub fn settlement_amounts(deposit: i128, accrued: i128, withdrawn: i128) -> Settlement {
+    let vested = accrued.max(withdrawn).min(deposit);
+    Settlement {
+        refund_to_sender: deposit - vested,
+        claimable_by_recipient: (vested - withdrawn).max(0),
+    }
+}
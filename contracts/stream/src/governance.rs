@@ -0,0 +1,309 @@
+//! N-of-M approvals for privileged admin operations.
+//!
+//! Until `set_governance` is called the admin acts alone. Afterwards the
+//! admin operations (cancels, arbitration, force-complete, disputes,
+//! milestone approvals, upgrades, freezes and the contract-wide settings)
+//! are only reachable through proposals: any approver proposes a
+//! [`GovAction`], approvers approve it, and once `threshold` distinct
+//! approvals are in, anyone may execute it before it expires.
+
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, BytesN, Env, Vec};
+
+use crate::{get_admin, load_stream, FluxoraStream, FluxoraStreamClient};
+
+/// Approver set and rules, fixed by `set_governance` and changed afterwards
+/// only through a `GovAction::UpdateGovernance` proposal.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GovernanceConfig {
+    pub approvers: Vec<Address>,
+    /// Distinct approvals needed before a proposal can be executed.
+    pub threshold: u32,
+    /// Seconds a proposal stays approvable and executable after creation.
+    pub proposal_ttl: u64,
+}
+
+/// Privileged operation a proposal executes.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GovAction {
+    /// Cancel the stream, exactly as `cancel_stream` would.
+    CancelStream(u64),
+    /// Same as `upgrade_contract`.
+    Upgrade(BytesN<32>),
+    /// Same as `set_withdrawal_fee`.
+    SetWithdrawalFee(u32, Address),
+    /// Replace the approver set, threshold and proposal TTL.
+    UpdateGovernance(GovernanceConfig),
+    /// Split the stream's remaining balance as `arbitrate_stream` would,
+    /// with the proposal's approvals standing in for the admin cancel delay.
+    ArbitrateStream(u64, u32),
+    /// Same as `set_fee_collector`.
+    SetFeeCollector(Address),
+    /// Same as `freeze_recipient`.
+    FreezeRecipient(Address),
+    /// Same as `unfreeze_recipient`.
+    UnfreezeRecipient(Address),
+    /// Same as `set_compliance_hook`.
+    SetComplianceHook(Option<Address>),
+    /// Same as `set_min_deposit`.
+    SetMinDeposit(Option<i128>),
+    /// Same as `force_complete`.
+    ForceComplete(u64),
+    /// Same as `resolve_dispute`.
+    ResolveDispute(u64, bool),
+    /// Same as `set_withdraw_grace_period`.
+    SetWithdrawGracePeriod(u64),
+    /// Same as `set_min_cliff_duration`.
+    SetMinCliffDuration(Option<u64>),
+    /// Same as `set_recipient_may_dispute`.
+    SetRecipientMayDispute(bool),
+    /// Same as `approve_milestone`.
+    ApproveMilestone(u64, u32),
+    /// Same as `set_admin_cancel_delay`.
+    SetAdminCancelDelay(u64),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Proposal {
+    pub action: GovAction,
+    pub proposer: Address,
+    /// Approvers who have approved so far, without duplicates.
+    pub approvals: Vec<Address>,
+    /// Last ledger timestamp at which the proposal may be approved or executed.
+    pub expires_at: u64,
+    pub executed: bool,
+}
+
+/// Storage keys owned by this module. Variant names must not clash with
+/// `DataKey`'s, as both encode to the variant name.
+#[contracttype]
+enum GovKey {
+    GovConfig,        // Instance: approver set, threshold and TTL.
+    NextProposalId,   // Instance: auto-incrementing proposal id.
+    GovProposal(u64), // Persistent: proposal by id.
+}
+
+fn load_governance(env: &Env) -> Option<GovernanceConfig> {
+    env.storage().instance().get(&GovKey::GovConfig)
+}
+
+fn save_governance(env: &Env, config: &GovernanceConfig) {
+    assert!(
+        config.threshold > 0 && config.threshold <= config.approvers.len(),
+        "threshold must be between 1 and the number of approvers"
+    );
+    assert!(config.proposal_ttl > 0, "proposal_ttl must be positive");
+    for (i, approver) in config.approvers.iter().enumerate() {
+        assert!(
+            config.approvers.first_index_of(&approver) == Some(i as u32),
+            "duplicate approver"
+        );
+    }
+    env.storage().instance().set(&GovKey::GovConfig, config);
+}
+
+fn load_proposal(env: &Env, proposal_id: u64) -> Proposal {
+    env.storage()
+        .persistent()
+        .get(&GovKey::GovProposal(proposal_id))
+        .expect("proposal not found")
+}
+
+fn save_proposal(env: &Env, proposal_id: u64, proposal: &Proposal) {
+    let key = GovKey::GovProposal(proposal_id);
+    env.storage().persistent().set(&key, proposal);
+    env.storage().persistent().extend_ttl(&key, 17280, 120960);
+}
+
+fn require_approver(config: &GovernanceConfig, approver: &Address) {
+    assert!(config.approvers.contains(approver), "not an approver");
+    approver.require_auth();
+}
+
+/// Auth check for the gated admin entrypoints: the admin's auth while
+/// governance is off; refused outright once it is on.
+pub(crate) fn require_admin(env: &Env) {
+    assert!(
+        load_governance(env).is_none(),
+        "admin action requires a governance proposal"
+    );
+    get_admin(env).require_auth();
+}
+
+#[contractimpl]
+impl FluxoraStream {
+    /// Hand the gated admin operations over to `approvers`, `threshold` of
+    /// whom must approve each one. Requires admin auth and can only be done
+    /// once; later changes go through a `UpdateGovernance` proposal.
+    ///
+    /// # Panics
+    /// - If governance is already configured.
+    /// - If `threshold` is 0 or exceeds the number of approvers.
+    /// - If `approvers` contains duplicates or `proposal_ttl` is 0.
+    pub fn set_governance(env: Env, approvers: Vec<Address>, threshold: u32, proposal_ttl: u64) {
+        require_admin(&env);
+
+        let config = GovernanceConfig {
+            approvers,
+            threshold,
+            proposal_ttl,
+        };
+        save_governance(&env, &config);
+
+        env.events()
+            .publish((symbol_short!("gov_set"),), (config.threshold, config.approvers));
+    }
+
+    /// Propose `action`. `proposer` must be an approver and authorize the
+    /// call; proposing does not count as approving. Returns the proposal id.
+    ///
+    /// # Panics
+    /// - If governance is not configured or `proposer` is not an approver.
+    pub fn propose(env: Env, proposer: Address, action: GovAction) -> u64 {
+        let config = load_governance(&env).expect("governance not configured");
+        require_approver(&config, &proposer);
+
+        let proposal_id: u64 = env
+            .storage()
+            .instance()
+            .get(&GovKey::NextProposalId)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&GovKey::NextProposalId, &(proposal_id + 1));
+
+        let proposal = Proposal {
+            action,
+            proposer: proposer.clone(),
+            approvals: Vec::new(&env),
+            expires_at: env.ledger().timestamp() + config.proposal_ttl,
+            executed: false,
+        };
+        save_proposal(&env, proposal_id, &proposal);
+
+        env.events().publish(
+            (symbol_short!("proposed"), proposal_id),
+            (proposer, proposal.action),
+        );
+        proposal_id
+    }
+
+    /// Record `approver`'s approval of a proposal. Requires the approver's
+    /// auth.
+    ///
+    /// # Panics
+    /// - If `approver` is not an approver or already approved.
+    /// - If the proposal is expired or already executed.
+    pub fn approve(env: Env, approver: Address, proposal_id: u64) {
+        let config = load_governance(&env).expect("governance not configured");
+        require_approver(&config, &approver);
+
+        let mut proposal = load_proposal(&env, proposal_id);
+        assert!(!proposal.executed, "proposal already executed");
+        assert!(
+            env.ledger().timestamp() <= proposal.expires_at,
+            "proposal expired"
+        );
+        assert!(
+            !proposal.approvals.contains(&approver),
+            "already approved"
+        );
+
+        proposal.approvals.push_back(approver.clone());
+        save_proposal(&env, proposal_id, &proposal);
+
+        env.events().publish(
+            (symbol_short!("approved"), proposal_id),
+            (approver, proposal.approvals.len()),
+        );
+    }
+
+    /// Execute a proposal that has reached the threshold. Callable by
+    /// anyone.
+    ///
+    /// Approvals from addresses removed from the approver set since they
+    /// approved no longer count.
+    ///
+    /// # Panics
+    /// - If the proposal lacks `threshold` approvals from current approvers.
+    /// - If the proposal is expired or already executed.
+    /// - Under any condition in which the underlying operation would panic.
+    pub fn execute_proposal(env: Env, proposal_id: u64) {
+        let config = load_governance(&env).expect("governance not configured");
+        let mut proposal = load_proposal(&env, proposal_id);
+        assert!(!proposal.executed, "proposal already executed");
+        assert!(
+            env.ledger().timestamp() <= proposal.expires_at,
+            "proposal expired"
+        );
+        let approvals = proposal
+            .approvals
+            .iter()
+            .filter(|approver| config.approvers.contains(approver))
+            .count() as u32;
+        assert!(approvals >= config.threshold, "not enough approvals");
+
+        proposal.executed = true;
+        save_proposal(&env, proposal_id, &proposal);
+
+        match proposal.action {
+            GovAction::CancelStream(stream_id) => {
+                Self::apply_cancel(&env, load_stream(&env, stream_id));
+            }
+            GovAction::Upgrade(new_wasm_hash) => Self::apply_upgrade(&env, new_wasm_hash),
+            GovAction::SetWithdrawalFee(fee_bps, fee_collector) => {
+                Self::apply_withdrawal_fee(&env, fee_bps, fee_collector);
+            }
+            GovAction::UpdateGovernance(new_config) => save_governance(&env, &new_config),
+            GovAction::ArbitrateStream(stream_id, recipient_share_bps) => {
+                Self::apply_arbitration(&env, load_stream(&env, stream_id), recipient_share_bps);
+            }
+            GovAction::SetFeeCollector(new_collector) => {
+                Self::apply_fee_collector(&env, new_collector);
+            }
+            GovAction::FreezeRecipient(recipient) => Self::apply_freeze(&env, recipient),
+            GovAction::UnfreezeRecipient(recipient) => Self::apply_unfreeze(&env, recipient),
+            GovAction::SetComplianceHook(hook) => Self::apply_compliance_hook(&env, hook),
+            GovAction::SetMinDeposit(min_deposit) => Self::apply_min_deposit(&env, min_deposit),
+            GovAction::ForceComplete(stream_id) => {
+                Self::apply_force_complete(&env, stream_id);
+            }
+            GovAction::ResolveDispute(stream_id, allow) => {
+                Self::apply_resolve_dispute(&env, stream_id, allow);
+            }
+            GovAction::SetWithdrawGracePeriod(grace_period) => {
+                Self::apply_withdraw_grace_period(&env, grace_period);
+            }
+            GovAction::SetMinCliffDuration(min_cliff) => {
+                Self::apply_min_cliff_duration(&env, min_cliff);
+            }
+            GovAction::SetRecipientMayDispute(enabled) => {
+                Self::apply_recipient_may_dispute(&env, enabled);
+            }
+            GovAction::ApproveMilestone(stream_id, index) => {
+                Self::apply_approve_milestone(&env, stream_id, index);
+            }
+            GovAction::SetAdminCancelDelay(delay_seconds) => {
+                Self::apply_admin_cancel_delay(&env, delay_seconds);
+            }
+        }
+
+        env.events()
+            .publish((symbol_short!("executed"), proposal_id), ());
+    }
+
+    /// Current governance rules, or `None` while the admin acts alone.
+    pub fn get_governance(env: Env) -> Option<GovernanceConfig> {
+        load_governance(&env)
+    }
+
+    /// Return a proposal by id.
+    ///
+    /// # Panics
+    /// - If no proposal has that id.
+    pub fn get_proposal(env: Env, proposal_id: u64) -> Proposal {
+        load_proposal(&env, proposal_id)
+    }
+}
@@ -1,17 +1,120 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, contracttype, symbol_short, token, Address, Env};
+mod accrual;
+mod governance;
+mod migration;
+
+pub use governance::{GovAction, GovernanceConfig, Proposal};
+
+use soroban_sdk::{
+    contract, contractclient, contracterror, contractimpl, contracttype, panic_with_error,
+    symbol_short, token, xdr::ToXdr, Address, Bytes, BytesN, Env, IntoVal, Symbol, Vec,
+};
+
+/// XDR encoding of `Asset::Native` (the `ASSET_TYPE_NATIVE` discriminant).
+/// Used to derive the deterministic address of the native XLM asset contract.
+const NATIVE_ASSET_XDR: [u8; 4] = [0, 0, 0, 0];
+
+/// Maximum length in bytes of the memo attached to a stream.
+const MAX_MEMO_LEN: u32 = 64;
+
+/// Maximum number of timestamps accepted by `sample_accrual` in one call.
+const MAX_SAMPLE_POINTS: u32 = 100;
+
+/// Maximum number of ids accepted by `get_stream_states` in one call.
+const MAX_STREAM_BATCH: u32 = 50;
+
+/// Maximum number of ids accepted by `get_bulk_stats` in one call.
+const MAX_BULK_STATS_IDS: u32 = 200;
+
+/// Maximum number of tranches in a milestone stream.
+const MAX_MILESTONES: u32 = 50;
+
+/// How long a `propose_co_cancel` proposal stays confirmable (7 days).
+const CO_CANCEL_WINDOW_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+/// Maximum number of claim dates in a withdrawal schedule.
+const MAX_WITHDRAW_SCHEDULE_LEN: u32 = 100;
+
+/// Default notice period for admin cancellations (24 hours).
+const DEFAULT_ADMIN_CANCEL_DELAY: u64 = 24 * 60 * 60;
+
+/// Default time a recipient has to withdraw from a cancelled stream before
+/// the sender may reclaim it (90 days).
+const DEFAULT_WITHDRAW_GRACE_PERIOD: u64 = 90 * 24 * 60 * 60;
+
+/// Denominator for fees expressed in basis points.
+const BPS_DENOMINATOR: u32 = 10_000;
+
+/// Highest share of a withdrawal `withdraw_with_withholding` may withhold.
+const MAX_WITHHOLDING_BPS: u32 = 5_000;
+
+/// Seconds in a 365-day year, for annualized rate display.
+const SECONDS_PER_YEAR: i128 = 31_536_000;
+
+/// Seconds in an average month (1/12 of `SECONDS_PER_YEAR`).
+const SECONDS_PER_MONTH: i128 = 2_628_000;
+
+/// Status transitions kept per stream by the audit trail.
+const MAX_AUDIT_ENTRIES: u32 = 50;
+
+/// Fixed-point scale of `YieldKey::HarvestIndex`.
+const YIELD_SCALE: i128 = 1_000_000_000;
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+/// Typed errors surfaced by the contract where callers need to distinguish
+/// the failure reason programmatically.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum ContractError {
+    /// The token contract rejected a transfer (insufficient balance or
+    /// allowance, or — for native XLM — the account would drop below its
+    /// minimum reserve).
+    TransferFailed = 1,
+    /// The configured compliance hook vetoed the withdrawal.
+    ComplianceDenied = 2,
+    /// The configured compliance hook trapped or returned something other
+    /// than a `bool`.
+    ComplianceHookFailed = 3,
+    /// A recipient callback registered as `required` trapped.
+    CallbackFailed = 4,
+    /// The sender's allowance to this contract does not cover the deposit.
+    InsufficientAllowance = 5,
+}
 
 // ---------------------------------------------------------------------------
 // Data types
 // ---------------------------------------------------------------------------
 
-/// Global configuration for the Fluxora protocol.
+/// Global configuration for the Fluxora protocol: the token and admin, and
+/// every contract-wide setting the admin can change. Governance rules, once
+/// set, are read separately with `get_governance`.
 #[contracttype]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Config {
     pub token: Address,
     pub admin: Address,
+    /// Seconds between `admin_request_cancel` and when
+    /// `admin_execute_cancel` may act on it.
+    pub admin_cancel_delay: u64,
+    /// Withdrawal fee in basis points; 0 when none is charged.
+    pub fee_bps: u32,
+    /// Receives withdrawal fees. No fee is charged until one is set.
+    pub fee_collector: Option<Address>,
+    /// Smallest deposit a new stream may have.
+    pub min_deposit: Option<i128>,
+    /// Shortest `cliff_time - start_time` a new stream may have.
+    pub min_cliff_duration: Option<u64>,
+    /// Time recipients have to withdraw from a cancelled stream.
+    pub withdraw_grace_period: u64,
+    /// Contract consulted before every withdrawal.
+    pub compliance_hook: Option<Address>,
+    /// Whether recipients, not just senders, may raise disputes.
+    pub recipient_may_dispute: bool,
 }
 
 #[contracttype]
@@ -21,6 +124,33 @@ pub enum StreamStatus {
     Paused = 1,
     Completed = 2,
     Cancelled = 3,
+    /// Created with a future `start_time`. Becomes `Active` the first time
+    /// the stream is loaded at or after `start_time`.
+    Scheduled = 4,
+    /// Ended by `arbitrate_stream`, which paid out everything the stream
+    /// still held.
+    Arbitrated = 5,
+}
+
+impl StreamStatus {
+    /// Whether the stream has not reached a terminal status yet.
+    fn is_open(self) -> bool {
+        matches!(
+            self,
+            StreamStatus::Scheduled | StreamStatus::Active | StreamStatus::Paused
+        )
+    }
+}
+
+/// Purpose a stream is tagged with, for segmenting streams by use case.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StreamCategory {
+    Payroll = 0,
+    Vesting = 1,
+    Grant = 2,
+    Subscription = 3,
+    Other = 4,
 }
 
 #[contracttype]
@@ -29,6 +159,8 @@ pub struct Stream {
     pub stream_id: u64,
     pub sender: Address,
     pub recipient: Address,
+    /// Amount locked for the stream. On cancellation it is reduced to what
+    /// had accrued to the recipient, the rest having been refunded.
     pub deposit_amount: i128,
     pub rate_per_second: i128,
     pub start_time: u64,
@@ -36,14 +168,319 @@ pub struct Stream {
     pub end_time: u64,
     pub withdrawn_amount: i128,
     pub status: StreamStatus,
+    /// Free-form reference to an external record (e.g. an invoice number).
+    /// Empty when no memo was supplied.
+    pub memo: Bytes,
+    /// Caller-supplied 32-byte identifier from an off-chain system, unique
+    /// across streams and resolvable via `get_stream_by_ref`. Held as `Bytes`
+    /// because the SDK cannot convert `Option<BytesN<_>>` struct fields.
+    pub external_ref: Option<Bytes>,
+    /// Receives cancellation refunds and completion dust. Defaults to the
+    /// sender; cancel authorization stays with the sender regardless.
+    pub refund_address: Address,
+    /// Set irreversibly by `lock_stream`; a locked stream can no longer be
+    /// paused or cancelled, by the sender or the admin.
+    pub immutable: bool,
+    /// Unlock interval of a stepped stream created with
+    /// `create_monthly_stream`: accrual only advances at whole multiples of
+    /// this many seconds after `start_time`. 0 for linear streams.
+    pub step_seconds: u64,
+    /// Purpose tag set via `set_stream_category`; `Other` until then.
+    pub category: StreamCategory,
+    /// Address allowed to pause and resume on the sender's behalf, set via
+    /// `set_sender_delegate`.
+    pub delegate: Option<Address>,
+    /// Whether `delegate` may also cancel.
+    pub delegate_can_cancel: bool,
+    /// Cumulative unlock amounts of a stream created with
+    /// `create_milestone_stream`; empty for time-based streams.
+    pub milestones: Vec<i128>,
+    /// How many leading `milestones` the admin has approved.
+    pub milestones_approved: u32,
+    /// Timestamps at which the recipient may claim, set via
+    /// `create_stream_with_schedule`. `None` allows withdrawing at any time.
+    pub withdraw_schedule: Option<Vec<u64>>,
+    /// Ledger time of the most recent withdrawal; 0 until the first one.
+    pub last_withdrawn_at: u64,
+    /// Amount accrued before the last `amend_stream`, which moved
+    /// `start_time` up to the amendment. Accrual is this plus
+    /// `rate_per_second` since `start_time`. 0 for unamended streams.
+    pub accrued_checkpoint: i128,
+}
+
+/// Compact record kept for a Completed stream after `archive_completed_stream`
+/// drops its full `Stream` entry.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ArchivedStream {
+    pub stream_id: u64,
+    pub sender: Address,
+    pub recipient: Address,
+    pub total_streamed: i128,
+    pub completed_at: u64,
+}
+
+/// Renewal terms of a stream created with `create_recurring_stream`.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct RecurringConfig {
+    pub period_seconds: u64,
+    pub period_deposit: i128,
+    /// Number of periods funded so far, including the first.
+    pub periods_funded: u32,
+    /// Set when `roll_period` could not pull the next deposit; cleared by the
+    /// next successful roll.
+    pub lapsed: bool,
+}
+
+/// Pending mutual cancellation proposed by a stream's sender.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoCancelProposal {
+    /// Part of the remaining balance paid to the recipient on confirmation;
+    /// the rest is refunded.
+    pub recipient_share: i128,
+    /// Last ledger timestamp at which the recipient may confirm.
+    pub valid_until: u64,
+}
+
+/// Terms of a new stream, as taken by `create_stream_v2`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamParameters {
+    pub recipient: Address,
+    pub deposit_amount: i128,
+    pub rate_per_second: i128,
+    pub start_time: u64,
+    pub cliff_time: u64,
+    pub end_time: u64,
+}
+
+/// A stream together with the amounts a UI needs to render it, all computed
+/// at the ledger time of the call. Returned by `get_stream_view`.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct StreamView {
+    pub stream: Stream,
+    /// Same as `calculate_accrued`.
+    pub accrued: i128,
+    /// What `withdraw_available` would pay out now, before any withdrawal fee.
+    pub withdrawable: i128,
+    /// What cancelling now would refund; 0 if the stream cannot be cancelled.
+    pub refundable_if_cancelled_now: i128,
+    /// Same as `time_remaining`.
+    pub seconds_remaining: u64,
+}
+
+/// Why `cancel_streams_as_admin` left a stream alone.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CancelSkipReason {
+    /// No such stream, or it was archived.
+    NotFound = 0,
+    /// Already ended (`Completed`, `Cancelled` or `Arbitrated`), or locked.
+    NotCancellable = 1,
+    /// No admin cancel was requested with `admin_request_cancel`.
+    NotRequested = 2,
+    /// The requested cancel's notice period has not elapsed.
+    DelayPending = 3,
+}
+
+/// Per-stream outcome of `cancel_streams_as_admin`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CancelResult {
+    /// Cancelled, refunding this much to the refund address.
+    Cancelled(i128),
+    Skipped(CancelSkipReason),
+}
+
+/// Outcome of a single withdrawal. Returned by `withdraw_receipt`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawReceipt {
+    pub stream_id: u64,
+    /// Paid to the recipient, net of the fee.
+    pub amount: i128,
+    /// Paid to the fee collector.
+    pub fee: i128,
+    /// The stream's `withdrawn_amount` afterwards.
+    pub total_withdrawn: i128,
+    /// Whether this withdrawal completed the stream.
+    pub completed: bool,
+}
+
+/// Coarse position of a stream in its lifecycle, for display.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StreamPhase {
+    /// Before `start_time`.
+    NotStarted = 0,
+    /// From `start_time` until `cliff_time`.
+    Cliff = 1,
+    /// From `cliff_time` until `end_time`.
+    Streaming = 2,
+    /// Past `end_time` but not yet `Completed`. Milestone streams, which have
+    /// no schedule, stay here until they complete.
+    Ended = 3,
+    /// `Completed`, `Cancelled` or `Arbitrated`.
+    Terminal = 4,
+}
+
+/// Display-ready progress of a stream. Returned by `get_stream_summary`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamSummary {
+    /// Accrued share of `get_total_streamable`, in basis points.
+    pub progress_bps: u32,
+    /// Withdrawn share of `get_total_streamable`, in basis points.
+    pub withdrawn_bps: u32,
+    pub phase: StreamPhase,
+}
+
+/// Protocol-wide totals. Returned by `get_stats`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Stats {
+    /// Streams ever created, including archived ones.
+    pub total_streams: u64,
+    /// Streams currently `Active`.
+    pub active_count: u32,
+    /// Tokens the contract holds for streams that have not completed.
+    pub total_locked: i128,
+    /// Tokens ever withdrawn from streams, fees included.
+    pub total_withdrawn_all_time: i128,
+}
+
+/// Totals over a chosen set of streams. Returned by `get_bulk_stats`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BulkStats {
+    /// Streams found among the requested ids.
+    pub count: u32,
+    pub total_deposit: i128,
+    pub total_accrued: i128,
+    pub total_withdrawn: i128,
+    /// Tokens the contract still holds for these streams.
+    pub total_remaining_in_contract: i128,
+}
+
+/// Aggregated view of every stream an address sends or receives.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AddressSummary {
+    /// Streams sent by the address that are currently `Active`.
+    pub active_sent: u32,
+    /// Streams received by the address that are currently `Active`.
+    pub active_received: u32,
+    /// What the address could withdraw right now across received streams.
+    pub total_claimable: i128,
+    /// What the address would get back by cancelling its sent streams now.
+    pub total_refundable: i128,
+    pub total_deposited_by_sender: i128,
+    pub total_received_by_recipient: i128,
+}
+
+/// Contract notified through `on_stream_withdraw(stream_id, amount)` after
+/// every successful withdrawal from a stream.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawCallback {
+    pub contract: Address,
+    /// When `true` a failing callback reverts the withdrawal with
+    /// [`ContractError::CallbackFailed`]; otherwise the failure is reported
+    /// through a `cb_fail` event and the withdrawal stands.
+    pub required: bool,
 }
 
 /// Namespace for all contract storage keys.
 #[contracttype]
 pub enum DataKey {
-    Config,       // Instance storage for global settings (admin/token).
+    Config,       // Instance storage for global settings (admin/token/limits).
     NextStreamId, // Instance storage for the auto-incrementing ID counter.
+    Initialised,  // Instance storage flag written last by `init`.
     Stream(u64),  // Persistent storage for individual stream data (O(1) lookup).
+    UsedNonce(BytesN<32>), // Persistent: idempotency nonce -> stream id.
+    StreamNonce(u64),      // Persistent: stream id -> nonce it was created with.
+    ByRef(BytesN<32>),     // Persistent: external reference -> stream id.
+    CompletedAt(u64),      // Persistent: stream id -> ledger time it completed.
+    ArchivedStream(u64),   // Persistent: compact record replacing an archived stream.
+    Recurring(u64),        // Persistent: renewal terms of a recurring stream.
+    FeeBps,                // Instance, before data version 5: now `Config::fee_bps`.
+    FeeCollector,          // Instance, before data version 5: now `Config::fee_collector`.
+    FeesCollected(Address), // Instance: cumulative fees taken, per token.
+    SenderStreams(Address),    // Persistent: ids of streams sent by an address.
+    RecipientStreams(Address), // Persistent: ids of streams received by an address.
+    Operator(u64, Address),    // Persistent: operator approved to withdraw for a recipient.
+    CategoryIndex(StreamCategory), // Persistent: ids of streams tagged with a category.
+    ContractVersion,           // Instance: code version, bumped by `upgrade_contract`.
+    UpgradeHistory,            // Persistent: (version, wasm_hash, timestamp) per upgrade.
+    FrozenRecipient(Address),  // Persistent: present while the admin holds a recipient's payouts.
+    ComplianceHook,            // Instance, before data version 5: now in `Config`.
+    WithdrawCallback(u64),     // Persistent: recipient's callback notified after withdrawals.
+    CoCancelProposal(u64),     // Persistent: sender's pending mutual-cancellation offer.
+    Receipt(u64),              // Persistent: current holder of a stream's position receipt.
+    FundingProcessor(Address), // Persistent: sender -> processor allowed to spend its allowance.
+    AutoWithdrawCap(u64),      // Persistent: most a single operator withdrawal may take.
+    PendingAdminCancel(u64),   // Persistent: time a requested admin cancel becomes executable.
+    MinDeposit,                // Instance, before data version 5: now in `Config`.
+    StreamsByStatus(StreamStatus), // Persistent: ascending ids of streams currently in a status.
+    DataVersion,               // Instance: storage layout version (absent = 1).
+    OriginalRecipient(u64),    // Persistent: recipient a stream was created for, once moved.
+    WithdrawAllCursor(Address), // Persistent: recipient index position `withdraw_all` resumes at.
+    PauseAllCursor(Address),   // Persistent: sender index position `pause_all` resumes at.
+    ResumeAllCursor(Address),  // Persistent: sender index position `resume_all` resumes at.
+    ActiveCount,               // Instance: number of streams currently `Active`.
+    LockedTotal,               // Instance: sum of `locked_in` over all streams.
+    TotalWithdrawn,            // Instance: tokens ever withdrawn from streams.
+    MinCliffDuration,          // Instance, before data version 5: now in `Config`.
+    AuditTrail(u64),           // Persistent: latest (status, timestamp) transitions of a stream.
+    MaxPause(u64),             // Persistent: pause length after which anyone may resume a stream.
+    PausedAt(u64),             // Persistent: when a stream with a pause limit was last paused.
+    CancelNotice(u64),         // Persistent: warning, in seconds, a cancellation must give.
+    CancelNoticeAt(u64),       // Persistent: when notice of a pending cancellation was given.
+    ReferenceHash(u64),        // Persistent: 32-byte application reference set by the sender.
+    Disputed(u64),             // Persistent: present while a dispute holds a stream's payouts.
+    RecipientMayDispute,       // Instance, before data version 5: now in `Config`.
+    AdvanceRequest(u64),       // Persistent: amount the recipient asked to be paid early.
+    CancelledAt(u64),          // Persistent: ledger time a stream was cancelled.
+    WithdrawGracePeriod,       // Instance, before data version 5: now in `Config`.
+    AutoResumeAt(u64),         // Persistent: when anyone may end a stream's current pause.
+}
+
+/// Storage keys for `harvest_yield`, kept apart as `DataKey` is at the SDK's
+/// variant limit. Variant names must not clash with `DataKey`'s, as both
+/// encode to the variant name.
+#[contracttype]
+enum YieldKey {
+    HarvestIndex,         // Instance: surplus attributed per locked token, scaled.
+    UnharvestedYield,     // Instance: attributed surplus not yet harvested.
+    YieldCheckpoint(u64), // Persistent: (index, owed yield) at a stream's last settle.
+}
+
+/// Storage keys for operator approvals, kept apart from `DataKey` for the
+/// same reason as `YieldKey`.
+#[contracttype]
+enum OperatorKey {
+    Operators(u64), // Persistent: operators approved on a stream, for revoking them all.
+}
+
+/// Swap venue used by `create_stream_from_swap`.
+///
+/// `swap` must move `amount_in` of `token_in` from `from` (who authorizes it)
+/// and deliver at least `min_out` of `token_out` to `to`.
+#[contractclient(name = "DexClient")]
+pub trait DexInterface {
+    #[allow(clippy::too_many_arguments)]
+    fn swap(
+        env: Env,
+        from: Address,
+        to: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        min_out: i128,
+    ) -> i128;
 }
 
 // ---------------------------------------------------------------------------
@@ -57,6 +494,10 @@ fn get_config(env: &Env) -> Config {
         .expect("contract not initialised: missing config")
 }
 
+fn save_config(env: &Env, config: &Config) {
+    env.storage().instance().set(&DataKey::Config, config);
+}
+
 fn get_token(env: &Env) -> Address {
     get_config(env).token
 }
@@ -76,14 +517,398 @@ fn set_stream_count(env: &Env, count: u64) {
     env.storage().instance().set(&DataKey::NextStreamId, &count);
 }
 
-fn load_stream(env: &Env, stream_id: u64) -> Stream {
+/// Transfers owed by a withdrawal that `settle_withdrawal` has recorded.
+struct Settlement {
+    /// Withdrawal fee, paid to `fee_collector` when one is set.
+    fee: i128,
+    fee_collector: Option<Address>,
+    /// Withheld for a tax authority by `withdraw_with_withholding`.
+    withheld: i128,
+    /// Due to the recipient.
+    payout: i128,
+    /// Unstreamed deposit due to the refund address on completion.
+    dust: i128,
+}
+
+/// Withdrawal fee in basis points and the address it is paid to, if a fee
+/// has been configured.
+fn get_withdrawal_fee(env: &Env) -> Option<(u32, Address)> {
+    let config = get_config(env);
+    Some((config.fee_bps, config.fee_collector?))
+}
+
+/// `amount * bps / 10_000`, rounded down, without intermediate overflow.
+fn bps_of(amount: i128, bps: u32) -> i128 {
+    let bps = bps as i128;
+    let denominator = BPS_DENOMINATOR as i128;
+    (amount / denominator) * bps + (amount % denominator) * bps / denominator
+}
+
+/// `part` as a share of `whole` in basis points, rounded down and capped at
+/// 10000; 0 when `whole` is 0. Falls back to dividing `whole` first where
+/// `part * 10000` would overflow.
+fn share_bps(part: i128, whole: i128) -> u32 {
+    if whole <= 0 {
+        return 0;
+    }
+    let denominator = BPS_DENOMINATOR as i128;
+    let bps = part
+        .checked_mul(denominator)
+        .map_or_else(|| part / (whole / denominator), |scaled| scaled / whole);
+    bps.clamp(0, denominator) as u32
+}
+
+/// Add `amount` to the running tally of fees collected in `token`.
+fn record_fee(env: &Env, token: &Address, amount: i128) {
+    let key = DataKey::FeesCollected(token.clone());
+    let collected: i128 = env.storage().instance().get(&key).unwrap_or(0);
+    env.storage().instance().set(&key, &(collected + amount));
+}
+
+/// Address of the Stellar Asset Contract for native XLM on the current network.
+fn native_asset_address(env: &Env) -> Address {
+    env.deployer()
+        .with_stellar_asset(Bytes::from_array(env, &NATIVE_ASSET_XDR))
+        .deployed_address()
+}
+
+/// Transfer `amount` of the stream token, translating token-contract failures
+/// into [`ContractError::TransferFailed`] instead of an opaque trap.
+fn transfer_token(env: &Env, from: &Address, to: &Address, amount: i128) {
+    let token_client = token::Client::new(env, &get_token(env));
+    match token_client.try_transfer(from, to, &amount) {
+        Ok(Ok(())) => {}
+        _ => panic_with_error!(env, ContractError::TransferFailed),
+    }
+}
+
+/// Pull `amount` of the stream token from `from` into this contract using the
+/// allowance `from` granted the contract. Returns `false` instead of panicking
+/// when the token contract rejects the transfer.
+fn try_pull_token(env: &Env, from: &Address, amount: i128) -> bool {
+    let token_client = token::Client::new(env, &get_token(env));
+    let contract = env.current_contract_address();
+    matches!(
+        token_client.try_transfer_from(&contract, from, &contract, &amount),
+        Ok(Ok(()))
+    )
+}
+
+fn load_recurring(env: &Env, stream_id: u64) -> Option<RecurringConfig> {
+    env.storage().persistent().get(&DataKey::Recurring(stream_id))
+}
+
+fn save_recurring(env: &Env, stream_id: u64, config: &RecurringConfig) {
+    let key = DataKey::Recurring(stream_id);
+    env.storage().persistent().set(&key, config);
+    env.storage().persistent().extend_ttl(&key, 17280, 120960);
+}
+
+fn get_index(env: &Env, key: &DataKey) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(key)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Append `stream_id` to the per-address index stored under `key`.
+fn add_to_index(env: &Env, key: DataKey, stream_id: u64) {
+    let mut ids = get_index(env, &key);
+    ids.push_back(stream_id);
+    env.storage().persistent().set(&key, &ids);
+    env.storage().persistent().extend_ttl(&key, 17280, 120960);
+}
+
+/// Insert `stream_id` into the ascending index stored under `key`, unless it
+/// is already there.
+fn add_to_sorted_index(env: &Env, key: DataKey, stream_id: u64) {
+    let mut ids = get_index(env, &key);
+    if let Err(pos) = ids.binary_search(stream_id) {
+        ids.insert(pos, stream_id);
+        env.storage().persistent().set(&key, &ids);
+        env.storage().persistent().extend_ttl(&key, 17280, 120960);
+    }
+}
+
+/// Drop `stream_id` from the per-address index stored under `key`.
+fn remove_from_index(env: &Env, key: DataKey, stream_id: u64) {
+    let mut ids = get_index(env, &key);
+    if let Some(pos) = ids.first_index_of(stream_id) {
+        ids.remove(pos);
+        env.storage().persistent().set(&key, &ids);
+    }
+}
+
+/// Revoke every operator approved on `stream_id` and drop its operator
+/// withdrawal cap, as both were granted by the position's previous holder.
+fn clear_operators(env: &Env, stream_id: u64) {
+    let list_key = OperatorKey::Operators(stream_id);
+    let operators: Vec<Address> = env
+        .storage()
+        .persistent()
+        .get(&list_key)
+        .unwrap_or_else(|| Vec::new(env));
+    for operator in operators.iter() {
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Operator(stream_id, operator));
+    }
+    env.storage().persistent().remove(&list_key);
+    env.storage()
+        .persistent()
+        .remove(&DataKey::AutoWithdrawCap(stream_id));
+}
+
+/// The next `MAX_STREAM_BATCH` entries of `ids` for a call that works
+/// through an index across several transactions, resuming at the position
+/// stored under `cursor_key` and starting over once the end is reached.
+fn next_batch(env: &Env, cursor_key: DataKey, ids: &Vec<u64>) -> Vec<u64> {
+    let start: u32 = env
+        .storage()
+        .persistent()
+        .get(&cursor_key)
+        .filter(|&start| start < ids.len())
+        .unwrap_or(0);
+    let end = ids.len().min(start + MAX_STREAM_BATCH);
+    if end < ids.len() {
+        env.storage().persistent().set(&cursor_key, &end);
+        env.storage().persistent().extend_ttl(&cursor_key, 17280, 120960);
+    } else {
+        env.storage().persistent().remove(&cursor_key);
+    }
+    ids.slice(start..end)
+}
+
+/// Panic unless `stream`'s cliff is at least the admin's minimum cliff
+/// duration after its start.
+fn check_min_cliff(env: &Env, stream: &Stream) {
+    if let Some(min_cliff) = get_config(env).min_cliff_duration {
+        assert!(
+            stream.cliff_time - stream.start_time >= min_cliff,
+            "cliff below required minimum"
+        );
+    }
+}
+
+/// Whether `stream` is paused and has been for at least the pause limit it
+/// was created with. Always `false` for streams without a limit.
+fn pause_expired(env: &Env, stream: &Stream) -> bool {
+    if stream.status != StreamStatus::Paused {
+        return false;
+    }
+    let storage = env.storage().persistent();
+    let max_pause: Option<u64> = storage.get(&DataKey::MaxPause(stream.stream_id));
+    let paused_at: Option<u64> = storage.get(&DataKey::PausedAt(stream.stream_id));
+    match (max_pause, paused_at) {
+        (Some(max_pause), Some(paused_at)) => {
+            env.ledger().timestamp() >= paused_at.saturating_add(max_pause)
+        }
+        _ => false,
+    }
+}
+
+/// Move `stream` to `status`, keeping the per-status index and the audit
+/// trail in step.
+fn set_status(env: &Env, stream: &mut Stream, status: StreamStatus) {
+    promote_if_started(env, stream.stream_id);
+    remove_from_index(env, DataKey::StreamsByStatus(stream.status), stream.stream_id);
+    add_to_sorted_index(env, DataKey::StreamsByStatus(status), stream.stream_id);
+    record_transition(env, stream.stream_id, status, env.ledger().timestamp());
+    stream.status = status;
+}
+
+/// Append `(status, at)` to the stream's audit trail, dropping the oldest
+/// entry once it holds `MAX_AUDIT_ENTRIES`.
+fn record_transition(env: &Env, stream_id: u64, status: StreamStatus, at: u64) {
+    let key = DataKey::AuditTrail(stream_id);
+    let mut trail: Vec<(StreamStatus, u64)> = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env));
+    if trail.len() == MAX_AUDIT_ENTRIES {
+        trail.pop_front();
+    }
+    trail.push_back((status, at));
+    env.storage().persistent().set(&key, &trail);
+    env.storage().persistent().extend_ttl(&key, 17280, 120960);
+}
+
+/// Whether `stream` is stored as `Scheduled` but its start has been reached.
+fn has_started(env: &Env, stream: &Stream) -> bool {
+    stream.status == StreamStatus::Scheduled && env.ledger().timestamp() >= stream.start_time
+}
+
+/// Persist the start of a stored `Scheduled` stream that `try_load_stream`
+/// already reports as `Active`, stamped at its `start_time`. Runs ahead of
+/// every write to a stream, so the status index, audit trail and `get_stats`
+/// counters take the start in order; views derive it without writing.
+fn promote_if_started(env: &Env, stream_id: u64) {
+    let Some(previous) = env
+        .storage()
+        .persistent()
+        .get::<_, Stream>(&DataKey::Stream(stream_id))
+        .filter(|stream| has_started(env, stream))
+    else {
+        return;
+    };
+    let mut stream = previous.clone();
+    stream.status = StreamStatus::Active;
+    remove_from_index(env, DataKey::StreamsByStatus(StreamStatus::Scheduled), stream_id);
+    add_to_sorted_index(env, DataKey::StreamsByStatus(StreamStatus::Active), stream_id);
+    record_transition(env, stream_id, StreamStatus::Active, stream.start_time);
+    update_stats(env, Some(&previous), &stream);
+    write_stream(env, &stream);
+}
+
+/// Streams stored as `Scheduled` whose start has been reached, ascending.
+fn started_scheduled_ids(env: &Env) -> Vec<u64> {
+    let mut started = Vec::new(env);
+    for stream_id in get_index(env, &DataKey::StreamsByStatus(StreamStatus::Scheduled)).iter() {
+        let stream: Option<Stream> = env.storage().persistent().get(&DataKey::Stream(stream_id));
+        if matches!(stream, Some(stream) if has_started(env, &stream)) {
+            started.push_back(stream_id);
+        }
+    }
+    started
+}
+
+fn is_frozen(env: &Env, recipient: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::FrozenRecipient(recipient.clone()))
+}
+
+fn is_disputed(env: &Env, stream_id: u64) -> bool {
+    env.storage().persistent().has(&DataKey::Disputed(stream_id))
+}
+
+/// Ask the configured compliance hook, if any, whether `amount` may be paid
+/// to `recipient`. The hook can only veto: a `false` answer aborts with
+/// [`ContractError::ComplianceDenied`], and a trapping or malformed hook with
+/// [`ContractError::ComplianceHookFailed`].
+fn check_compliance(env: &Env, recipient: &Address, amount: i128) {
+    let Some(hook) = get_config(env).compliance_hook else {
+        return;
+    };
+
+    let args = (recipient.clone(), amount).into_val(env);
+    let allowed = env.try_invoke_contract::<bool, soroban_sdk::Error>(
+        &hook,
+        &Symbol::new(env, "is_transfer_allowed"),
+        args,
+    );
+    match allowed {
+        Ok(Ok(true)) => {}
+        Ok(Ok(false)) => panic_with_error!(env, ContractError::ComplianceDenied),
+        _ => panic_with_error!(env, ContractError::ComplianceHookFailed),
+    }
+}
+
+/// Notify the stream's withdrawal callback, if the recipient registered one.
+///
+/// Runs only after the withdrawal is persisted. A non-`required` callback
+/// that traps is reported via a `cb_fail` event instead of reverting, so a
+/// broken receiver cannot block withdrawals.
+fn notify_withdraw_callback(env: &Env, stream_id: u64, amount: i128) {
+    let Some(callback) = env
+        .storage()
+        .persistent()
+        .get::<_, WithdrawCallback>(&DataKey::WithdrawCallback(stream_id))
+    else {
+        return;
+    };
+
+    let args = (stream_id, amount).into_val(env);
+    let result = env.try_invoke_contract::<(), soroban_sdk::Error>(
+        &callback.contract,
+        &Symbol::new(env, "on_stream_withdraw"),
+        args,
+    );
+    if !matches!(result, Ok(Ok(()))) {
+        if callback.required {
+            panic_with_error!(env, ContractError::CallbackFailed);
+        }
+        env.events().publish(
+            (symbol_short!("cb_fail"), stream_id),
+            callback.contract,
+        );
+    }
+}
+
+/// Earliest entry of the stream's withdrawal schedule after its last
+/// withdrawal, or `None` if it has no schedule or every entry has passed.
+fn next_withdraw_time(stream: &Stream) -> Option<u64> {
+    let schedule = stream.withdraw_schedule.as_ref()?;
+    schedule
+        .iter()
+        .find(|&time| time > stream.last_withdrawn_at)
+}
+
+/// Drop any pending admin cancel once a stream has reached a terminal state.
+fn clear_pending_admin_cancel(env: &Env, stream_id: u64) {
     env.storage()
         .persistent()
-        .get(&DataKey::Stream(stream_id))
-        .expect("stream not found")
+        .remove(&DataKey::PendingAdminCancel(stream_id));
+}
+
+/// Burn a stream's position receipt, if it has one, once nothing is left to
+/// claim.
+fn burn_receipt(env: &Env, stream_id: u64) {
+    let key = DataKey::Receipt(stream_id);
+    if env.storage().persistent().has(&key) {
+        env.storage().persistent().remove(&key);
+        env.events()
+            .publish((symbol_short!("rcpt_burn"), stream_id), ());
+    }
+}
+
+/// Streams are never sent from or paid to this contract itself.
+fn assert_not_contract(env: &Env, sender: &Address, recipient: &Address) {
+    let contract = env.current_contract_address();
+    assert!(
+        sender != &contract && recipient != &contract,
+        "address must not be the contract"
+    );
+}
+
+fn load_stream(env: &Env, stream_id: u64) -> Stream {
+    try_load_stream(env, stream_id).expect("stream not found")
+}
+
+/// Load a stream, or `None` if it does not exist (or was archived). A
+/// `Scheduled` stream whose start has been reached is returned as `Active`,
+/// so callers only ever see it as started; nothing is written until the
+/// stream is next saved (see `promote_if_started`).
+fn try_load_stream(env: &Env, stream_id: u64) -> Option<Stream> {
+    let mut stream: Stream = env.storage().persistent().get(&DataKey::Stream(stream_id))?;
+    if has_started(env, &stream) {
+        stream.status = StreamStatus::Active;
+    }
+    Some(stream)
+}
+
+/// `Active`, or `Scheduled` while `stream` has yet to start.
+fn running_status(env: &Env, stream: &Stream) -> StreamStatus {
+    if env.ledger().timestamp() < stream.start_time {
+        StreamStatus::Scheduled
+    } else {
+        StreamStatus::Active
+    }
 }
 
 fn save_stream(env: &Env, stream: &Stream) {
+    promote_if_started(env, stream.stream_id);
+    let key = DataKey::Stream(stream.stream_id);
+    let previous: Option<Stream> = env.storage().persistent().get(&key);
+    update_stats(env, previous.as_ref(), stream);
+    settle_yield(env, previous.as_ref(), stream);
+    write_stream(env, stream);
+}
+
+/// Store `stream` without touching the `get_stats` counters, for migrations
+/// that recount them afterwards.
+pub(crate) fn write_stream(env: &Env, stream: &Stream) {
     let key = DataKey::Stream(stream.stream_id);
     env.storage().persistent().set(&key, stream);
 
@@ -91,6 +916,163 @@ fn save_stream(env: &Env, stream: &Stream) {
     env.storage().persistent().extend_ttl(&key, 17280, 120960);
 }
 
+/// What the contract holds for `stream`: nothing once it has completed (any
+/// unstreamed dust is refunded then), otherwise its unwithdrawn deposit.
+pub(crate) fn locked_in(stream: &Stream) -> i128 {
+    if stream.status == StreamStatus::Completed {
+        0
+    } else {
+        stream.deposit_amount - stream.withdrawn_amount
+    }
+}
+
+/// Attribute any surplus the contract holds beyond locked and already
+/// attributed balances to the streams locking tokens now, by raising the
+/// yield index. Returns the index.
+fn accrue_yield(env: &Env) -> i128 {
+    let contract = env.current_contract_address();
+    let balance = token::Client::new(env, &get_token(env)).balance(&contract);
+    let total_locked = instance_total(env, &DataKey::LockedTotal);
+    let reserved = yield_total(env, &YieldKey::UnharvestedYield);
+    let surplus = balance - total_locked - reserved;
+    let mut index = yield_total(env, &YieldKey::HarvestIndex);
+    if surplus > 0 && total_locked > 0 {
+        index += surplus
+            .checked_mul(YIELD_SCALE)
+            .expect("yield index overflow")
+            / total_locked;
+        env.storage().instance().set(&YieldKey::HarvestIndex, &index);
+        env.storage()
+            .instance()
+            .set(&YieldKey::UnharvestedYield, &(reserved + surplus));
+    }
+    index
+}
+
+/// Yield owed to `stream` at `index`: what was settled at its checkpoint plus
+/// its locked balance times the index growth since.
+fn pending_yield(env: &Env, stream: &Stream, index: i128) -> i128 {
+    let (checkpoint, owed): (i128, i128) = env
+        .storage()
+        .persistent()
+        .get(&YieldKey::YieldCheckpoint(stream.stream_id))
+        .unwrap_or((0, 0));
+    let growth = index - checkpoint;
+    let locked = locked_in(stream);
+    // locked * growth / YIELD_SCALE without overflowing the product.
+    owed + (locked / YIELD_SCALE) * growth + (locked % YIELD_SCALE) * growth / YIELD_SCALE
+}
+
+fn set_yield_checkpoint(env: &Env, stream_id: u64, index: i128, owed: i128) {
+    let key = YieldKey::YieldCheckpoint(stream_id);
+    env.storage().persistent().set(&key, &(index, owed));
+    env.storage().persistent().extend_ttl(&key, 17280, 120960);
+}
+
+/// Before a stream's locked balance changes, bank the yield its old balance
+/// earned so the new balance only earns index growth from here on.
+fn settle_yield(env: &Env, previous: Option<&Stream>, stream: &Stream) {
+    let index = yield_total(env, &YieldKey::HarvestIndex);
+    if index == 0 {
+        return;
+    }
+    let owed = match previous {
+        None => 0,
+        Some(p) if locked_in(p) == locked_in(stream) => return,
+        Some(p) => pending_yield(env, p, index),
+    };
+    set_yield_checkpoint(env, stream.stream_id, index, owed);
+}
+
+fn yield_total(env: &Env, key: &YieldKey) -> i128 {
+    env.storage().instance().get(key).unwrap_or(0)
+}
+
+fn instance_total(env: &Env, key: &DataKey) -> i128 {
+    env.storage().instance().get(key).unwrap_or(0)
+}
+
+/// Apply the change from `previous` (absent for a new stream) to `stream` to
+/// the counters behind `get_stats`.
+fn update_stats(env: &Env, previous: Option<&Stream>, stream: &Stream) {
+    let was_active = matches!(previous, Some(p) if p.status == StreamStatus::Active);
+    let is_active = stream.status == StreamStatus::Active;
+    if was_active != is_active {
+        let count: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ActiveCount)
+            .unwrap_or(0);
+        let count = if is_active { count + 1 } else { count - 1 };
+        env.storage().instance().set(&DataKey::ActiveCount, &count);
+    }
+
+    let (locked_before, withdrawn_before) =
+        previous.map_or((0, 0), |p| (locked_in(p), p.withdrawn_amount));
+    // Both totals could exceed i128 across many large streams; panic rather
+    // than wrap.
+    let locked = locked_in(stream) - locked_before;
+    if locked != 0 {
+        let total = instance_total(env, &DataKey::LockedTotal)
+            .checked_add(locked)
+            .expect("locked total overflow");
+        env.storage().instance().set(&DataKey::LockedTotal, &total);
+    }
+    let withdrawn = stream.withdrawn_amount - withdrawn_before;
+    if withdrawn != 0 {
+        let total = instance_total(env, &DataKey::TotalWithdrawn)
+            .checked_add(withdrawn)
+            .expect("withdrawn total overflow");
+        env.storage().instance().set(&DataKey::TotalWithdrawn, &total);
+    }
+}
+
+/// Total amount the stream's schedule will ever release to the recipient:
+/// `min(rate_per_second * (end_time - start_time), deposit_amount)`, or the
+/// whole deposit for stepped streams.
+fn total_streamable(stream: &Stream) -> i128 {
+    if stream.step_seconds > 0 || !stream.milestones.is_empty() {
+        return stream.deposit_amount;
+    }
+    let duration = (stream.end_time - stream.start_time) as i128;
+    stream
+        .rate_per_second
+        .checked_mul(duration)
+        .and_then(|scheduled| scheduled.checked_add(stream.accrued_checkpoint))
+        .unwrap_or(stream.deposit_amount)
+        .min(stream.deposit_amount)
+}
+
+/// Check that `start_time..end_time` is a non-empty schedule and that
+/// `deposit_amount` covers `rate_per_second` over all of it. Shared by every
+/// path that sets or changes a stream's rate, deposit or schedule.
+///
+/// # Panics
+/// - If `start_time >= end_time`.
+/// - If `rate_per_second * (end_time - start_time)` overflows or exceeds
+///   `deposit_amount`.
+fn validate_coverage(deposit_amount: i128, rate_per_second: i128, start_time: u64, end_time: u64) {
+    assert!(start_time < end_time, "start_time must be before end_time");
+    let duration = (end_time - start_time) as i128;
+    let total_streamable = rate_per_second
+        .checked_mul(duration)
+        .expect("overflow calculating total streamable amount");
+    assert!(
+        deposit_amount >= total_streamable,
+        "deposit_amount must cover total streamable amount (rate * duration)"
+    );
+}
+
+/// Drop the idempotency nonce (if any) a stream was created with, so it can no
+/// longer be resolved once the stream has completed.
+fn clear_stream_nonce(env: &Env, stream_id: u64) {
+    let key = DataKey::StreamNonce(stream_id);
+    if let Some(nonce) = env.storage().persistent().get::<_, BytesN<32>>(&key) {
+        env.storage().persistent().remove(&DataKey::UsedNonce(nonce));
+        env.storage().persistent().remove(&key);
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Contract Implementation
 // ---------------------------------------------------------------------------
@@ -103,12 +1085,51 @@ impl FluxoraStream {
     /// Initialise the contract with the streaming token and admin address.
     /// Can only be called once. Sets up global Config and ID counter.
     pub fn init(env: Env, token: Address, admin: Address) {
-        if env.storage().instance().has(&DataKey::Config) {
+        Self::initialise(&env, token, admin);
+    }
+
+    /// Initialise the contract to stream native XLM.
+    ///
+    /// Resolves the native asset's Stellar Asset Contract address for the
+    /// current network and stores it as the stream token, so deployers don't
+    /// need to look it up per network. Otherwise identical to `init`.
+    pub fn init_native(env: Env, admin: Address) {
+        let token = native_asset_address(&env);
+        Self::initialise(&env, token, admin);
+    }
+
+    /// Shared initialisation logic for `init` and `init_native`.
+    fn initialise(env: &Env, token: Address, admin: Address) {
+        if env.storage().instance().has(&DataKey::Initialised) {
             panic!("already initialised");
         }
-        let config = Config { token, admin };
-        env.storage().instance().set(&DataKey::Config, &config);
+        // Any config without the flag means an earlier write sequence was
+        // interrupted; refuse rather than layer a second config over it.
+        assert!(
+            !env.storage().instance().has(&DataKey::Config)
+                && !env.storage().instance().has(&DataKey::NextStreamId),
+            "contract partially initialised"
+        );
+
+        let config = Config {
+            token,
+            admin,
+            admin_cancel_delay: DEFAULT_ADMIN_CANCEL_DELAY,
+            fee_bps: 0,
+            fee_collector: None,
+            min_deposit: None,
+            min_cliff_duration: None,
+            withdraw_grace_period: DEFAULT_WITHDRAW_GRACE_PERIOD,
+            compliance_hook: None,
+            recipient_may_dispute: false,
+        };
+        save_config(env, &config);
         env.storage().instance().set(&DataKey::NextStreamId, &0u64);
+        env.storage()
+            .instance()
+            .set(&DataKey::DataVersion, &migration::DATA_VERSION);
+        // Written last so its presence implies every key above is in place
+        env.storage().instance().set(&DataKey::Initialised, &true);
 
         // Ensure instance storage (Config/ID) doesn't expire quickly
         env.storage().instance().extend_ttl(17280, 120960);
@@ -122,10 +1143,17 @@ impl FluxoraStream {
     /// # Panics
     /// - If `deposit_amount` or `rate_per_second` is not positive.
     /// - If `sender` and `recipient` are the same address.
+    /// - If `sender` or `recipient` is this contract.
     /// - If `start_time >= end_time`.
     /// - If `cliff_time` is not in `[start_time, end_time]`.
     /// - If `deposit_amount < rate_per_second * (end_time - start_time)` (insufficient deposit).
+    /// - If `deposit_amount` is below the minimum set via `set_min_deposit`.
+    /// - If `memo` is longer than 64 bytes.
+    /// - If `external_ref` is already used by another stream.
     /// - If token transfer fails (e.g., insufficient balance or allowance).
+    ///
+    /// Deprecated: new callers should use `create_stream_v2`. Kept for
+    /// existing integrations.
     #[allow(clippy::too_many_arguments)]
     pub fn create_stream(
         env: Env,
@@ -136,256 +1164,3903 @@ impl FluxoraStream {
         start_time: u64,
         cliff_time: u64,
         end_time: u64,
+        memo: Bytes,
+        external_ref: Option<BytesN<32>>,
     ) -> u64 {
-        sender.require_auth();
-
+        Self::create_stream_with_refund(
+            env,
+            sender.clone(),
+            recipient,
+            deposit_amount,
+            rate_per_second,
+            start_time,
+            cliff_time,
+            end_time,
+            memo,
+            external_ref,
+            sender,
+        )
+    }
+
+    /// Create a stream from `params`, with no memo or external reference.
+    /// Otherwise identical to `create_stream`, including when it panics.
+    pub fn create_stream_v2(env: Env, sender: Address, params: StreamParameters) -> u64 {
+        Self::create_stream(
+            env.clone(),
+            sender,
+            params.recipient,
+            params.deposit_amount,
+            params.rate_per_second,
+            params.start_time,
+            params.cliff_time,
+            params.end_time,
+            Bytes::new(&env),
+            None,
+        )
+    }
+
+    /// Create a stream exactly like `create_stream`, returning the persisted
+    /// `Stream` instead of only its id.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stream_full(
+        env: Env,
+        sender: Address,
+        recipient: Address,
+        deposit_amount: i128,
+        rate_per_second: i128,
+        start_time: u64,
+        cliff_time: u64,
+        end_time: u64,
+        memo: Bytes,
+        external_ref: Option<BytesN<32>>,
+    ) -> Stream {
+        let stream_id = Self::create_stream(
+            env.clone(),
+            sender,
+            recipient,
+            deposit_amount,
+            rate_per_second,
+            start_time,
+            cliff_time,
+            end_time,
+            memo,
+            external_ref,
+        );
+        load_stream(&env, stream_id)
+    }
+
+    /// Create a stream whose refunds go to `refund_address` instead of the
+    /// sender.
+    ///
+    /// Identical to `create_stream` except that unstreamed funds returned on
+    /// cancellation, and dust returned on completion, are sent to
+    /// `refund_address`. Only the sender may still pause or cancel.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stream_with_refund(
+        env: Env,
+        sender: Address,
+        recipient: Address,
+        deposit_amount: i128,
+        rate_per_second: i128,
+        start_time: u64,
+        cliff_time: u64,
+        end_time: u64,
+        memo: Bytes,
+        external_ref: Option<BytesN<32>>,
+        refund_address: Address,
+    ) -> u64 {
+        let stream = Self::build_stream(
+            sender,
+            recipient,
+            deposit_amount,
+            rate_per_second,
+            start_time,
+            cliff_time,
+            end_time,
+            memo,
+            refund_address,
+        );
+        Self::open_stream(&env, stream, external_ref)
+    }
+
+    /// Create a stream paid for by `funder` on behalf of `sender`.
+    ///
+    /// The deposit is taken from `funder`, whose auth is required. `sender`
+    /// is the stream's controlling party with the usual pause, resume and
+    /// cancel rights; the funder gets none. Cancellation refunds and
+    /// completion dust go to `funder` if `refund_to_funder`, else to `sender`.
+    /// Emits a `sponsored` event carrying `(funder, sender)` after `created`.
+    ///
+    /// # Panics
+    /// - If `funder` is this contract.
+    /// - Under any condition in which `create_stream` would panic.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_sponsored_stream(
+        env: Env,
+        funder: Address,
+        sender: Address,
+        recipient: Address,
+        deposit_amount: i128,
+        rate_per_second: i128,
+        start_time: u64,
+        cliff_time: u64,
+        end_time: u64,
+        memo: Bytes,
+        refund_to_funder: bool,
+    ) -> u64 {
+        funder.require_auth();
+        assert_not_contract(&env, &funder, &recipient);
+
+        let refund_address = if refund_to_funder {
+            funder.clone()
+        } else {
+            sender.clone()
+        };
+        let stream = Self::build_stream(
+            sender.clone(),
+            recipient,
+            deposit_amount,
+            rate_per_second,
+            start_time,
+            cliff_time,
+            end_time,
+            memo,
+            refund_address,
+        );
+        assert_not_contract(&env, &stream.sender, &stream.recipient);
+
+        transfer_token(
+            &env,
+            &funder,
+            &env.current_contract_address(),
+            deposit_amount,
+        );
+        let stream_id = Self::record_stream(&env, stream, None);
+
+        env.events()
+            .publish((symbol_short!("sponsored"), stream_id), (funder, sender));
+        stream_id
+    }
+
+    /// Let `processor` open streams funded from the allowance `sender` has
+    /// granted this contract (see `create_stream_from_allowance`), or with
+    /// `None` revoke it. Requires the sender's auth and replaces any previous
+    /// processor.
+    pub fn set_funding_processor(env: Env, sender: Address, processor: Option<Address>) {
+        sender.require_auth();
+
+        let key = DataKey::FundingProcessor(sender.clone());
+        match &processor {
+            Some(processor) => {
+                env.storage().persistent().set(&key, processor);
+                env.storage().persistent().extend_ttl(&key, 17280, 120960);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+
+        env.events()
+            .publish((symbol_short!("proc_set"), sender), processor);
+    }
+
+    /// Create a stream on `sender`'s behalf, funded by pulling the deposit
+    /// through the token allowance `sender` granted this contract.
+    ///
+    /// Only `processor`'s auth is required, and it must be the processor the
+    /// sender registered with `set_funding_processor`. Each stream consumes
+    /// `deposit_amount` of the allowance. Refunds go to the sender.
+    ///
+    /// # Panics
+    /// - If `processor` is not the sender's registered processor.
+    /// - With [`ContractError::InsufficientAllowance`] if the allowance does
+    ///   not cover `deposit_amount`.
+    /// - With [`ContractError::TransferFailed`] if the token contract rejects
+    ///   the pull (e.g. insufficient balance).
+    /// - Under any condition in which `create_stream` would panic.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stream_from_allowance(
+        env: Env,
+        processor: Address,
+        sender: Address,
+        recipient: Address,
+        deposit_amount: i128,
+        rate_per_second: i128,
+        start_time: u64,
+        cliff_time: u64,
+        end_time: u64,
+        memo: Bytes,
+    ) -> u64 {
+        processor.require_auth();
+        let approved: Option<Address> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::FundingProcessor(sender.clone()));
+        assert!(
+            approved.as_ref() == Some(&processor),
+            "processor not approved by sender"
+        );
+
+        let stream = Self::build_stream(
+            sender.clone(),
+            recipient,
+            deposit_amount,
+            rate_per_second,
+            start_time,
+            cliff_time,
+            end_time,
+            memo,
+            sender.clone(),
+        );
+        assert_not_contract(&env, &stream.sender, &stream.recipient);
+
+        let allowance = token::Client::new(&env, &get_token(&env))
+            .allowance(&sender, &env.current_contract_address());
+        if allowance < deposit_amount {
+            panic_with_error!(&env, ContractError::InsufficientAllowance);
+        }
+        if !try_pull_token(&env, &sender, deposit_amount) {
+            panic_with_error!(&env, ContractError::TransferFailed);
+        }
+
+        Self::record_stream(&env, stream, None)
+    }
+
+    /// Create a stream whose recipient may only withdraw on the given dates
+    /// (e.g. quarterly).
+    ///
+    /// Identical to `create_stream` otherwise. A withdrawal is allowed once
+    /// the first `withdraw_schedule` entry after the previous withdrawal has
+    /// been reached; it then pays out everything accrued so far. After the
+    /// last entry has been claimed, withdrawals are no longer restricted.
+    ///
+    /// # Panics
+    /// - If `withdraw_schedule` is empty, longer than 100 entries, or not
+    ///   strictly increasing.
+    /// - Under any condition in which `create_stream` would panic.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stream_with_schedule(
+        env: Env,
+        sender: Address,
+        recipient: Address,
+        deposit_amount: i128,
+        rate_per_second: i128,
+        start_time: u64,
+        cliff_time: u64,
+        end_time: u64,
+        memo: Bytes,
+        withdraw_schedule: Vec<u64>,
+    ) -> u64 {
+        assert!(
+            !withdraw_schedule.is_empty(),
+            "withdraw_schedule must not be empty"
+        );
+        assert!(
+            withdraw_schedule.len() <= MAX_WITHDRAW_SCHEDULE_LEN,
+            "withdraw_schedule exceeds 100 entries"
+        );
+        for i in 1..withdraw_schedule.len() {
+            assert!(
+                withdraw_schedule.get_unchecked(i) > withdraw_schedule.get_unchecked(i - 1),
+                "withdraw_schedule must be strictly increasing"
+            );
+        }
+
+        let mut stream = Self::build_stream(
+            sender.clone(),
+            recipient,
+            deposit_amount,
+            rate_per_second,
+            start_time,
+            cliff_time,
+            end_time,
+            memo,
+            sender,
+        );
+        stream.withdraw_schedule = Some(withdraw_schedule);
+        Self::open_stream(&env, stream, None)
+    }
+
+    /// Create a stream and mint a transferable receipt for the recipient's
+    /// position.
+    ///
+    /// Identical to `create_stream` otherwise. Whoever holds the receipt is
+    /// the stream's recipient: `transfer_receipt` moves it, together with the
+    /// right to withdraw everything not yet withdrawn. The receipt is burned
+    /// when the stream completes or is settled by `confirm_co_cancel`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stream_with_receipt(
+        env: Env,
+        sender: Address,
+        recipient: Address,
+        deposit_amount: i128,
+        rate_per_second: i128,
+        start_time: u64,
+        cliff_time: u64,
+        end_time: u64,
+        memo: Bytes,
+    ) -> u64 {
+        let stream_id = Self::create_stream(
+            env.clone(),
+            sender,
+            recipient.clone(),
+            deposit_amount,
+            rate_per_second,
+            start_time,
+            cliff_time,
+            end_time,
+            memo,
+            None,
+        );
+
+        let key = DataKey::Receipt(stream_id);
+        env.storage().persistent().set(&key, &recipient);
+        env.storage().persistent().extend_ttl(&key, 17280, 120960);
+        env.events()
+            .publish((symbol_short!("rcpt_mint"), stream_id), recipient);
+        stream_id
+    }
+
+    /// Create a stream that cannot stay paused for longer than
+    /// `max_pause_seconds`.
+    ///
+    /// Identical to `create_stream` otherwise. Once a pause has lasted that
+    /// long, anyone may end it with `resume_if_expired`, and the recipient
+    /// can withdraw as if the stream were running, which resumes it.
+    ///
+    /// # Panics
+    /// - If `max_pause_seconds` is 0.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stream_with_max_pause(
+        env: Env,
+        sender: Address,
+        recipient: Address,
+        deposit_amount: i128,
+        rate_per_second: i128,
+        start_time: u64,
+        cliff_time: u64,
+        end_time: u64,
+        memo: Bytes,
+        max_pause_seconds: u64,
+    ) -> u64 {
+        assert!(max_pause_seconds > 0, "max_pause_seconds must be positive");
+        let stream_id = Self::create_stream(
+            env.clone(),
+            sender,
+            recipient,
+            deposit_amount,
+            rate_per_second,
+            start_time,
+            cliff_time,
+            end_time,
+            memo,
+            None,
+        );
+
+        let key = DataKey::MaxPause(stream_id);
+        env.storage().persistent().set(&key, &max_pause_seconds);
+        env.storage().persistent().extend_ttl(&key, 17280, 120960);
+        stream_id
+    }
+
+    /// Create a stream whose cancellation takes effect only
+    /// `cancel_notice_seconds` after it is announced.
+    ///
+    /// Identical to `create_stream` otherwise. `cancel_stream` then records a
+    /// notice instead of settling; the stream keeps accruing until anyone
+    /// calls `finalize_cancel` once the notice has run.
+    ///
+    /// # Panics
+    /// - If `cancel_notice_seconds` is 0.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stream_with_cancel_notice(
+        env: Env,
+        sender: Address,
+        recipient: Address,
+        deposit_amount: i128,
+        rate_per_second: i128,
+        start_time: u64,
+        cliff_time: u64,
+        end_time: u64,
+        memo: Bytes,
+        cancel_notice_seconds: u64,
+    ) -> u64 {
+        assert!(
+            cancel_notice_seconds > 0,
+            "cancel_notice_seconds must be positive"
+        );
+        let stream_id = Self::create_stream(
+            env.clone(),
+            sender,
+            recipient,
+            deposit_amount,
+            rate_per_second,
+            start_time,
+            cliff_time,
+            end_time,
+            memo,
+            None,
+        );
+
+        let key = DataKey::CancelNotice(stream_id);
+        env.storage().persistent().set(&key, &cancel_notice_seconds);
+        env.storage().persistent().extend_ttl(&key, 17280, 120960);
+        stream_id
+    }
+
+    /// Create a stream that the recipient has agreed to receive. Requires
+    /// the recipient's auth in addition to the sender's; identical to
+    /// `create_stream` otherwise.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stream_mutual_consent(
+        env: Env,
+        sender: Address,
+        recipient: Address,
+        deposit_amount: i128,
+        rate_per_second: i128,
+        start_time: u64,
+        cliff_time: u64,
+        end_time: u64,
+        memo: Bytes,
+    ) -> u64 {
+        recipient.require_auth();
+        Self::create_stream(
+            env,
+            sender,
+            recipient,
+            deposit_amount,
+            rate_per_second,
+            start_time,
+            cliff_time,
+            end_time,
+            memo,
+            None,
+        )
+    }
+
+    /// Create a stream funded by swapping `input_amount` of `input_token` into
+    /// the stream token through `dex_contract`.
+    ///
+    /// The swap delivers straight to this contract; the amount actually
+    /// received (measured from this contract's balance, not the DEX's return
+    /// value) becomes `deposit_amount`. The swap runs before a stream id is
+    /// allocated, so any failure leaves no state behind.
+    ///
+    /// # Panics
+    /// - If fewer than `min_deposit_received` stream tokens arrive.
+    /// - Under any condition in which `create_stream` would panic for a
+    ///   deposit of the received amount.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stream_from_swap(
+        env: Env,
+        sender: Address,
+        recipient: Address,
+        input_token: Address,
+        input_amount: i128,
+        min_deposit_received: i128,
+        rate_per_second: i128,
+        start_time: u64,
+        cliff_time: u64,
+        end_time: u64,
+        dex_contract: Address,
+    ) -> u64 {
+        assert_not_contract(&env, &sender, &recipient);
+        sender.require_auth();
+        assert!(input_amount > 0, "input_amount must be positive");
+
+        let stream_token = token::Client::new(&env, &get_token(&env));
+        let contract = env.current_contract_address();
+        let balance_before = stream_token.balance(&contract);
+        DexClient::new(&env, &dex_contract).swap(
+            &sender,
+            &contract,
+            &input_token,
+            &stream_token.address,
+            &input_amount,
+            &min_deposit_received,
+        );
+        let received = stream_token.balance(&contract) - balance_before;
+        assert!(
+            received >= min_deposit_received,
+            "swap returned less than min_deposit_received"
+        );
+
+        let stream = Self::build_stream(
+            sender.clone(),
+            recipient,
+            received,
+            rate_per_second,
+            start_time,
+            cliff_time,
+            end_time,
+            Bytes::new(&env),
+            sender,
+        );
+        Self::record_stream(&env, stream, None)
+    }
+
+    /// Create a stream at most once per `nonce`.
+    ///
+    /// Behaves like `create_stream` on first use of `nonce` and records the
+    /// nonce -> stream id mapping. Re-submitting with the same nonce returns
+    /// the existing stream id without creating a stream or moving tokens. The
+    /// mapping is dropped once the stream completes.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stream_idempotent(
+        env: Env,
+        sender: Address,
+        recipient: Address,
+        deposit_amount: i128,
+        rate_per_second: i128,
+        start_time: u64,
+        cliff_time: u64,
+        end_time: u64,
+        memo: Bytes,
+        external_ref: Option<BytesN<32>>,
+        nonce: BytesN<32>,
+    ) -> u64 {
+        let nonce_key = DataKey::UsedNonce(nonce.clone());
+        if let Some(stream_id) = env.storage().persistent().get(&nonce_key) {
+            return stream_id;
+        }
+
+        let stream_id = Self::create_stream(
+            env.clone(),
+            sender,
+            recipient,
+            deposit_amount,
+            rate_per_second,
+            start_time,
+            cliff_time,
+            end_time,
+            memo,
+            external_ref,
+        );
+
+        let id_key = DataKey::StreamNonce(stream_id);
+        env.storage().persistent().set(&nonce_key, &stream_id);
+        env.storage().persistent().set(&id_key, &nonce);
+        env.storage().persistent().extend_ttl(&nonce_key, 17280, 120960);
+        env.storage().persistent().extend_ttl(&id_key, 17280, 120960);
+
+        stream_id
+    }
+
+    /// Create a new stream with the same terms as `stream_id`, shifted to
+    /// start at `new_start_time`.
+    ///
+    /// Copies the recipient, deposit, rate, cliff offset, duration, unlock
+    /// step, memo and refund address, and transfers a fresh deposit from the original
+    /// sender, who must authorize. The source stream may be in any status and
+    /// is left untouched. Its `external_ref` is not copied since references
+    /// are unique. Returns the new stream id.
+    ///
+    /// Cancelling a stream cuts its deposit down to what had accrued, so the
+    /// clone's deposit is raised to whatever its full schedule needs
+    /// (`rate * duration`, or the last milestone) when the source's falls short.
+    ///
+    /// A `withdraw_schedule` is shifted along with the rest of the schedule:
+    /// each date moves by `new_start_time - start_time` of the source, so the
+    /// clone keeps the same claim dates relative to its start.
+    ///
+    /// # Panics
+    /// - If the source stream does not exist.
+    /// - If the shifted schedule or withdrawal dates overflow `u64`.
+    /// - If token transfer fails.
+    pub fn clone_stream(env: Env, stream_id: u64, new_start_time: u64) -> u64 {
+        let source = load_stream(&env, stream_id);
+
+        let cliff_offset = source.cliff_time - source.start_time;
+        let duration = source.end_time - source.start_time;
+        let cliff_time = new_start_time
+            .checked_add(cliff_offset)
+            .expect("overflow shifting cliff_time");
+        let end_time = new_start_time
+            .checked_add(duration)
+            .expect("overflow shifting end_time");
+        let deposit_amount = match source.milestones.last() {
+            // Milestone streams have no rate or duration to validate; covering
+            // the last milestone is all they need
+            Some(total) => source.deposit_amount.max(total),
+            None => {
+                let full_deposit = source
+                    .rate_per_second
+                    .checked_mul(duration as i128)
+                    .expect("overflow calculating total streamable amount");
+                let deposit_amount = source.deposit_amount.max(full_deposit);
+                validate_coverage(deposit_amount, source.rate_per_second, new_start_time, end_time);
+                deposit_amount
+            }
+        };
+        let withdraw_schedule = source.withdraw_schedule.as_ref().map(|schedule| {
+            let mut shifted = Vec::new(&env);
+            for time in schedule.iter() {
+                let time = if new_start_time >= source.start_time {
+                    time.checked_add(new_start_time - source.start_time)
+                } else {
+                    time.checked_sub(source.start_time - new_start_time)
+                };
+                shifted.push_back(time.expect("overflow shifting withdraw_schedule"));
+            }
+            shifted
+        });
+
+        let stream = Stream {
+            stream_id: 0, // assigned by `record_stream`
+            deposit_amount,
+            withdraw_schedule,
+            start_time: new_start_time,
+            cliff_time,
+            end_time,
+            withdrawn_amount: 0,
+            status: StreamStatus::Active,
+            external_ref: None,
+            immutable: false,
+            delegate: None,
+            delegate_can_cancel: false,
+            milestones_approved: 0,
+            last_withdrawn_at: 0,
+            accrued_checkpoint: 0,
+            ..source
+        };
+        Self::open_stream(&env, stream, None)
+    }
+
+    /// Create a subscription-style stream that renews every `period_seconds`.
+    ///
+    /// The first period runs from `start_time` with `period_deposit` taken
+    /// from the sender now, at `rate_per_second = period_deposit / period_seconds`.
+    /// Later periods are funded by `roll_period`, which pulls from the
+    /// allowance the sender grants this contract. The stream never completes
+    /// on its own while active; the sender ends it with `cancel_stream`.
+    ///
+    /// # Panics
+    /// - If `period_seconds` is zero.
+    /// - If `period_deposit < period_seconds` (the rate would round to zero).
+    /// - If `period_deposit` is not a multiple of `period_seconds`, as the
+    ///   rounded-down rate would leave part of every period's deposit
+    ///   unstreamed.
+    /// - Under any condition in which `create_stream` would panic.
+    pub fn create_recurring_stream(
+        env: Env,
+        sender: Address,
+        recipient: Address,
+        period_deposit: i128,
+        period_seconds: u64,
+        start_time: u64,
+    ) -> u64 {
+        assert!(period_seconds > 0, "period_seconds must be positive");
+        assert!(
+            period_deposit % period_seconds as i128 == 0,
+            "period_deposit must be a multiple of period_seconds"
+        );
+        let end_time = start_time
+            .checked_add(period_seconds)
+            .expect("overflow calculating end_time");
+
+        let stream_id = Self::create_stream_with_refund(
+            env.clone(),
+            sender.clone(),
+            recipient,
+            period_deposit,
+            period_deposit / period_seconds as i128,
+            start_time,
+            start_time,
+            end_time,
+            Bytes::new(&env),
+            None,
+            sender,
+        );
+
+        let config = RecurringConfig {
+            period_seconds,
+            period_deposit,
+            periods_funded: 1,
+            lapsed: false,
+        };
+        save_recurring(&env, stream_id, &config);
+
+        stream_id
+    }
+
+    /// Fund and open the next period of a recurring stream. Callable by anyone
+    /// once the current period has elapsed.
+    ///
+    /// Pulls `period_deposit` from the sender's allowance to this contract and
+    /// opens a new period of `period_seconds` starting now; accrual carries
+    /// over continuously, so nothing owed from earlier periods is lost. If the
+    /// pull fails the stream is marked lapsed and `false` is returned; a later
+    /// call retries once the sender has re-funded. Returns `true` on renewal.
+    ///
+    /// # Panics
+    /// - If the stream is not recurring.
+    /// - If the stream is not `Active`.
+    /// - If the current period has not yet elapsed.
+    pub fn roll_period(env: Env, stream_id: u64) -> bool {
+        let mut stream = load_stream(&env, stream_id);
+        let mut config = load_recurring(&env, stream_id).expect("stream is not recurring");
+
+        assert!(
+            stream.status == StreamStatus::Active,
+            "stream must be active to roll"
+        );
+        let now = env.ledger().timestamp();
+        assert!(now >= stream.end_time, "current period has not elapsed");
+
+        if !try_pull_token(&env, &stream.sender, config.period_deposit) {
+            config.lapsed = true;
+            save_recurring(&env, stream_id, &config);
+            env.events()
+                .publish((symbol_short!("lapsed"), stream_id), config.periods_funded);
+            return false;
+        }
+
+        // Slide the schedule so the new period starts now; shifting start by
+        // the same gap keeps the amount accrued so far unchanged.
+        let gap = now - stream.end_time;
+        stream.start_time += gap;
+        stream.cliff_time += gap;
+        stream.end_time = now + config.period_seconds;
+        stream.deposit_amount += config.period_deposit;
+        save_stream(&env, &stream);
+
+        config.periods_funded += 1;
+        config.lapsed = false;
+        save_recurring(&env, stream_id, &config);
+
+        env.events().publish(
+            (symbol_short!("renewed"), stream_id),
+            (config.period_deposit, stream.end_time),
+        );
+        true
+    }
+
+    /// Create a payroll-style stream that unlocks `per_period_amount` at each
+    /// of `periods` boundaries, the first at `first_unlock` and then every
+    /// `period_seconds`.
+    ///
+    /// Takes a deposit of `per_period_amount * periods`. Nothing becomes
+    /// withdrawable between boundaries. `rate_per_second` is stored as the
+    /// average rate rounded down and is informational only.
+    ///
+    /// # Panics
+    /// - If `per_period_amount`, `period_seconds` or `periods` is not positive.
+    /// - If `per_period_amount * periods` or the schedule length overflows.
+    /// - If `first_unlock < period_seconds`.
+    /// - If `sender` and `recipient` are the same address.
+    /// - If token transfer fails.
+    pub fn create_monthly_stream(
+        env: Env,
+        sender: Address,
+        recipient: Address,
+        per_period_amount: i128,
+        period_seconds: u64,
+        periods: u32,
+        first_unlock: u64,
+    ) -> u64 {
+        assert!(per_period_amount > 0, "per_period_amount must be positive");
+        assert!(period_seconds > 0, "period_seconds must be positive");
+        assert!(periods > 0, "periods must be positive");
+        assert!(
+            sender != recipient,
+            "sender and recipient must be different"
+        );
+
+        let deposit_amount = per_period_amount
+            .checked_mul(periods as i128)
+            .expect("overflow calculating deposit_amount");
+        let duration = period_seconds
+            .checked_mul(periods as u64)
+            .expect("overflow calculating schedule length");
+        let start_time = first_unlock
+            .checked_sub(period_seconds)
+            .expect("first_unlock must be at least period_seconds");
+        let end_time = start_time
+            .checked_add(duration)
+            .expect("overflow calculating end_time");
+
+        let stream = Stream {
+            stream_id: 0, // assigned by `record_stream`
+            sender: sender.clone(),
+            recipient,
+            deposit_amount,
+            rate_per_second: per_period_amount / period_seconds as i128,
+            start_time,
+            cliff_time: first_unlock,
+            end_time,
+            withdrawn_amount: 0,
+            status: StreamStatus::Active,
+            memo: Bytes::new(&env),
+            external_ref: None,
+            refund_address: sender,
+            immutable: false,
+            step_seconds: period_seconds,
+            category: StreamCategory::Other,
+            delegate: None,
+            delegate_can_cancel: false,
+            milestones: Vec::new(&env),
+            milestones_approved: 0,
+            withdraw_schedule: None,
+            last_withdrawn_at: 0,
+            accrued_checkpoint: 0,
+        };
+        Self::open_stream(&env, stream, None)
+    }
+
+    /// Create a grant-style stream released in tranches as the admin approves
+    /// milestones, regardless of time.
+    ///
+    /// Each entry of `milestones` is the cumulative amount unlocked once that
+    /// milestone is approved via `approve_milestone`; the last entry is the
+    /// deposit. Nothing is withdrawable until milestone 0 is approved.
+    ///
+    /// # Panics
+    /// - If `milestones` is empty or has more than 50 entries.
+    /// - If the entries are not positive and strictly increasing.
+    /// - If `sender` and `recipient` are the same address.
+    /// - If token transfer fails.
+    pub fn create_milestone_stream(
+        env: Env,
+        sender: Address,
+        recipient: Address,
+        milestones: Vec<i128>,
+    ) -> u64 {
+        assert!(!milestones.is_empty(), "milestones must not be empty");
+        assert!(
+            milestones.len() <= MAX_MILESTONES,
+            "too many milestones (max 50)"
+        );
+        let mut previous = 0;
+        for amount in milestones.iter() {
+            assert!(
+                amount > previous,
+                "milestones must be positive and strictly increasing"
+            );
+            previous = amount;
+        }
+        assert!(
+            sender != recipient,
+            "sender and recipient must be different"
+        );
+
+        let now = env.ledger().timestamp();
+        let stream = Stream {
+            stream_id: 0, // assigned by `record_stream`
+            sender: sender.clone(),
+            recipient,
+            deposit_amount: previous,
+            rate_per_second: 0,
+            start_time: now,
+            cliff_time: now,
+            end_time: now,
+            withdrawn_amount: 0,
+            status: StreamStatus::Active,
+            memo: Bytes::new(&env),
+            external_ref: None,
+            refund_address: sender,
+            immutable: false,
+            step_seconds: 0,
+            category: StreamCategory::Other,
+            delegate: None,
+            delegate_can_cancel: false,
+            milestones,
+            milestones_approved: 0,
+            withdraw_schedule: None,
+            last_withdrawn_at: 0,
+            accrued_checkpoint: 0,
+        };
+        Self::open_stream(&env, stream, None)
+    }
+
+    /// Validate the parameters of a linear stream and build it, ready for
+    /// `open_stream`/`record_stream`.
+    #[allow(clippy::too_many_arguments)]
+    fn build_stream(
+        sender: Address,
+        recipient: Address,
+        deposit_amount: i128,
+        rate_per_second: i128,
+        start_time: u64,
+        cliff_time: u64,
+        end_time: u64,
+        memo: Bytes,
+        refund_address: Address,
+    ) -> Stream {
         // Validate positive amounts (#35)
         assert!(deposit_amount > 0, "deposit_amount must be positive");
         assert!(rate_per_second > 0, "rate_per_second must be positive");
 
-        // Validate sender != recipient (#35)
+        // Validate sender != recipient (#35)
+        assert!(
+            sender != recipient,
+            "sender and recipient must be different"
+        );
+
+        // Validate time constraints and that the deposit covers them (#34)
+        validate_coverage(deposit_amount, rate_per_second, start_time, end_time);
+        assert!(
+            cliff_time >= start_time && cliff_time <= end_time,
+            "cliff_time must be within [start_time, end_time]"
+        );
+
+        assert!(memo.len() <= MAX_MEMO_LEN, "memo exceeds 64 bytes");
+
+        let env = memo.env().clone();
+        Stream {
+            stream_id: 0, // assigned by `record_stream`
+            sender,
+            recipient,
+            deposit_amount,
+            rate_per_second,
+            start_time,
+            cliff_time,
+            end_time,
+            withdrawn_amount: 0,
+            status: StreamStatus::Active,
+            memo,
+            external_ref: None, // set by `record_stream`
+            refund_address,
+            immutable: false,
+            step_seconds: 0,
+            category: StreamCategory::Other,
+            delegate: None,
+            delegate_can_cancel: false,
+            milestones: Vec::new(&env),
+            milestones_approved: 0,
+            withdraw_schedule: None,
+            last_withdrawn_at: 0,
+            accrued_checkpoint: 0,
+        }
+    }
+
+    /// Shared tail of every stream constructor: takes the deposit from the
+    /// sender, then hands off to `record_stream`. Returns the new stream id.
+    fn open_stream(env: &Env, stream: Stream, external_ref: Option<BytesN<32>>) -> u64 {
+        assert_not_contract(env, &stream.sender, &stream.recipient);
+        stream.sender.require_auth();
+
+        if let Some(external_ref) = &external_ref {
+            assert!(
+                !env
+                    .storage()
+                    .persistent()
+                    .has(&DataKey::ByRef(external_ref.clone())),
+                "external_ref already in use"
+            );
+        }
+
+        // Transfer tokens from sender to this contract (#36)
+        // If transfer fails (insufficient balance/allowance), this will panic
+        // with `TransferFailed` and no state will be persisted (atomic transaction)
+        transfer_token(
+            env,
+            &stream.sender,
+            &env.current_contract_address(),
+            stream.deposit_amount,
+        );
+
+        Self::record_stream(env, stream, external_ref)
+    }
+
+    /// Persist a stream whose deposit this contract already holds: assigns
+    /// the next id, writes the stream and its index entries, and emits the
+    /// `created` event. Returns the new stream id. Panics, reverting the
+    /// deposit transfer with it, if the deposit or cliff is below the admin
+    /// minimum.
+    fn record_stream(env: &Env, mut stream: Stream, external_ref: Option<BytesN<32>>) -> u64 {
+        if let Some(min_deposit) = get_config(env).min_deposit {
+            assert!(
+                stream.deposit_amount >= min_deposit,
+                "deposit below minimum"
+            );
+        }
+        // Milestone streams unlock on approval rather than with time
+        if stream.milestones.is_empty() {
+            check_min_cliff(env, &stream);
+        }
+
+        // Only allocate stream id and persist state AFTER the deposit is held
+        let stream_id = get_stream_count(env);
+        set_stream_count(env, stream_id + 1);
+
+        stream.stream_id = stream_id;
+        stream.status = running_status(env, &stream);
+        stream.external_ref = external_ref.clone().map(Bytes::from);
+        save_stream(env, &stream);
+        add_to_index(env, DataKey::SenderStreams(stream.sender.clone()), stream_id);
+        add_to_index(
+            env,
+            DataKey::RecipientStreams(stream.recipient.clone()),
+            stream_id,
+        );
+        add_to_index(env, DataKey::CategoryIndex(stream.category), stream_id);
+        add_to_sorted_index(env, DataKey::StreamsByStatus(stream.status), stream_id);
+        record_transition(env, stream_id, stream.status, env.ledger().timestamp());
+
+        if let Some(external_ref) = external_ref {
+            let key = DataKey::ByRef(external_ref);
+            env.storage().persistent().set(&key, &stream_id);
+            env.storage().persistent().extend_ttl(&key, 17280, 120960);
+        }
+
+        env.events().publish(
+            (symbol_short!("created"), stream_id),
+            (stream.deposit_amount, stream.memo),
+        );
+
+        stream_id
+    }
+
+    /// Pause an active stream. Only the sender or admin may call this.
+    /// # Panics
+    /// - If the stream is locked.
+    /// - If the stream is not in `Active` state.
+    pub fn pause_stream(env: Env, stream_id: u64) {
+        let stream = load_stream(&env, stream_id);
+
+        // Corrected Auth Check
+        Self::require_sender_or_admin(&env, &stream.sender);
+        Self::apply_pause(&env, stream);
+    }
+
+    /// Pause an active stream until `resume_at`, e.g. for a maintenance
+    /// window. Same rules as `pause_stream`; from `resume_at` on, anyone may
+    /// end the pause with `trigger_auto_resume`. Resuming earlier by any
+    /// other means drops the scheduled resume.
+    ///
+    /// # Panics
+    /// - If `resume_at` is not in the future.
+    /// - Under any condition in which `pause_stream` would panic.
+    pub fn pause_with_auto_resume(env: Env, stream_id: u64, resume_at: u64) {
+        let stream = load_stream(&env, stream_id);
+        Self::require_sender_or_admin(&env, &stream.sender);
+        assert!(
+            resume_at > env.ledger().timestamp(),
+            "resume_at must be in the future"
+        );
+        Self::apply_pause(&env, stream);
+
+        let key = DataKey::AutoResumeAt(stream_id);
+        env.storage().persistent().set(&key, &resume_at);
+        env.storage().persistent().extend_ttl(&key, 17280, 120960);
+    }
+
+    /// Pause every `Active`, unlocked stream `sender` sends. Only the sender
+    /// or admin may call this. Returns how many streams were paused.
+    ///
+    /// Each call looks at no more than 50 streams of the sender index,
+    /// resuming where the previous call stopped and starting over once the
+    /// end is reached; call again until it returns 0.
+    pub fn pause_all(env: Env, sender: Address) -> u32 {
+        Self::require_sender_or_admin(&env, &sender);
+
+        let ids = get_index(&env, &DataKey::SenderStreams(sender.clone()));
+        let mut paused = 0;
+        for stream_id in next_batch(&env, DataKey::PauseAllCursor(sender), &ids).iter() {
+            let Some(stream) = try_load_stream(&env, stream_id) else {
+                continue;
+            };
+            let running = matches!(stream.status, StreamStatus::Active | StreamStatus::Scheduled);
+            if running && !stream.immutable {
+                Self::apply_pause(&env, stream);
+                paused += 1;
+            }
+        }
+        paused
+    }
+
+    /// Resume every `Paused` stream `sender` sends. Only the sender or admin
+    /// may call this. Returns how many streams were resumed. Works through
+    /// the sender index in batches like `pause_all`.
+    pub fn resume_all(env: Env, sender: Address) -> u32 {
+        Self::require_sender_or_admin(&env, &sender);
+
+        let ids = get_index(&env, &DataKey::SenderStreams(sender.clone()));
+        let mut resumed = 0;
+        for stream_id in next_batch(&env, DataKey::ResumeAllCursor(sender), &ids).iter() {
+            let Some(stream) = try_load_stream(&env, stream_id) else {
+                continue;
+            };
+            if stream.status == StreamStatus::Paused {
+                Self::apply_resume(&env, stream);
+                resumed += 1;
+            }
+        }
+        resumed
+    }
+
+    /// Resume a stream paused with `pause_with_auto_resume` once its resume
+    /// time has come. Callable by anyone.
+    ///
+    /// # Panics
+    /// - If no resume is scheduled, or its time has not come yet.
+    pub fn trigger_auto_resume(env: Env, stream_id: u64) {
+        let stream = load_stream(&env, stream_id);
+        let resume_at: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::AutoResumeAt(stream_id))
+            .expect("no auto-resume scheduled");
+        assert!(
+            env.ledger().timestamp() >= resume_at,
+            "auto-resume time not reached"
+        );
+        Self::apply_resume(&env, stream);
+    }
+
+    /// When the stream's current pause may be ended by anyone, if it was
+    /// paused with `pause_with_auto_resume`.
+    pub fn get_auto_resume_time(env: Env, stream_id: u64) -> Option<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::AutoResumeAt(stream_id))
+    }
+
+    /// Resume a stream whose pause has outlasted the limit it was created
+    /// with. Callable by anyone, so the recipient need not rely on the
+    /// sender.
+    ///
+    /// # Panics
+    /// - If the stream is not paused, has no pause limit, or has not been
+    ///   paused for that long yet.
+    pub fn resume_if_expired(env: Env, stream_id: u64) {
+        let stream = load_stream(&env, stream_id);
+        assert!(pause_expired(&env, &stream), "pause has not expired");
+        Self::apply_resume(&env, stream);
+    }
+
+    /// Resume a paused stream. Only the sender or admin may call this.
+    /// # Panics
+    /// - If the stream is not in `Paused` state.
+    pub fn resume_stream(env: Env, stream_id: u64) {
+        let stream = load_stream(&env, stream_id);
+        Self::require_sender_or_admin(&env, &stream.sender);
+        Self::apply_resume(&env, stream);
+    }
+
+    /// Hand the sender role of a live stream to `new_sender`.
+    ///
+    /// Requires the current sender's auth. The new sender immediately holds
+    /// the pause, resume and cancel rights, and any sender delegate is
+    /// dropped. If refunds were going to the old sender they follow to the
+    /// new one; an explicitly designated refund address is kept.
+    ///
+    /// # Panics
+    /// - If the stream is not `Active` or `Paused`.
+    /// - If `new_sender` is the stream's recipient or this contract.
+    pub fn transfer_sender_rights(env: Env, stream_id: u64, new_sender: Address) {
+        let mut stream = load_stream(&env, stream_id);
+        stream.sender.require_auth();
+
+        assert!(
+            stream.status.is_open(),
+            "stream must be active or paused to transfer sender rights"
+        );
+        assert!(
+            new_sender != stream.recipient,
+            "sender and recipient must be different"
+        );
+        assert!(
+            new_sender != env.current_contract_address(),
+            "address must not be the contract"
+        );
+
+        let old_sender = stream.sender.clone();
+        if stream.refund_address == old_sender {
+            stream.refund_address = new_sender.clone();
+        }
+        stream.sender = new_sender.clone();
+        stream.delegate = None;
+        stream.delegate_can_cancel = false;
+        save_stream(&env, &stream);
+        remove_from_index(&env, DataKey::SenderStreams(old_sender.clone()), stream_id);
+        add_to_index(&env, DataKey::SenderStreams(new_sender.clone()), stream_id);
+
+        env.events().publish(
+            (symbol_short!("sndr_chg"), stream_id),
+            (old_sender, new_sender),
+        );
+    }
+
+    /// Transfer a stream's receipt, and with it the recipient position, to
+    /// `to`. Requires the current holder's auth.
+    ///
+    /// `to` becomes the stream's recipient and alone may withdraw from then
+    /// on, including anything accrued but not yet withdrawn. The previous
+    /// holder's withdrawal callback is dropped. Cancellation refunds to the
+    /// sender are unaffected.
+    ///
+    /// # Panics
+    /// - If the stream was not created with a receipt, or it was burned.
+    /// - If the stream is not `Active` or `Paused`.
+    /// - If the current holder is frozen.
+    /// - If `to` is the current holder, the sender, or this contract.
+    pub fn transfer_receipt(env: Env, stream_id: u64, to: Address) {
+        let stream = load_stream(&env, stream_id);
+        let holder: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Receipt(stream_id))
+            .expect("stream has no receipt");
+        holder.require_auth();
+
+        Self::move_position(&env, stream, to.clone());
+
+        env.events()
+            .publish((symbol_short!("rcpt_xfer"), stream_id), (holder, to));
+    }
+
+    /// Transfer the recipient position of any stream to `new_owner`.
+    /// Requires the current recipient's auth.
+    ///
+    /// Same effect as `transfer_receipt`, whose receipt, if the stream has
+    /// one, moves along with the position. The address the stream was
+    /// created for stays available through `get_original_recipient`.
+    /// Operators, the operator withdrawal cap and the withdrawal callback
+    /// set by the previous owner are cleared.
+    ///
+    /// # Panics
+    /// - If the stream is not `Active` or `Paused`.
+    /// - If the current recipient is frozen.
+    /// - If `new_owner` is the current recipient, the sender, or this
+    ///   contract.
+    pub fn transfer_stream_position(env: Env, stream_id: u64, new_owner: Address) {
+        let stream = load_stream(&env, stream_id);
+        let owner = stream.recipient.clone();
+        owner.require_auth();
+
+        Self::move_position(&env, stream, new_owner.clone());
+
+        env.events()
+            .publish((symbol_short!("pos_xfer"), stream_id), (owner, new_owner));
+    }
+
+    /// Shared body of `transfer_receipt` and `transfer_stream_position`:
+    /// make `to` the stream's recipient, move everything keyed on the
+    /// recipient with it, and drop what the previous recipient configured.
+    fn move_position(env: &Env, mut stream: Stream, to: Address) {
+        let stream_id = stream.stream_id;
+        let from = stream.recipient.clone();
+        assert!(
+            stream.status.is_open(),
+            "stream must be active or paused to transfer position"
+        );
+        assert!(!is_frozen(env, &from), "recipient is frozen");
+        assert!(to != from, "position already held by recipient");
+        assert_not_contract(env, &stream.sender, &to);
+        assert!(to != stream.sender, "sender and recipient must be different");
+
+        let original_key = DataKey::OriginalRecipient(stream_id);
+        if !env.storage().persistent().has(&original_key) {
+            env.storage().persistent().set(&original_key, &from);
+            env.storage()
+                .persistent()
+                .extend_ttl(&original_key, 17280, 120960);
+        }
+        let receipt_key = DataKey::Receipt(stream_id);
+        if env.storage().persistent().has(&receipt_key) {
+            env.storage().persistent().set(&receipt_key, &to);
+            env.storage()
+                .persistent()
+                .extend_ttl(&receipt_key, 17280, 120960);
+        }
+
+        stream.recipient = to.clone();
+        save_stream(env, &stream);
+        env.storage()
+            .persistent()
+            .remove(&DataKey::WithdrawCallback(stream_id));
+        clear_operators(env, stream_id);
+        remove_from_index(env, DataKey::RecipientStreams(from), stream_id);
+        add_to_index(env, DataKey::RecipientStreams(to), stream_id);
+    }
+
+    /// Tag a stream with `category`, moving it between category indexes.
+    /// Requires the sender's auth.
+    pub fn set_stream_category(env: Env, stream_id: u64, category: StreamCategory) {
+        let mut stream = load_stream(&env, stream_id);
+        stream.sender.require_auth();
+
+        if stream.category == category {
+            return;
+        }
+        remove_from_index(&env, DataKey::CategoryIndex(stream.category), stream_id);
+        add_to_index(&env, DataKey::CategoryIndex(category), stream_id);
+
+        stream.category = category;
+        save_stream(&env, &stream);
+    }
+
+    /// Move the cliff earlier so funds become withdrawable sooner. Requires
+    /// the sender's auth. The cliff can never be moved later, so funds the
+    /// recipient has already earned cannot be re-locked.
+    ///
+    /// # Panics
+    /// - If the stream is not `Active` or `Paused`.
+    /// - If `new_cliff` is not earlier than the current cliff.
+    /// - If `new_cliff` is before `start_time`.
+    /// - If `new_cliff` is below the admin's minimum cliff duration.
+    pub fn lower_cliff(env: Env, stream_id: u64, new_cliff: u64) {
+        let mut stream = load_stream(&env, stream_id);
+        stream.sender.require_auth();
+
+        assert!(
+            stream.status.is_open(),
+            "stream must be active or paused to change cliff"
+        );
+        assert!(
+            new_cliff < stream.cliff_time,
+            "new_cliff must be earlier than the current cliff"
+        );
+        assert!(
+            new_cliff >= stream.start_time,
+            "cliff_time must be within [start_time, end_time]"
+        );
+
+        stream.cliff_time = new_cliff;
+        check_min_cliff(&env, &stream);
+        save_stream(&env, &stream);
+
+        env.events()
+            .publish((symbol_short!("cliff"), stream_id), new_cliff);
+    }
+
+    /// Add `additional_deposit` to a stream and move its end to
+    /// `new_end_time` in one step, so the stream is never extended without
+    /// the funds to cover it. Requires the sender's auth; the sender pays in
+    /// the additional deposit. The rate is unchanged.
+    ///
+    /// # Panics
+    /// - If the stream is not `Scheduled`, `Active` or `Paused`.
+    /// - If the stream is stepped, milestone-based or recurring.
+    /// - If `additional_deposit` is not positive or `new_end_time` is not
+    ///   after the current `end_time`.
+    /// - If the new deposit does not cover `rate_per_second` up to
+    ///   `new_end_time`.
+    /// - If the sender cannot pay.
+    pub fn increase_deposit_and_extend(
+        env: Env,
+        stream_id: u64,
+        additional_deposit: i128,
+        new_end_time: u64,
+    ) {
+        let mut stream = load_stream(&env, stream_id);
+        stream.sender.require_auth();
+
+        assert!(
+            stream.status.is_open(),
+            "stream must be active or paused to extend"
+        );
+        assert!(
+            stream.step_seconds == 0
+                && stream.milestones.is_empty()
+                && !env.storage().persistent().has(&DataKey::Recurring(stream_id)),
+            "only linear streams can be extended"
+        );
+        assert!(additional_deposit > 0, "additional_deposit must be positive");
+        assert!(
+            new_end_time > stream.end_time,
+            "new_end_time must be after end_time"
+        );
+        let deposit_amount = stream
+            .deposit_amount
+            .checked_add(additional_deposit)
+            .expect("overflow calculating deposit_amount");
+        validate_coverage(
+            deposit_amount - stream.accrued_checkpoint,
+            stream.rate_per_second,
+            stream.start_time,
+            new_end_time,
+        );
+
+        stream.deposit_amount = deposit_amount;
+        stream.end_time = new_end_time;
+        save_stream(&env, &stream);
+        transfer_token(
+            &env,
+            &stream.sender,
+            &env.current_contract_address(),
+            additional_deposit,
+        );
+
+        env.events().publish(
+            (symbol_short!("extended"), stream_id),
+            (additional_deposit, deposit_amount, new_end_time),
+        );
+    }
+
+    /// Move a stream that has not started yet to a new schedule at the same
+    /// rate, rather than cancelling and recreating it. Requires the sender's
+    /// auth. The new schedule is validated as in `create_stream`.
+    ///
+    /// # Panics
+    /// - If the stream's `start_time` has been reached.
+    /// - If the stream is not `Active` or has been withdrawn from.
+    /// - If the stream is stepped or milestone-based.
+    /// - If the new times are out of order, or the deposit does not cover
+    ///   `rate_per_second` over the new schedule.
+    /// - If the new cliff is below the admin's minimum cliff duration.
+    pub fn reschedule_stream(
+        env: Env,
+        stream_id: u64,
+        new_start: u64,
+        new_cliff: u64,
+        new_end: u64,
+    ) {
+        let mut stream = load_stream(&env, stream_id);
+        stream.sender.require_auth();
+
+        assert!(
+            env.ledger().timestamp() < stream.start_time,
+            "stream has already started"
+        );
+        assert!(
+            matches!(stream.status, StreamStatus::Scheduled | StreamStatus::Active)
+                && stream.withdrawn_amount == 0,
+            "only untouched active streams can be rescheduled"
+        );
+        assert!(
+            stream.step_seconds == 0 && stream.milestones.is_empty(),
+            "only linear streams can be rescheduled"
+        );
+        validate_coverage(stream.deposit_amount, stream.rate_per_second, new_start, new_end);
+        assert!(
+            new_cliff >= new_start && new_cliff <= new_end,
+            "cliff_time must be within [start_time, end_time]"
+        );
+
+        let old = (stream.start_time, stream.cliff_time, stream.end_time);
+        stream.start_time = new_start;
+        stream.cliff_time = new_cliff;
+        stream.end_time = new_end;
+        check_min_cliff(&env, &stream);
+        save_stream(&env, &stream);
+
+        env.events().publish(
+            (symbol_short!("resched"), stream_id),
+            (old, (new_start, new_cliff, new_end)),
+        );
+    }
+
+    /// Change a linear stream's rate and end time from now on, e.g. for a
+    /// raise, keeping its id and history. Requires both the sender's and
+    /// the recipient's auth.
+    ///
+    /// What has accrued so far is checkpointed into `accrued_checkpoint` and
+    /// `start_time` moves up to now (or stays, if the stream has not
+    /// started), so accrual before the amendment is exactly preserved. A
+    /// cliff still ahead keeps gating everything, including the checkpoint.
+    /// The deposit is resized to cover the new schedule exactly: the sender
+    /// pays in any increase, and any excess goes to the refund address.
+    ///
+    /// # Panics
+    /// - If the stream is not `Active` or `Paused`.
+    /// - If the stream is stepped, milestone-based or recurring.
+    /// - If `new_rate` is not positive or `new_end_time` is not in the future.
+    /// - If the new schedule overflows or ends before the cliff.
+    /// - If the new deposit would not cover what was already withdrawn
+    ///   (possible after an advance).
+    /// - If the sender cannot pay the increase.
+    pub fn amend_stream(env: Env, stream_id: u64, new_rate: i128, new_end_time: u64) {
+        let mut stream = load_stream(&env, stream_id);
+        stream.sender.require_auth();
+        stream.recipient.require_auth();
+
+        assert!(
+            stream.status.is_open(),
+            "stream must be active or paused to amend"
+        );
+        assert!(
+            stream.step_seconds == 0
+                && stream.milestones.is_empty()
+                && !env.storage().persistent().has(&DataKey::Recurring(stream_id)),
+            "only linear streams can be amended"
+        );
+        assert!(new_rate > 0, "rate_per_second must be positive");
+
+        // Accrual up to now, ignoring a cliff that is still ahead
+        let now = env.ledger().timestamp().max(stream.start_time);
+        let checkpoint = stream.accrued_checkpoint
+            + accrual::calculate_accrued_amount(
+                stream.start_time,
+                stream.start_time,
+                stream.end_time,
+                stream.rate_per_second,
+                stream.deposit_amount - stream.accrued_checkpoint,
+                now,
+            );
+        assert!(new_end_time > now, "new_end_time must be in the future");
+        assert!(
+            stream.cliff_time <= new_end_time,
+            "cliff_time must be within [start_time, end_time]"
+        );
+        let deposit_amount = new_rate
+            .checked_mul((new_end_time - now) as i128)
+            .and_then(|scheduled| scheduled.checked_add(checkpoint))
+            .expect("overflow calculating total streamable amount");
+        assert!(
+            deposit_amount >= stream.withdrawn_amount,
+            "new schedule must cover what was already withdrawn"
+        );
+        let delta = deposit_amount - stream.deposit_amount;
+        let old_rate = stream.rate_per_second;
+
+        stream.accrued_checkpoint = checkpoint;
+        stream.rate_per_second = new_rate;
+        stream.start_time = now;
+        stream.cliff_time = stream.cliff_time.max(now);
+        stream.end_time = new_end_time;
+        stream.deposit_amount = deposit_amount;
+        save_stream(&env, &stream);
+
+        let contract = env.current_contract_address();
+        if delta > 0 {
+            transfer_token(&env, &stream.sender, &contract, delta);
+        } else if delta < 0 {
+            transfer_token(&env, &contract, &stream.refund_address, -delta);
+        }
+
+        env.events().publish(
+            (symbol_short!("amended"), stream_id),
+            (old_rate, new_rate, new_end_time, delta),
+        );
+    }
+
+    /// Make a stream irrevocable. Requires both the sender's and the
+    /// recipient's auth.
+    ///
+    /// Once locked the stream can no longer be paused or cancelled, so the
+    /// recipient is guaranteed the full schedule. Withdrawals are unaffected.
+    /// Locking cannot be undone.
+    ///
+    /// # Panics
+    /// - If the stream is not `Active` or `Paused`.
+    /// - If the stream is already locked.
+    pub fn lock_stream(env: Env, stream_id: u64) {
+        let mut stream = load_stream(&env, stream_id);
+        stream.sender.require_auth();
+        stream.recipient.require_auth();
+
+        assert!(
+            stream.status.is_open(),
+            "stream must be active or paused to lock"
+        );
+        assert!(!stream.immutable, "stream is already locked");
+
+        stream.immutable = true;
+        save_stream(&env, &stream);
+
+        env.events()
+            .publish((symbol_short!("locked"), stream_id), ());
+    }
+
+    /// Attach a 32-byte application reference, such as an invoice hash, to a
+    /// stream, replacing any set before. Requires the sender's auth. Unlike
+    /// the `memo` given at creation it can be changed at any time; nothing
+    /// in the contract reads it.
+    pub fn set_reference_hash(env: Env, stream_id: u64, reference_hash: BytesN<32>) {
+        let stream = load_stream(&env, stream_id);
+        stream.sender.require_auth();
+
+        let key = DataKey::ReferenceHash(stream_id);
+        env.storage().persistent().set(&key, &reference_hash);
+        env.storage().persistent().extend_ttl(&key, 17280, 120960);
+        env.events()
+            .publish((symbol_short!("ref_set"), stream_id), reference_hash);
+    }
+
+    /// Cancel a stream and refund unstreamed funds to the sender.
+    ///
+    /// ## Behaviour
+    /// 1. **Auth** — only the original sender or the contract admin can cancel.
+    /// 2. **State check** — only `Active` or `Paused` streams that are not locked
+    ///    can be cancelled.
+    /// 3. **Accrual** — computes `accrued = min((now − start_time) × rate, deposit_amount)`.
+    /// 4. **Refund** — transfers `deposit_amount − accrued` to the stream's refund
+    ///    address (the sender unless set via `create_stream_with_refund`) immediately.
+    /// 5. **Persistence** — the portion `accrued − withdrawn_amount` remains for the recipient.
+    ///
+    /// A stream created with a cancellation notice is not settled here: the
+    /// notice is recorded and steps 3–5 happen in `finalize_cancel`.
+    pub fn cancel_stream(env: Env, stream_id: u64) {
+        let stream = load_stream(&env, stream_id);
+        Self::require_sender_or_admin(&env, &stream.sender);
+        if !Self::give_cancel_notice(&env, &stream) {
+            Self::apply_cancel(&env, stream);
+        }
+    }
+
+    /// Recover what a cancelled stream still owes its recipient once the
+    /// recipient has left it unclaimed for the withdraw grace period (see
+    /// `set_withdraw_grace_period`). Requires the sender's auth. The amount
+    /// goes to the refund address and the stream stays `Cancelled` with
+    /// nothing left to withdraw. Returns the amount reclaimed.
+    ///
+    /// # Panics
+    /// - If the stream is not `Cancelled`, or was cancelled before
+    ///   cancellation times were recorded.
+    /// - If the grace period has not passed since the cancellation.
+    /// - If the stream is disputed.
+    /// - If there is nothing left to reclaim.
+    pub fn reclaim_after_expiry(env: Env, stream_id: u64) -> i128 {
+        let mut stream = load_stream(&env, stream_id);
+        stream.sender.require_auth();
+        assert!(
+            stream.status == StreamStatus::Cancelled,
+            "stream is not cancelled"
+        );
+        let cancelled_at: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::CancelledAt(stream_id))
+            .expect("cancellation time not recorded");
+        let grace_period = Self::get_withdraw_grace_period(env.clone());
+        assert!(
+            env.ledger().timestamp() >= cancelled_at.saturating_add(grace_period),
+            "grace period has not expired"
+        );
+        assert!(!is_disputed(&env, stream_id), "stream is disputed");
+
+        let amount = stream.deposit_amount - stream.withdrawn_amount;
+        assert!(amount > 0, "nothing to reclaim");
+        stream.deposit_amount = stream.withdrawn_amount;
+        save_stream(&env, &stream);
+        transfer_token(
+            &env,
+            &env.current_contract_address(),
+            &stream.refund_address,
+            amount,
+        );
+
+        env.events()
+            .publish((symbol_short!("reclaimed"), stream_id), amount);
+        amount
+    }
+
+    /// Settle a cancellation once its notice period has run. Anyone may call
+    /// this; the recipient keeps everything accrued up to now.
+    ///
+    /// # Panics
+    /// - If no cancellation notice is pending.
+    /// - If the notice period has not elapsed.
+    pub fn finalize_cancel(env: Env, stream_id: u64) -> i128 {
+        let stream = load_stream(&env, stream_id);
+        let effective_at =
+            Self::get_cancel_effective_at(env.clone(), stream_id).expect("no cancellation notice");
+        assert!(
+            env.ledger().timestamp() >= effective_at,
+            "notice period has not elapsed"
+        );
+        Self::apply_cancel(&env, stream)
+    }
+
+    /// Withdraw a pending cancellation notice, leaving the stream running.
+    /// Requires the sender's auth.
+    ///
+    /// # Panics
+    /// - If no cancellation notice is pending.
+    pub fn withdraw_cancel_notice(env: Env, stream_id: u64) {
+        let stream = load_stream(&env, stream_id);
+        stream.sender.require_auth();
+        let key = DataKey::CancelNoticeAt(stream_id);
+        assert!(
+            env.storage().persistent().has(&key),
+            "no cancellation notice"
+        );
+        env.storage().persistent().remove(&key);
+        env.events()
+            .publish((symbol_short!("ntc_wdrn"), stream_id), ());
+    }
+
+    /// When a pending cancellation notice takes effect, if one was given.
+    pub fn get_cancel_effective_at(env: Env, stream_id: u64) -> Option<u64> {
+        let storage = env.storage().persistent();
+        let given_at: u64 = storage.get(&DataKey::CancelNoticeAt(stream_id))?;
+        let notice: u64 = storage.get(&DataKey::CancelNotice(stream_id))?;
+        Some(given_at.saturating_add(notice))
+    }
+
+    /// Propose cancelling a stream by mutual agreement: on confirmation the
+    /// recipient receives `proposed_recipient_share` of the balance still
+    /// held for the stream and the rest is refunded. Requires the sender's
+    /// auth. The proposal replaces any earlier one and can be confirmed for
+    /// 7 days.
+    ///
+    /// # Panics
+    /// - If the stream is not `Active` or `Paused`, or is locked.
+    /// - If the share is negative or exceeds the remaining balance.
+    pub fn propose_co_cancel(env: Env, stream_id: u64, proposed_recipient_share: i128) {
+        let stream = load_stream(&env, stream_id);
+        stream.sender.require_auth();
+
+        assert!(!stream.immutable, "stream is locked");
+        assert!(
+            stream.status.is_open(),
+            "stream must be active or paused to cancel"
+        );
+        assert!(
+            proposed_recipient_share >= 0
+                && proposed_recipient_share <= stream.deposit_amount - stream.withdrawn_amount,
+            "recipient share must be within the remaining balance"
+        );
+
+        let proposal = CoCancelProposal {
+            recipient_share: proposed_recipient_share,
+            valid_until: env.ledger().timestamp() + CO_CANCEL_WINDOW_SECONDS,
+        };
+        let key = DataKey::CoCancelProposal(stream_id);
+        env.storage().persistent().set(&key, &proposal);
+        env.storage().persistent().extend_ttl(&key, 17280, 120960);
+
+        env.events().publish(
+            (symbol_short!("cc_prop"), stream_id),
+            (proposal.recipient_share, proposal.valid_until),
+        );
+    }
+
+    /// Accept the pending co-cancel proposal. Requires the recipient's auth.
+    ///
+    /// Pays the agreed share to the recipient, refunds the rest to the refund
+    /// address and cancels the stream. The deposit is reduced to what the
+    /// recipient ends up with, so nothing further accrues.
+    ///
+    /// # Panics
+    /// - If there is no proposal or it has expired.
+    /// - If the stream is no longer `Active` or `Paused`.
+    /// - If the recipient has since withdrawn into the proposed share.
+    /// - If the stream is disputed (settle it with `resolve_dispute` or
+    ///   `arbitrate_stream` instead).
+    /// - If the recipient is frozen or the compliance hook objects.
+    pub fn confirm_co_cancel(env: Env, stream_id: u64) {
+        let mut stream = load_stream(&env, stream_id);
+        stream.recipient.require_auth();
+
+        let key = DataKey::CoCancelProposal(stream_id);
+        let proposal: CoCancelProposal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .expect("no co-cancel proposal");
+        assert!(
+            env.ledger().timestamp() <= proposal.valid_until,
+            "co-cancel proposal expired"
+        );
+        assert!(
+            stream.status.is_open(),
+            "stream must be active or paused to cancel"
+        );
+        let remaining = stream.deposit_amount - stream.withdrawn_amount;
+        assert!(
+            proposal.recipient_share <= remaining,
+            "recipient share must be within the remaining balance"
+        );
+        assert!(!is_frozen(&env, &stream.recipient), "recipient is frozen");
+        assert!(!is_disputed(&env, stream_id), "stream is disputed");
+
+        // Settle the stream before any tokens move
+        let refund = remaining - proposal.recipient_share;
+        stream.withdrawn_amount += proposal.recipient_share;
+        stream.deposit_amount = stream.withdrawn_amount;
+        set_status(&env, &mut stream, StreamStatus::Cancelled);
+        save_stream(&env, &stream);
+        env.storage().persistent().remove(&key);
+        clear_pending_admin_cancel(&env, stream_id);
+        burn_receipt(&env, stream_id);
+
+        let contract = env.current_contract_address();
+        if proposal.recipient_share > 0 {
+            check_compliance(&env, &stream.recipient, proposal.recipient_share);
+            transfer_token(&env, &contract, &stream.recipient, proposal.recipient_share);
+        }
+        if refund > 0 {
+            transfer_token(&env, &contract, &stream.refund_address, refund);
+        }
+
+        env.events().publish(
+            (symbol_short!("co_cancel"), stream_id),
+            (proposal.recipient_share, refund),
+        );
+    }
+
+    /// Decline the pending co-cancel proposal. Requires the recipient's auth.
+    ///
+    /// # Panics
+    /// - If there is no proposal.
+    pub fn reject_co_cancel(env: Env, stream_id: u64) {
+        let stream = load_stream(&env, stream_id);
+        stream.recipient.require_auth();
+
+        let key = DataKey::CoCancelProposal(stream_id);
+        assert!(
+            env.storage().persistent().has(&key),
+            "no co-cancel proposal"
+        );
+        env.storage().persistent().remove(&key);
+
+        env.events()
+            .publish((symbol_short!("cc_rej"), stream_id), ());
+    }
+
+    /// Ask the sender to pay `amount` of the stream out ahead of accrual.
+    /// Requires the recipient's auth. Replaces any earlier request; nothing
+    /// is paid until the sender calls `approve_advance`.
+    ///
+    /// # Panics
+    /// - If the stream is not `Active`, `Paused` or `Scheduled`.
+    /// - If `amount` is not positive, or not less than what the stream has
+    ///   left to pay out: the final part is always withdrawn normally, which
+    ///   completes the stream.
+    pub fn request_advance(env: Env, stream_id: u64, amount: i128) {
+        let stream = load_stream(&env, stream_id);
+        stream.recipient.require_auth();
+        assert!(
+            stream.status.is_open(),
+            "stream must be active or paused to advance"
+        );
+        assert!(
+            amount > 0 && amount < total_streamable(&stream) - stream.withdrawn_amount,
+            "advance must leave part of the stream to accrue"
+        );
+
+        let key = DataKey::AdvanceRequest(stream_id);
+        env.storage().persistent().set(&key, &amount);
+        env.storage().persistent().extend_ttl(&key, 17280, 120960);
+        env.events()
+            .publish((symbol_short!("adv_req"), stream_id), amount);
+    }
+
+    /// Pay the recipient's pending advance request now. Requires the
+    /// sender's auth.
+    ///
+    /// The advance counts as withdrawn, so the recipient can withdraw
+    /// nothing more until accrual catches up with it. No withdrawal fee is
+    /// taken.
+    ///
+    /// # Panics
+    /// - If there is no pending request.
+    /// - If the stream is no longer open, or the recipient has since
+    ///   withdrawn into the requested amount.
+    /// - If the stream is disputed, the recipient is frozen or the
+    ///   compliance hook objects.
+    pub fn approve_advance(env: Env, stream_id: u64) {
+        let mut stream = load_stream(&env, stream_id);
+        stream.sender.require_auth();
+
+        let key = DataKey::AdvanceRequest(stream_id);
+        let amount: i128 = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .expect("no advance request");
+        assert!(
+            stream.status.is_open(),
+            "stream must be active or paused to advance"
+        );
+        assert!(
+            amount < total_streamable(&stream) - stream.withdrawn_amount,
+            "advance must leave part of the stream to accrue"
+        );
+        assert!(!is_disputed(&env, stream_id), "stream is disputed");
+        assert!(!is_frozen(&env, &stream.recipient), "recipient is frozen");
+        check_compliance(&env, &stream.recipient, amount);
+
+        env.storage().persistent().remove(&key);
+        stream.withdrawn_amount += amount;
+        save_stream(&env, &stream);
+        transfer_token(
+            &env,
+            &env.current_contract_address(),
+            &stream.recipient,
+            amount,
+        );
+
+        env.events()
+            .publish((symbol_short!("advanced"), stream_id), amount);
+    }
+
+    /// Amount of the recipient's pending advance request, if any.
+    pub fn get_advance_request(env: Env, stream_id: u64) -> Option<i128> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::AdvanceRequest(stream_id))
+    }
+
+    /// Settle a contested stream by splitting what it still holds
+    /// (`deposit_amount - withdrawn_amount`): `recipient_share_bps` of it,
+    /// rounded down, goes to the recipient and the rest to the refund
+    /// address. The stream ends `Arbitrated` with nothing left to claim.
+    ///
+    /// Subject to the same timelock as an admin cancel: the admin must first
+    /// announce the intervention with `admin_request_cancel` and wait out the
+    /// admin cancel delay. Requires admin auth, and is only reachable through
+    /// a `GovAction::ArbitrateStream` proposal once governance is configured.
+    ///
+    /// # Panics
+    /// - If `recipient_share_bps` exceeds 10000.
+    /// - If no admin cancel is pending or the delay has not yet elapsed.
+    /// - If the stream is locked, or has ended and is not disputed.
+    /// - If the recipient is frozen or the compliance hook objects.
+    pub fn arbitrate_stream(env: Env, stream_id: u64, recipient_share_bps: u32) {
+        governance::require_admin(&env);
+        let executable_at: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::PendingAdminCancel(stream_id))
+            .expect("no admin cancel requested");
+        assert!(
+            env.ledger().timestamp() >= executable_at,
+            "admin cancel delay has not elapsed"
+        );
+
+        Self::apply_arbitration(&env, load_stream(&env, stream_id), recipient_share_bps);
+    }
+
+    /// Clear a co-cancel proposal whose confirmation window has passed.
+    /// Callable by anyone.
+    ///
+    /// # Panics
+    /// - If there is no proposal or it is still confirmable.
+    pub fn expire_co_cancel(env: Env, stream_id: u64) {
+        let key = DataKey::CoCancelProposal(stream_id);
+        let proposal: CoCancelProposal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .expect("no co-cancel proposal");
+        assert!(
+            env.ledger().timestamp() > proposal.valid_until,
+            "co-cancel proposal has not expired"
+        );
+        env.storage().persistent().remove(&key);
+
+        env.events()
+            .publish((symbol_short!("cc_exp"), stream_id), ());
+    }
+
+    /// Let `delegate` pause, resume and, if `can_cancel`, cancel the stream on
+    /// the sender's behalf. Requires the sender's auth and replaces any
+    /// previous delegate. Refunds still go to the stream's refund address.
+    pub fn set_sender_delegate(env: Env, stream_id: u64, delegate: Address, can_cancel: bool) {
+        let mut stream = load_stream(&env, stream_id);
+        stream.sender.require_auth();
+
+        stream.delegate = Some(delegate.clone());
+        stream.delegate_can_cancel = can_cancel;
+        save_stream(&env, &stream);
+
+        env.events()
+            .publish((symbol_short!("delegate"), stream_id), (delegate, can_cancel));
+    }
+
+    /// Remove the stream's sender delegate. Requires the sender's auth.
+    pub fn clear_sender_delegate(env: Env, stream_id: u64) {
+        let mut stream = load_stream(&env, stream_id);
+        stream.sender.require_auth();
+
+        stream.delegate = None;
+        stream.delegate_can_cancel = false;
+        save_stream(&env, &stream);
+    }
+
+    /// Pause a stream as its sender delegate. Same rules as `pause_stream`.
+    pub fn pause_stream_as_delegate(env: Env, stream_id: u64) {
+        let stream = load_stream(&env, stream_id);
+        Self::require_delegate(&stream);
+        Self::apply_pause(&env, stream);
+    }
+
+    /// Resume a stream as its sender delegate. Same rules as `resume_stream`.
+    pub fn resume_stream_as_delegate(env: Env, stream_id: u64) {
+        let stream = load_stream(&env, stream_id);
+        Self::require_delegate(&stream);
+        Self::apply_resume(&env, stream);
+    }
+
+    /// Cancel a stream as its sender delegate. Same rules as `cancel_stream`.
+    ///
+    /// # Panics
+    /// - If the delegate was not granted cancel rights.
+    pub fn cancel_stream_as_delegate(env: Env, stream_id: u64) {
+        let stream = load_stream(&env, stream_id);
+        Self::require_delegate(&stream);
+        assert!(stream.delegate_can_cancel, "delegate cannot cancel");
+        if !Self::give_cancel_notice(&env, &stream) {
+            Self::apply_cancel(&env, stream);
+        }
+    }
+
+    /// Withdraw accrued-but-not-yet-withdrawn tokens to the recipient.
+    /// Returns the amount transferred to the recipient.
+    ///
+    /// If a withdrawal fee is configured, `fee_bps` of the withdrawn amount is
+    /// sent to the fee collector instead; `withdrawn_amount` still advances
+    /// by the full amount.
+    ///
+    /// # Panics
+    /// - If the stream is `Completed` (nothing left to withdraw).
+    /// - If the stream is `Paused` (withdrawals not allowed while paused).
+    /// - If the recipient is frozen by the admin.
+    /// - If the stream is disputed (see `raise_dispute`).
+    /// - If the compliance hook vetoes or fails (typed `ContractError`).
+    /// - If there is nothing to withdraw (accrued == withdrawn).
+    pub fn withdraw(env: Env, stream_id: u64) -> i128 {
+        let stream = load_stream(&env, stream_id);
+
+        // Enforce recipient-only authorization: only the stream's recipient can withdraw
+        // This is equivalent to checking env.invoker() == stream.recipient
+        // require_auth() ensures only the recipient can authorize this call,
+        // preventing anyone from withdrawing on behalf of the recipient
+        stream.recipient.require_auth();
+
+        Self::pay_out(&env, stream, None, None, true).payout
+    }
+
+    /// Withdraw exactly like `withdraw`, returning a `WithdrawReceipt` that
+    /// also reports the fee taken, the stream's new `withdrawn_amount` and
+    /// whether this withdrawal completed it.
+    ///
+    /// # Panics
+    /// - Under the same conditions as `withdraw`.
+    pub fn withdraw_receipt(env: Env, stream_id: u64) -> WithdrawReceipt {
+        let stream = load_stream(&env, stream_id);
+        stream.recipient.require_auth();
+
+        let settlement = Self::pay_out(&env, stream, None, None, true);
+        let stream = load_stream(&env, stream_id);
+        WithdrawReceipt {
+            stream_id,
+            amount: settlement.payout,
+            fee: settlement.fee,
+            total_withdrawn: stream.withdrawn_amount,
+            completed: stream.status == StreamStatus::Completed,
+        }
+    }
+
+    /// Withdraw like `withdraw`, withholding `withholding_bps` of the payout
+    /// and sending it to `tax_authority`. Requires the recipient's auth.
+    ///
+    /// Returns `(net_to_recipient, tax_amount)`, where
+    /// `net_to_recipient = payout * (10000 - withholding_bps) / 10000` rounded
+    /// down and `tax_amount` is the rest of the payout. Any protocol fee is
+    /// taken before withholding.
+    ///
+    /// # Panics
+    /// - If `withholding_bps` exceeds 5000.
+    /// - Under any condition in which `withdraw` would panic.
+    pub fn withdraw_with_withholding(
+        env: Env,
+        stream_id: u64,
+        tax_authority: Address,
+        withholding_bps: u32,
+    ) -> (i128, i128) {
+        assert!(
+            withholding_bps <= MAX_WITHHOLDING_BPS,
+            "withholding_bps cannot exceed 5000"
+        );
+        let stream = load_stream(&env, stream_id);
+        stream.recipient.require_auth();
+
+        let withholding = Some((&tax_authority, withholding_bps));
+        let settlement = Self::pay_out(&env, stream, withholding, None, true);
+        let (net, tax) = (settlement.payout, settlement.withheld);
+        env.events().publish(
+            (symbol_short!("withheld"), stream_id),
+            (net, tax, tax_authority),
+        );
+        (net, tax)
+    }
+
+    /// Register (or with `None`, remove) the contract notified after each
+    /// withdrawal from `stream_id`. Requires the recipient's auth.
+    pub fn set_withdraw_callback(
+        env: Env,
+        stream_id: u64,
+        callback: Option<WithdrawCallback>,
+    ) {
+        let stream = load_stream(&env, stream_id);
+        stream.recipient.require_auth();
+
+        let key = DataKey::WithdrawCallback(stream_id);
+        match &callback {
+            Some(callback) => {
+                assert!(
+                    callback.contract != env.current_contract_address(),
+                    "callback must not be the contract"
+                );
+                env.storage().persistent().set(&key, callback);
+                env.storage().persistent().extend_ttl(&key, 17280, 120960);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+
+        env.events().publish(
+            (symbol_short!("cb_set"), stream_id),
+            callback.map(|callback| callback.contract),
+        );
+    }
+
+    /// Approve `operator` to trigger withdrawals of `stream_id` on the
+    /// recipient's behalf. Requires the recipient's auth. Funds always go to
+    /// the recipient.
+    pub fn approve_operator(env: Env, stream_id: u64, operator: Address) {
+        let stream = load_stream(&env, stream_id);
+        stream.recipient.require_auth();
+
+        let key = DataKey::Operator(stream_id, operator.clone());
+        env.storage().persistent().set(&key, &true);
+        env.storage().persistent().extend_ttl(&key, 17280, 120960);
+
+        let list_key = OperatorKey::Operators(stream_id);
+        let mut operators: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&list_key)
+            .unwrap_or_else(|| Vec::new(&env));
+        if !operators.contains(&operator) {
+            operators.push_back(operator.clone());
+        }
+        env.storage().persistent().set(&list_key, &operators);
+        env.storage()
+            .persistent()
+            .extend_ttl(&list_key, 17280, 120960);
+
+        env.events()
+            .publish((symbol_short!("op_appr"), stream_id), operator);
+    }
+
+    /// Revoke a previously approved operator. Requires the recipient's auth
+    /// and takes effect immediately.
+    pub fn revoke_operator(env: Env, stream_id: u64, operator: Address) {
+        let stream = load_stream(&env, stream_id);
+        stream.recipient.require_auth();
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Operator(stream_id, operator.clone()));
+        let list_key = OperatorKey::Operators(stream_id);
+        if let Some(mut operators) = env
+            .storage()
+            .persistent()
+            .get::<_, Vec<Address>>(&list_key)
+        {
+            if let Some(pos) = operators.first_index_of(&operator) {
+                operators.remove(pos);
+                env.storage().persistent().set(&list_key, &operators);
+            }
+        }
+
+        env.events()
+            .publish((symbol_short!("op_revk"), stream_id), operator);
+    }
+
+    /// Withdraw to the recipient, authorized by an approved `operator` instead
+    /// of the recipient. Otherwise identical to `withdraw`, except that at
+    /// most the cap set via `set_auto_withdraw_cap` is paid per call.
+    ///
+    /// # Panics
+    /// - If `operator` is not approved for the stream.
+    /// - Under any condition in which `withdraw` would panic.
+    pub fn withdraw_as_operator(env: Env, stream_id: u64, operator: Address) -> i128 {
+        operator.require_auth();
+        assert!(
+            env.storage()
+                .persistent()
+                .has(&DataKey::Operator(stream_id, operator)),
+            "operator not approved"
+        );
+
+        let cap = env
+            .storage()
+            .persistent()
+            .get(&DataKey::AutoWithdrawCap(stream_id));
+        Self::pay_out(&env, load_stream(&env, stream_id), None, cap, true).payout
+    }
+
+    /// Limit how much a single `withdraw_as_operator` call may withdraw, so a
+    /// misbehaving keeper cannot drain the stream at once; `None` removes the
+    /// limit. Requires the recipient's auth. `withdraw` itself is unaffected.
+    ///
+    /// # Panics
+    /// - If `cap` is not positive.
+    pub fn set_auto_withdraw_cap(env: Env, stream_id: u64, cap: Option<i128>) {
+        let stream = load_stream(&env, stream_id);
+        stream.recipient.require_auth();
+
+        let key = DataKey::AutoWithdrawCap(stream_id);
+        match cap {
+            Some(cap) => {
+                assert!(cap > 0, "cap must be positive");
+                env.storage().persistent().set(&key, &cap);
+                env.storage().persistent().extend_ttl(&key, 17280, 120960);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+
+        env.events()
+            .publish((symbol_short!("cap_set"), stream_id), cap);
+    }
+
+    /// Shared body of the withdraw entrypoints, run once the caller has been
+    /// authorized: pays the recipient everything accrued but not yet withdrawn
+    /// (less any fee and withholding) and completes the stream when fully
+    /// paid. `cap` limits the amount taken from the stream in this call, and
+    /// without `charge_fee` no withdrawal fee is taken.
+    /// Returns what was paid out, once all of it has been transferred.
+    fn pay_out(
+        env: &Env,
+        mut stream: Stream,
+        withholding: Option<(&Address, u32)>,
+        cap: Option<i128>,
+        charge_fee: bool,
+    ) -> Settlement {
+        let settlement = Self::settle_withdrawal(
+            env,
+            &mut stream,
+            withholding.map(|(_, withholding_bps)| withholding_bps),
+            cap,
+            charge_fee,
+        );
+
+        if let Some(collector) = &settlement.fee_collector {
+            transfer_token(env, &env.current_contract_address(), collector, settlement.fee);
+            record_fee(env, &get_token(env), settlement.fee);
+        }
+        if let Some((tax_authority, _)) = withholding.filter(|_| settlement.withheld > 0) {
+            transfer_token(
+                env,
+                &env.current_contract_address(),
+                tax_authority,
+                settlement.withheld,
+            );
+        }
+        transfer_token(
+            env,
+            &env.current_contract_address(),
+            &stream.recipient,
+            settlement.payout,
+        );
+        Self::refund_dust(env, &stream, settlement.dust);
+
+        env.events()
+            .publish((symbol_short!("withdrew"), stream.stream_id), settlement.payout);
+
+        notify_withdraw_callback(env, stream.stream_id, settlement.payout);
+        settlement
+    }
+
+    /// Check and record a withdrawal from `stream`, completing it when fully
+    /// paid, and return the transfers still to be made. Everything is saved
+    /// before any tokens move, so a token that calls back in already sees the
+    /// withdrawal (checks-effects-interactions).
+    fn settle_withdrawal(
+        env: &Env,
+        stream: &mut Stream,
+        withholding_bps: Option<u32>,
+        cap: Option<i128>,
+        charge_fee: bool,
+    ) -> Settlement {
+        let stream_id = stream.stream_id;
+
+        assert!(
+            stream.status != StreamStatus::Completed,
+            "stream already completed"
+        );
+
+        // A pause past its limit no longer holds the recipient back
+        if pause_expired(env, stream) {
+            Self::resume_in_place(env, stream);
+        }
+        assert!(
+            stream.status != StreamStatus::Paused,
+            "cannot withdraw from paused stream"
+        );
+        assert!(!is_frozen(env, &stream.recipient), "recipient is frozen");
+        assert!(!is_disputed(env, stream_id), "stream is disputed");
+        let now = env.ledger().timestamp();
+        if let Some(next) = next_withdraw_time(stream) {
+            assert!(now >= next, "not yet scheduled");
+        }
+
+        let accrued = Self::accrued_at(stream, now);
+        let withdrawable = (accrued - stream.withdrawn_amount).min(cap.unwrap_or(i128::MAX));
+        assert!(withdrawable > 0, "nothing to withdraw");
+
+        let fee_setting = get_withdrawal_fee(env).filter(|_| charge_fee);
+        let fee = fee_setting
+            .as_ref()
+            .map_or(0, |(fee_bps, _)| bps_of(withdrawable, *fee_bps));
+        let mut payout = withdrawable - fee;
+        let withheld = withholding_bps.map_or(0, |withholding_bps| {
+            payout - bps_of(payout, BPS_DENOMINATOR - withholding_bps)
+        });
+        payout -= withheld;
+
+        // Consulted before any funds move so a veto leaves no partial effects
+        check_compliance(env, &stream.recipient, payout);
+
+        stream.withdrawn_amount += withdrawable;
+        stream.last_withdrawn_at = now;
+
+        // // If the full deposit has been streamed and withdrawn, mark completed
+        // let now = env.ledger().timestamp();
+        // if stream.status == StreamStatus::Active
+        //     && now >= stream.end_time
+        //     && stream.withdrawn_amount == stream.deposit_amount
+        // {
+        //     stream.status = StreamStatus::Completed;
+        // }
+
+        // An active recurring stream may still be renewed, so it only
+        // completes once cancelled. A cancelled stream completes once the
+        // recipient has withdrawn everything cancellation left it.
+        let renewable = stream.status == StreamStatus::Active
+            && env.storage().persistent().has(&DataKey::Recurring(stream_id));
+        let streamable = total_streamable(stream);
+        let mut dust = 0;
+        if stream.withdrawn_amount >= streamable && !renewable {
+            // Return the part of the deposit the schedule never streams so it
+            // isn't stranded. Cancelled streams already refunded it.
+            if stream.status == StreamStatus::Active {
+                dust = stream.deposit_amount - streamable;
+            }
+            set_status(env, stream, StreamStatus::Completed);
+            clear_stream_nonce(env, stream_id);
+            clear_pending_admin_cancel(env, stream_id);
+            burn_receipt(env, stream_id);
+
+            let key = DataKey::CompletedAt(stream_id);
+            env.storage().persistent().set(&key, &env.ledger().timestamp());
+            env.storage().persistent().extend_ttl(&key, 17280, 120960);
+        }
+
+        save_stream(env, stream);
+
+        Settlement {
+            fee,
+            fee_collector: fee_setting
+                .filter(|_| fee > 0)
+                .map(|(_, collector)| collector),
+            withheld,
+            payout,
+            dust,
+        }
+    }
+
+    /// Send a completed stream's unstreamed `dust` to its refund address.
+    fn refund_dust(env: &Env, stream: &Stream, dust: i128) {
+        if dust > 0 {
+            transfer_token(
+                env,
+                &env.current_contract_address(),
+                &stream.refund_address,
+                dust,
+            );
+        }
+    }
+
+    /// Replace a Completed stream's full record with a compact `ArchivedStream`.
+    ///
+    /// Callable by anyone: completed streams hold no funds, so archiving only
+    /// shrinks the persistent storage footprint. The full `Stream` entry is
+    /// deleted and no longer readable via `get_stream_state`.
+    ///
+    /// # Panics
+    /// - If the stream does not exist (or was already archived).
+    /// - If the stream is not `Completed`.
+    pub fn archive_completed_stream(env: Env, stream_id: u64) {
+        let stream = load_stream(&env, stream_id);
+        assert!(
+            stream.status == StreamStatus::Completed,
+            "only completed streams can be archived"
+        );
+
+        let completed_key = DataKey::CompletedAt(stream_id);
+        let completed_at: u64 = env
+            .storage()
+            .persistent()
+            .get(&completed_key)
+            .unwrap_or(stream.end_time);
+
+        let archived = ArchivedStream {
+            stream_id,
+            sender: stream.sender,
+            recipient: stream.recipient,
+            total_streamed: stream.withdrawn_amount,
+            completed_at,
+        };
+
+        let key = DataKey::ArchivedStream(stream_id);
+        env.storage().persistent().set(&key, &archived);
+        env.storage().persistent().extend_ttl(&key, 17280, 120960);
+        env.storage().persistent().remove(&DataKey::Stream(stream_id));
+        env.storage().persistent().remove(&completed_key);
+        remove_from_index(&env, DataKey::StreamsByStatus(StreamStatus::Completed), stream_id);
+
+        env.events()
+            .publish((symbol_short!("archived"), stream_id), ());
+    }
+
+    /// Pay the stream's recipient its share of any surplus the contract
+    /// holds beyond what streams have locked, e.g. from a rebasing or
+    /// yield-bearing token. Callable by anyone. Returns the amount paid.
+    ///
+    /// New surplus is attributed to the streams locking tokens when it is
+    /// first seen, pro rata to what each locks (see `get_stats`), by raising
+    /// a global yield-per-locked-token index. A stream is paid the index
+    /// growth since its last checkpoint, so harvesting again pays nothing
+    /// until more surplus arrives, and the order in which streams harvest
+    /// does not change their shares.
+    ///
+    /// Like `withdrawable_at`, returns 0 while the recipient is frozen or the
+    /// stream is disputed; the yield stays owed and is paid on a later call.
+    ///
+    /// # Panics
+    /// - If the compliance hook refuses the payout.
+    pub fn harvest_yield(env: Env, stream_id: u64) -> i128 {
+        let stream = load_stream(&env, stream_id);
+        if is_frozen(&env, &stream.recipient) || is_disputed(&env, stream_id) {
+            return 0;
+        }
+        let index = accrue_yield(&env);
+        let share = pending_yield(&env, &stream, index);
+        if share <= 0 {
+            return 0;
+        }
+        set_yield_checkpoint(&env, stream_id, index, 0);
+        let reserved = yield_total(&env, &YieldKey::UnharvestedYield);
+        env.storage()
+            .instance()
+            .set(&YieldKey::UnharvestedYield, &(reserved - share));
+
+        check_compliance(&env, &stream.recipient, share);
+        let contract = env.current_contract_address();
+        transfer_token(&env, &contract, &stream.recipient, share);
+
+        env.events()
+            .publish((symbol_short!("harvested"), stream_id), share);
+        share
+    }
+
+    /// Withdraw whatever is currently withdrawable, returning 0 instead of
+    /// panicking when there is nothing to pay out.
+    ///
+    /// Returns 0 without requiring auth when the stream is `Paused` (within
+    /// any pause limit) or `Completed`, the recipient is frozen, the stream
+    /// is disputed, or nothing has accrued beyond `withdrawn_amount`.
+    /// Otherwise behaves exactly like `withdraw`. Intended for keeper loops.
+    /// (Not named `try_withdraw`: the generated client already defines that
+    /// as the fallible variant of `withdraw`.)
+    pub fn withdraw_available(env: Env, stream_id: u64) -> i128 {
+        let stream = load_stream(&env, stream_id);
+        if (stream.status == StreamStatus::Paused && !pause_expired(&env, &stream))
+            || stream.status == StreamStatus::Completed
+            || is_frozen(&env, &stream.recipient)
+            || is_disputed(&env, stream_id)
+            || next_withdraw_time(&stream).is_some_and(|next| env.ledger().timestamp() < next)
+        {
+            return 0;
+        }
+
+        let accrued = Self::accrued_at(&stream, env.ledger().timestamp());
+        if accrued <= stream.withdrawn_amount {
+            return 0;
+        }
+
+        Self::withdraw(env, stream_id)
+    }
+
+    /// Withdraw from every stream paying `recipient` that has something
+    /// claimable, in one transfer to the recipient. Requires the recipient's
+    /// auth. Returns the total paid to the recipient.
+    ///
+    /// Streams that are paused, completed, archived, disputed or between
+    /// scheduled claim times are skipped, as is everything while the recipient is
+    /// frozen. Each call
+    /// looks at no more than 50 streams of the recipient index, resuming
+    /// where the previous call stopped and starting over once the end is
+    /// reached. Per-stream `withdrew` events and withdrawal callbacks fire
+    /// as for `withdraw`, after the transfer.
+    ///
+    /// # Panics
+    /// - If the compliance hook vetoes or fails for any stream.
+    pub fn withdraw_all(env: Env, recipient: Address) -> i128 {
+        recipient.require_auth();
+
+        let ids = get_index(&env, &DataKey::RecipientStreams(recipient.clone()));
+        let batch = next_batch(&env, DataKey::WithdrawAllCursor(recipient.clone()), &ids);
+
+        let now = env.ledger().timestamp();
+        let mut total = 0;
+        let mut fees = 0;
+        let mut fee_collector = None;
+        let mut paid: Vec<(u64, i128)> = Vec::new(&env);
+        for stream_id in batch.iter() {
+            let Some(mut stream) = try_load_stream(&env, stream_id) else {
+                continue;
+            };
+            if Self::withdrawable_at(&env, &stream, now) <= 0 {
+                continue;
+            }
+            let settlement = Self::settle_withdrawal(&env, &mut stream, None, None, true);
+            total += settlement.payout;
+            fees += settlement.fee;
+            fee_collector = fee_collector.or(settlement.fee_collector);
+            Self::refund_dust(&env, &stream, settlement.dust);
+            paid.push_back((stream_id, settlement.payout));
+        }
+
+        if let Some(collector) = &fee_collector {
+            transfer_token(&env, &env.current_contract_address(), collector, fees);
+            record_fee(&env, &get_token(&env), fees);
+        }
+        if total > 0 {
+            transfer_token(&env, &env.current_contract_address(), &recipient, total);
+        }
+        for (stream_id, payout) in paid.iter() {
+            env.events()
+                .publish((symbol_short!("withdrew"), stream_id), payout);
+            notify_withdraw_callback(&env, stream_id, payout);
+        }
+        total
+    }
+
+    /// Calculate the total amount accrued to the recipient so far.
+    pub fn calculate_accrued(env: Env, stream_id: u64) -> i128 {
+        let stream = load_stream(&env, stream_id);
+        Self::accrued_at(&stream, env.ledger().timestamp())
+    }
+
+    /// Amount the recipient could withdraw right now, before any withdrawal
+    /// fee. Same as the `withdrawable` field of `get_stream_view`.
+    pub fn calculate_withdrawable(env: Env, stream_id: u64) -> i128 {
+        let stream = load_stream(&env, stream_id);
+        Self::withdrawable_at(&env, &stream, env.ledger().timestamp())
+    }
+
+    /// Sum of `calculate_withdrawable` over the streams paying `recipient`.
+    ///
+    /// Covers every stream in the recipient index, or only `stream_ids` when
+    /// given, so recipients with many streams can page through them. Ids
+    /// that are archived or pay someone else are skipped.
+    ///
+    /// # Panics
+    /// - If more than 50 `stream_ids` are given.
+    pub fn get_total_withdrawable(
+        env: Env,
+        recipient: Address,
+        stream_ids: Option<Vec<u64>>,
+    ) -> i128 {
+        let ids = match stream_ids {
+            Some(ids) => {
+                assert!(ids.len() <= MAX_STREAM_BATCH, "too many stream ids (max 50)");
+                ids
+            }
+            None => get_index(&env, &DataKey::RecipientStreams(recipient.clone())),
+        };
+        let now = env.ledger().timestamp();
+
+        let mut total = 0;
+        for stream_id in ids.iter() {
+            let Some(stream) = try_load_stream(&env, stream_id) else {
+                continue;
+            };
+            if stream.recipient == recipient {
+                total += Self::withdrawable_at(&env, &stream, now);
+            }
+        }
+        total
+    }
+
+    /// Evaluate the stream's accrual curve at each of `times`.
+    ///
+    /// Returns the accrued amount for every requested timestamp, in order,
+    /// without touching any state. Useful for rendering vesting charts.
+    ///
+    /// # Panics
+    /// - If more than 100 timestamps are requested.
+    pub fn sample_accrual(env: Env, stream_id: u64, times: Vec<u64>) -> Vec<i128> {
         assert!(
-            sender != recipient,
-            "sender and recipient must be different"
+            times.len() <= MAX_SAMPLE_POINTS,
+            "too many sample points (max 100)"
         );
+        let stream = load_stream(&env, stream_id);
+
+        let mut samples = Vec::new(&env);
+        for time in times.iter() {
+            samples.push_back(Self::accrued_at(&stream, time));
+        }
+        samples
+    }
+
+    /// Sample the stream's vesting curve as `(timestamp, cumulative_vested)`
+    /// points for charting.
+    ///
+    /// Takes `sample_count` evenly spaced timestamps from `start_time` to
+    /// `end_time` inclusive, clamped to between 2 and 100, plus `cliff_time`
+    /// if it is not already one of them, in ascending order. Read-only.
+    pub fn precompute_vesting_schedule(
+        env: Env,
+        stream_id: u64,
+        sample_count: u32,
+    ) -> Vec<(u64, i128)> {
+        let stream = load_stream(&env, stream_id);
+        let intervals = (sample_count.clamp(2, MAX_SAMPLE_POINTS) - 1) as u128;
+        let duration = (stream.end_time - stream.start_time) as u128;
+
+        let mut schedule: Vec<(u64, i128)> = Vec::new(&env);
+        let mut push = |time: u64| {
+            if !matches!(schedule.last(), Some((last, _)) if last >= time) {
+                schedule.push_back((time, Self::accrued_at(&stream, time)));
+            }
+        };
+        for i in 0..=intervals {
+            let time = stream.start_time + (duration * i / intervals) as u64;
+            if stream.cliff_time < time {
+                push(stream.cliff_time);
+            }
+            push(time);
+        }
+        schedule
+    }
+
+    /// Version of the contract code: 1 as deployed, plus one per
+    /// `upgrade_contract`.
+    pub fn get_contract_version(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::ContractVersion)
+            .unwrap_or(1)
+    }
+
+    /// Fetches the global configuration, including every admin-settable,
+    /// contract-wide setting.
+    pub fn get_config(env: Env) -> Config {
+        get_config(&env)
+    }
+
+    /// Protocol-wide stream counts and token totals. The counters are kept
+    /// up to date on every stream change rather than computed on demand,
+    /// except that streams which have reached their start since last being
+    /// saved are counted as active here.
+    pub fn get_stats(env: Env) -> Stats {
+        let active_count: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ActiveCount)
+            .unwrap_or(0);
+        Stats {
+            total_streams: get_stream_count(&env),
+            active_count: active_count + started_scheduled_ids(&env).len(),
+            total_locked: instance_total(&env, &DataKey::LockedTotal),
+            total_withdrawn_all_time: instance_total(&env, &DataKey::TotalWithdrawn),
+        }
+    }
 
-        // Validate time constraints
-        assert!(start_time < end_time, "start_time must be before end_time");
+    /// Totals over the streams in `stream_ids`, for breaking figures down by
+    /// cohort. Ids that do not exist or were archived are skipped; an id
+    /// listed twice is counted twice.
+    ///
+    /// # Panics
+    /// - If more than 200 ids are given.
+    pub fn get_bulk_stats(env: Env, stream_ids: Vec<u64>) -> BulkStats {
         assert!(
-            cliff_time >= start_time && cliff_time <= end_time,
-            "cliff_time must be within [start_time, end_time]"
+            stream_ids.len() <= MAX_BULK_STATS_IDS,
+            "too many stream ids (max 200)"
+        );
+        let now = env.ledger().timestamp();
+
+        let mut stats = BulkStats {
+            count: 0,
+            total_deposit: 0,
+            total_accrued: 0,
+            total_withdrawn: 0,
+            total_remaining_in_contract: 0,
+        };
+        for stream_id in stream_ids.iter() {
+            let Some(stream) = try_load_stream(&env, stream_id) else {
+                continue;
+            };
+            stats.count += 1;
+            stats.total_deposit += stream.deposit_amount;
+            stats.total_accrued += Self::accrued_at(&stream, now);
+            stats.total_withdrawn += stream.withdrawn_amount;
+            stats.total_remaining_in_contract += locked_in(&stream);
+        }
+        stats
+    }
+
+    /// Cumulative withdrawal fees collected in `token` since deployment.
+    pub fn get_fees_collected(env: Env, token: Address) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::FeesCollected(token))
+            .unwrap_or(0)
+    }
+
+    /// Whether withdrawals to `recipient` are currently frozen by the admin.
+    pub fn is_recipient_frozen(env: Env, recipient: Address) -> bool {
+        is_frozen(&env, &recipient)
+    }
+
+    /// Ids of streams currently in `status`, in ascending order: the first
+    /// `limit` of them from `start_id` on. Page through by passing one past
+    /// the last id returned. Archived streams are not listed.
+    ///
+    /// # Panics
+    /// - If `limit` exceeds 50.
+    pub fn get_streams_by_status(
+        env: Env,
+        status: StreamStatus,
+        start_id: u64,
+        limit: u32,
+    ) -> Vec<u64> {
+        assert!(limit <= MAX_STREAM_BATCH, "limit too large (max 50)");
+        let mut ids = get_index(&env, &DataKey::StreamsByStatus(status));
+        // Streams that have started since last saved are still indexed as
+        // `Scheduled`; list them under the status `get_stream_state` reports.
+        if matches!(status, StreamStatus::Scheduled | StreamStatus::Active) {
+            for stream_id in started_scheduled_ids(&env).iter() {
+                match ids.binary_search(stream_id) {
+                    Ok(pos) if status == StreamStatus::Scheduled => {
+                        ids.remove(pos);
+                    }
+                    Err(pos) if status == StreamStatus::Active => ids.insert(pos, stream_id),
+                    _ => {}
+                }
+            }
+        }
+        let from = match ids.binary_search(start_id) {
+            Ok(pos) | Err(pos) => pos,
+        };
+        ids.slice(from..ids.len().min(from.saturating_add(limit)))
+    }
+
+    /// Ids of every stream sent by `sender`, in creation order.
+    pub fn get_streams_by_sender(env: Env, sender: Address) -> Vec<u64> {
+        get_index(&env, &DataKey::SenderStreams(sender))
+    }
+
+    /// Ids of every stream paying `recipient`, in creation order.
+    pub fn get_streams_by_recipient(env: Env, recipient: Address) -> Vec<u64> {
+        get_index(&env, &DataKey::RecipientStreams(recipient))
+    }
+
+    /// Ids of every stream tagged with `category`, in tagging order.
+    pub fn get_streams_by_category(env: Env, category: StreamCategory) -> Vec<u64> {
+        get_index(&env, &DataKey::CategoryIndex(category))
+    }
+
+    /// Aggregate the streams `address` sends and receives for a dashboard.
+    ///
+    /// Claimable amounts are what `calculate_withdrawable` reports, so they
+    /// exclude paused, frozen and disputed streams and honour withdrawal
+    /// schedules. Refundable amounts are what cancelling each stream would
+    /// refund now, including streams yet to start, and exclude locked streams
+    /// (they cannot be cancelled). Archived streams are skipped.
+    pub fn get_streams_summary_for_address(env: Env, address: Address) -> AddressSummary {
+        let now = env.ledger().timestamp();
+        let mut summary = AddressSummary {
+            active_sent: 0,
+            active_received: 0,
+            total_claimable: 0,
+            total_refundable: 0,
+            total_deposited_by_sender: 0,
+            total_received_by_recipient: 0,
+        };
+
+        for stream_id in get_index(&env, &DataKey::SenderStreams(address.clone())).iter() {
+            let Some(stream) = try_load_stream(&env, stream_id) else {
+                continue;
+            };
+            summary.total_deposited_by_sender += stream.deposit_amount;
+            if stream.status == StreamStatus::Active {
+                summary.active_sent += 1;
+            }
+            if stream.status.is_open() && !stream.immutable {
+                let accrued = Self::accrued_at(&stream, now);
+                summary.total_refundable +=
+                    Self::cancel_settlement(&stream, accrued).refund_to_sender;
+            }
+        }
+
+        for stream_id in get_index(&env, &DataKey::RecipientStreams(address)).iter() {
+            let Some(stream) = try_load_stream(&env, stream_id) else {
+                continue;
+            };
+            summary.total_received_by_recipient += stream.withdrawn_amount;
+            if stream.status == StreamStatus::Active {
+                summary.active_received += 1;
+            }
+            summary.total_claimable += Self::withdrawable_at(&env, &stream, now);
+        }
+
+        summary
+    }
+
+    /// Return the current state of the stream identified by `stream_id`.
+    pub fn get_stream_state(env: Env, stream_id: u64) -> Stream {
+        load_stream(&env, stream_id)
+    }
+
+    /// Return the stream with its accrued, withdrawable and refundable
+    /// amounts and time remaining, so clients need not repeat the contract's
+    /// accrual rules.
+    pub fn get_stream_view(env: Env, stream_id: u64) -> StreamView {
+        let stream = load_stream(&env, stream_id);
+        let now = env.ledger().timestamp();
+        let accrued = Self::accrued_at(&stream, now);
+        let withdrawable = Self::withdrawable_at(&env, &stream, now);
+        let (refundable_if_cancelled_now, seconds_remaining) = match stream.status {
+            StreamStatus::Scheduled | StreamStatus::Active | StreamStatus::Paused => {
+                let refundable = if stream.immutable {
+                    0
+                } else {
+                    Self::cancel_settlement(&stream, accrued).refund_to_sender
+                };
+                (refundable, stream.end_time.saturating_sub(now))
+            }
+            StreamStatus::Completed | StreamStatus::Cancelled | StreamStatus::Arbitrated => (0, 0),
+        };
+
+        StreamView {
+            stream,
+            accrued,
+            withdrawable,
+            refundable_if_cancelled_now,
+            seconds_remaining,
+        }
+    }
+
+    /// Vested and withdrawn percentages and lifecycle phase of a stream, for
+    /// wallets to render directly. Percentages are relative to
+    /// `get_total_streamable`, so an overfunded stream still reaches 10000,
+    /// as does a cancelled stream once its deposit is cut to what accrued.
+    pub fn get_stream_summary(env: Env, stream_id: u64) -> StreamSummary {
+        let stream = load_stream(&env, stream_id);
+        let now = env.ledger().timestamp();
+        let total = total_streamable(&stream);
+
+        let phase = match stream.status {
+            StreamStatus::Completed | StreamStatus::Cancelled | StreamStatus::Arbitrated => {
+                StreamPhase::Terminal
+            }
+            StreamStatus::Scheduled | StreamStatus::Active | StreamStatus::Paused => {
+                if now < stream.start_time {
+                    StreamPhase::NotStarted
+                } else if now < stream.cliff_time {
+                    StreamPhase::Cliff
+                } else if now < stream.end_time {
+                    StreamPhase::Streaming
+                } else {
+                    StreamPhase::Ended
+                }
+            }
+        };
+
+        StreamSummary {
+            progress_bps: share_bps(Self::accrued_at(&stream, now), total),
+            withdrawn_bps: share_bps(stream.withdrawn_amount, total),
+            phase,
+        }
+    }
+
+    /// SHA-256 of the XDR encoding of the tuple `(stream_id, sender,
+    /// recipient, deposit_amount, rate_per_second, start_time, cliff_time,
+    /// end_time)`, for off-chain parties to sign as an attestation of a
+    /// stream's creation parameters. The hash changes if any of these is
+    /// later amended.
+    pub fn get_stream_hash_for_signing(env: Env, stream_id: u64) -> BytesN<32> {
+        let stream = load_stream(&env, stream_id);
+        let stream_bytes = (
+            stream.stream_id,
+            stream.sender,
+            stream.recipient,
+            stream.deposit_amount,
+            stream.rate_per_second,
+            stream.start_time,
+            stream.cliff_time,
+            stream.end_time,
+        )
+            .to_xdr(&env);
+        env.crypto().sha256(&stream_bytes).into()
+    }
+
+    /// Return the current state of each stream in `ids`, in order.
+    ///
+    /// # Panics
+    /// - If more than 50 ids are requested.
+    /// - If any id does not exist (or was archived).
+    pub fn get_stream_states(env: Env, ids: Vec<u64>) -> Vec<Stream> {
+        assert!(ids.len() <= MAX_STREAM_BATCH, "too many stream ids (max 50)");
+
+        let mut streams = Vec::new(&env);
+        for stream_id in ids.iter() {
+            streams.push_back(load_stream(&env, stream_id));
+        }
+        streams
+    }
+
+    /// Status transitions of a stream as `(new_status, timestamp)`, oldest
+    /// first, starting with its creation. Only the latest 50 are kept. The
+    /// trail outlives archiving; it is empty for unknown ids.
+    pub fn get_audit_trail(env: Env, stream_id: u64) -> Vec<(StreamStatus, u64)> {
+        let mut trail: Vec<(StreamStatus, u64)> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::AuditTrail(stream_id))
+            .unwrap_or(Vec::new(&env));
+        // A start not yet persisted by `promote_if_started`
+        let stored: Option<Stream> = env.storage().persistent().get(&DataKey::Stream(stream_id));
+        if let Some(stream) = stored.filter(|stream| has_started(&env, stream)) {
+            if trail.len() == MAX_AUDIT_ENTRIES {
+                trail.pop_front();
+            }
+            trail.push_back((StreamStatus::Active, stream.start_time));
+        }
+        trail
+    }
+
+    /// Return the compact record of an archived stream.
+    ///
+    /// # Panics
+    /// - If the stream has not been archived.
+    pub fn get_archived_stream(env: Env, stream_id: u64) -> ArchivedStream {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ArchivedStream(stream_id))
+            .expect("archived stream not found")
+    }
+
+    /// Whether `stream_id` was ever created, whether it is live or archived.
+    pub fn stream_exists(env: Env, stream_id: u64) -> bool {
+        let storage = env.storage().persistent();
+        storage.has(&DataKey::Stream(stream_id))
+            || storage.has(&DataKey::ArchivedStream(stream_id))
+    }
+
+    /// Whether the stream has been made irrevocable with `lock_stream`.
+    pub fn is_stream_locked(env: Env, stream_id: u64) -> bool {
+        load_stream(&env, stream_id).immutable
+    }
+
+    /// Time recipients have to withdraw from a cancelled stream before its
+    /// sender may reclaim it: 90 days unless the admin has changed it.
+    pub fn get_withdraw_grace_period(env: Env) -> u64 {
+        get_config(&env).withdraw_grace_period
+    }
+
+    /// Smallest deposit a new stream may have, if the admin has set one.
+    pub fn get_min_deposit(env: Env) -> Option<i128> {
+        get_config(&env).min_deposit
+    }
+
+    /// Shortest cliff, in seconds after `start_time`, a new stream may have,
+    /// if the admin has set one.
+    pub fn get_min_cliff_duration(env: Env) -> Option<u64> {
+        get_config(&env).min_cliff_duration
+    }
+
+    /// Longest the stream may stay paused before anyone can resume it, if it
+    /// was created with a limit.
+    pub fn get_max_pause_seconds(env: Env, stream_id: u64) -> Option<u64> {
+        env.storage().persistent().get(&DataKey::MaxPause(stream_id))
+    }
+
+    /// Application reference attached with `set_reference_hash`, if any.
+    pub fn get_reference_hash(env: Env, stream_id: u64) -> Option<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ReferenceHash(stream_id))
+    }
+
+    /// Return the per-call cap on operator withdrawals for `stream_id`, if any.
+    pub fn get_auto_withdraw_cap(env: Env, stream_id: u64) -> Option<i128> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::AutoWithdrawCap(stream_id))
+    }
+
+    /// Return the withdrawal callback registered for `stream_id`, if any.
+    pub fn get_withdraw_callback(env: Env, stream_id: u64) -> Option<WithdrawCallback> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::WithdrawCallback(stream_id))
+    }
+
+    /// Return the earliest time the recipient may next withdraw under the
+    /// stream's withdrawal schedule, or `None` if withdrawals are not
+    /// restricted (no schedule, or every entry has been claimed).
+    pub fn get_next_withdraw_time(env: Env, stream_id: u64) -> Option<u64> {
+        next_withdraw_time(&load_stream(&env, stream_id))
+    }
+
+    /// Return the current holder of a stream's receipt, or `None` if it was
+    /// created without one or the receipt has been burned.
+    pub fn get_receipt_holder(env: Env, stream_id: u64) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::Receipt(stream_id))
+    }
+
+    /// Address the stream was created for, which differs from its current
+    /// `recipient` once the position has been transferred.
+    pub fn get_original_recipient(env: Env, stream_id: u64) -> Address {
+        env.storage()
+            .persistent()
+            .get(&DataKey::OriginalRecipient(stream_id))
+            .unwrap_or_else(|| load_stream(&env, stream_id).recipient)
+    }
+
+    /// Return the pending co-cancel proposal for `stream_id`, if any.
+    pub fn get_co_cancel_proposal(env: Env, stream_id: u64) -> Option<CoCancelProposal> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::CoCancelProposal(stream_id))
+    }
+
+    /// Return the renewal terms of a recurring stream.
+    ///
+    /// # Panics
+    /// - If the stream is not recurring.
+    pub fn get_recurring_config(env: Env, stream_id: u64) -> RecurringConfig {
+        load_recurring(&env, stream_id).expect("stream is not recurring")
+    }
+
+    /// Return the stream created with the given external reference.
+    ///
+    /// # Panics
+    /// - If no stream was created with `external_ref`.
+    pub fn get_stream_by_ref(env: Env, external_ref: BytesN<32>) -> Stream {
+        let stream_id: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ByRef(external_ref))
+            .expect("external_ref not found");
+        load_stream(&env, stream_id)
+    }
+
+    /// Length of the stream's schedule in seconds (`end_time - start_time`).
+    pub fn get_stream_duration(env: Env, stream_id: u64) -> u64 {
+        let stream = load_stream(&env, stream_id);
+        stream.end_time - stream.start_time
+    }
+
+    /// `rate_per_second` scaled to a 365-day year, for display. Saturates at
+    /// `i128::MAX`.
+    pub fn get_stream_annual_rate_display(env: Env, stream_id: u64) -> i128 {
+        let stream = load_stream(&env, stream_id);
+        stream
+            .rate_per_second
+            .checked_mul(SECONDS_PER_YEAR)
+            .unwrap_or(i128::MAX)
+    }
+
+    /// `rate_per_second` scaled to an average month (a twelfth of a 365-day
+    /// year), for display. Saturates at `i128::MAX`.
+    pub fn get_stream_monthly_rate_display(env: Env, stream_id: u64) -> i128 {
+        let stream = load_stream(&env, stream_id);
+        stream
+            .rate_per_second
+            .checked_mul(SECONDS_PER_MONTH)
+            .unwrap_or(i128::MAX)
+    }
+
+    /// Seconds until the stream's `end_time`, for countdowns. 0 once the end
+    /// has passed and for streams that have ended.
+    pub fn time_remaining(env: Env, stream_id: u64) -> u64 {
+        let stream = load_stream(&env, stream_id);
+        match stream.status {
+            StreamStatus::Completed | StreamStatus::Cancelled | StreamStatus::Arbitrated => 0,
+            StreamStatus::Scheduled | StreamStatus::Active | StreamStatus::Paused => {
+                stream.end_time.saturating_sub(env.ledger().timestamp())
+            }
+        }
+    }
+
+    /// Ledger time at which the stream will have accrued everything it will
+    /// ever stream (`get_total_streamable`), or `None` if it already has.
+    /// Also `None` for a milestone stream, which vests on approval rather
+    /// than over time and so has no such time until its last approval.
+    ///
+    /// Deposits always cover `rate_per_second * duration`, so linear and
+    /// stepped streams both vest fully at `end_time`; a cliff only delays
+    /// when accrual becomes claimable, not when it completes.
+    pub fn get_time_to_fully_vest(env: Env, stream_id: u64) -> Option<u64> {
+        let stream = load_stream(&env, stream_id);
+        if !stream.milestones.is_empty()
+            || Self::accrued_at(&stream, env.ledger().timestamp()) >= total_streamable(&stream)
+        {
+            return None;
+        }
+        Some(stream.end_time)
+    }
+
+    /// Total amount the schedule will ever stream to the recipient:
+    /// `min(rate_per_second * duration, deposit_amount)`.
+    pub fn get_total_streamable(env: Env, stream_id: u64) -> i128 {
+        total_streamable(&load_stream(&env, stream_id))
+    }
+
+    /// Portion of the deposit the schedule never streams
+    /// (`deposit_amount - get_total_streamable`). Refunded to the refund address when
+    /// the stream completes or is cancelled.
+    pub fn get_stream_dust(env: Env, stream_id: u64) -> i128 {
+        let deposit_amount = load_stream(&env, stream_id).deposit_amount;
+        deposit_amount - Self::get_total_streamable(env, stream_id)
+    }
+
+    /// Portion of the deposit not yet vested: `deposit_amount - accrued` for
+    /// `Active` and `Paused` streams, i.e. what a cancel would refund right
+    /// now. 0 once the stream has ended.
+    pub fn get_locked(env: Env, stream_id: u64) -> i128 {
+        let stream = load_stream(&env, stream_id);
+        match stream.status {
+            StreamStatus::Scheduled | StreamStatus::Active | StreamStatus::Paused => {
+                let accrued = Self::accrued_at(&stream, env.ledger().timestamp());
+                Self::cancel_settlement(&stream, accrued).refund_to_sender
+            }
+            StreamStatus::Completed | StreamStatus::Cancelled | StreamStatus::Arbitrated => 0,
+        }
+    }
+
+    /// Internal helper splitting the stream's deposit as cancelling it with
+    /// `accrued` vested would.
+    fn cancel_settlement(stream: &Stream, accrued: i128) -> accrual::Settlement {
+        accrual::settlement_amounts(
+            stream.deposit_amount,
+            accrued,
+            stream.withdrawn_amount,
+        )
+    }
+
+    /// Internal helper evaluating a stream's schedule at `current_time`.
+    fn accrued_at(stream: &Stream, current_time: u64) -> i128 {
+        if matches!(
+            stream.status,
+            StreamStatus::Cancelled | StreamStatus::Arbitrated
+        ) {
+            // Cancellation cut the deposit down to what had accrued
+            return stream.deposit_amount;
+        }
+        if !stream.milestones.is_empty() {
+            return match stream.milestones_approved {
+                0 => 0,
+                approved => stream.milestones.get_unchecked(approved - 1),
+            };
+        }
+        if stream.step_seconds > 0 {
+            return accrual::calculate_stepped_accrued_amount(
+                stream.start_time,
+                stream.end_time,
+                stream.step_seconds,
+                stream.deposit_amount,
+                current_time,
+            );
+        }
+        if current_time < stream.cliff_time {
+            return 0;
+        }
+        stream.accrued_checkpoint
+            + accrual::calculate_accrued_amount(
+                stream.start_time,
+                stream.cliff_time,
+                stream.end_time,
+                stream.rate_per_second,
+                stream.deposit_amount - stream.accrued_checkpoint,
+                current_time,
+            )
+    }
+
+    /// What `withdraw_available` would pay out of `stream` at `current_time`,
+    /// before any withdrawal fee: 0 while it is paused, completed, frozen,
+    /// disputed or between scheduled claim times.
+    fn withdrawable_at(env: &Env, stream: &Stream, current_time: u64) -> i128 {
+        let claimable = (stream.status == StreamStatus::Active
+            || stream.status == StreamStatus::Cancelled
+            || pause_expired(env, stream))
+            && !is_frozen(env, &stream.recipient)
+            && !is_disputed(env, stream.stream_id)
+            && !matches!(next_withdraw_time(stream), Some(next) if current_time < next);
+        if claimable {
+            // Negative while an advance is still ahead of accrual
+            (Self::accrued_at(stream, current_time) - stream.withdrawn_amount).max(0)
+        } else {
+            0
+        }
+    }
+
+    /// Internal helper pausing an authorized caller's stream.
+    fn apply_pause(env: &Env, mut stream: Stream) {
+        assert!(!stream.immutable, "stream is locked");
+        assert!(
+            matches!(stream.status, StreamStatus::Active | StreamStatus::Scheduled),
+            "stream is not active"
+        );
+
+        set_status(env, &mut stream, StreamStatus::Paused);
+        save_stream(env, &stream);
+        if env.storage().persistent().has(&DataKey::MaxPause(stream.stream_id)) {
+            let key = DataKey::PausedAt(stream.stream_id);
+            env.storage().persistent().set(&key, &env.ledger().timestamp());
+            env.storage().persistent().extend_ttl(&key, 17280, 120960);
+        }
+
+        env.events().publish(
+            (symbol_short!("paused"), stream.stream_id),
+            env.ledger().timestamp(),
+        );
+    }
+
+    /// Internal helper resuming an authorized caller's stream.
+    fn apply_resume(env: &Env, mut stream: Stream) {
+        assert!(
+            stream.status == StreamStatus::Paused,
+            "stream is not paused"
+        );
+
+        Self::resume_in_place(env, &mut stream);
+        save_stream(env, &stream);
+    }
+
+    /// Mark a paused stream `Active` again, leaving the caller to save it.
+    fn resume_in_place(env: &Env, stream: &mut Stream) {
+        set_status(env, stream, running_status(env, stream));
+        let storage = env.storage().persistent();
+        storage.remove(&DataKey::PausedAt(stream.stream_id));
+        storage.remove(&DataKey::AutoResumeAt(stream.stream_id));
+
+        env.events().publish(
+            (symbol_short!("resumed"), stream.stream_id),
+            env.ledger().timestamp(),
+        );
+    }
+
+    /// Record notice of a cancellation if the stream requires one. Returns
+    /// `false`, changing nothing, if it does not.
+    fn give_cancel_notice(env: &Env, stream: &Stream) -> bool {
+        let storage = env.storage().persistent();
+        let Some(notice) = storage.get::<_, u64>(&DataKey::CancelNotice(stream.stream_id)) else {
+            return false;
+        };
+        assert!(!stream.immutable, "stream is locked");
+        assert!(
+            stream.status.is_open(),
+            "stream must be active or paused to cancel"
+        );
+        let key = DataKey::CancelNoticeAt(stream.stream_id);
+        assert!(!storage.has(&key), "cancellation notice already given");
+
+        let now = env.ledger().timestamp();
+        storage.set(&key, &now);
+        storage.extend_ttl(&key, 17280, 120960);
+        env.events().publish(
+            (symbol_short!("cncl_ntc"), stream.stream_id),
+            now.saturating_add(notice),
+        );
+        true
+    }
+
+    /// Internal helper splitting an arbitrated stream's remaining balance.
+    fn apply_arbitration(env: &Env, mut stream: Stream, recipient_share_bps: u32) {
+        assert!(
+            recipient_share_bps <= BPS_DENOMINATOR,
+            "recipient_share_bps must be at most 10000"
+        );
+        let stream_id = stream.stream_id;
+        assert!(!stream.immutable, "stream is locked");
+        assert!(
+            stream.status.is_open()
+                || (stream.status == StreamStatus::Cancelled && is_disputed(env, stream_id)),
+            "stream must be active, paused or disputed to arbitrate"
         );
 
-        // Validate deposit covers total streamable amount (#34)
-        let duration = (end_time - start_time) as i128;
-        let total_streamable = rate_per_second
-            .checked_mul(duration)
-            .expect("overflow calculating total streamable amount");
+        let remaining = stream.deposit_amount - stream.withdrawn_amount;
+        let share = bps_of(remaining, recipient_share_bps);
+        let refund = remaining - share;
+
+        stream.withdrawn_amount += share;
+        stream.deposit_amount = stream.withdrawn_amount;
+        set_status(env, &mut stream, StreamStatus::Arbitrated);
+        save_stream(env, &stream);
+        clear_pending_admin_cancel(env, stream_id);
+        let storage = env.storage().persistent();
+        storage.remove(&DataKey::Disputed(stream_id));
+        storage.remove(&DataKey::CancelNoticeAt(stream_id));
+        storage.remove(&DataKey::CoCancelProposal(stream_id));
+        burn_receipt(env, stream_id);
+
+        let contract = env.current_contract_address();
+        if share > 0 {
+            assert!(!is_frozen(env, &stream.recipient), "recipient is frozen");
+            check_compliance(env, &stream.recipient, share);
+            transfer_token(env, &contract, &stream.recipient, share);
+        }
+        if refund > 0 {
+            transfer_token(env, &contract, &stream.refund_address, refund);
+        }
+
+        env.events().publish(
+            (symbol_short!("arbitrate"), stream_id),
+            (recipient_share_bps, share, refund),
+        );
+    }
+
+    /// Internal helper cancelling an authorized caller's stream and refunding
+    /// the unstreamed deposit to its refund address. Returns the refund.
+    fn apply_cancel(env: &Env, mut stream: Stream) -> i128 {
+        assert!(!stream.immutable, "stream is locked");
         assert!(
-            deposit_amount >= total_streamable,
-            "deposit_amount must cover total streamable amount (rate * duration)"
+            stream.status.is_open(),
+            "stream must be active or paused to cancel"
         );
 
-        // Transfer tokens from sender to this contract (#36)
-        // If transfer fails (insufficient balance/allowance), this will panic
-        // and no state will be persisted (atomic transaction)
-        let token_client = token::Client::new(&env, &get_token(&env));
-        token_client.transfer(&sender, &env.current_contract_address(), &deposit_amount);
-
-        // Only allocate stream id and persist state AFTER successful transfer
-        let stream_id = get_stream_count(&env);
-        set_stream_count(&env, stream_id + 1);
+        let accrued = Self::accrued_at(&stream, env.ledger().timestamp());
+        let unstreamed = Self::cancel_settlement(&stream, accrued).refund_to_sender;
 
-        let stream = Stream {
-            stream_id,
-            sender,
-            recipient,
-            deposit_amount,
-            rate_per_second,
-            start_time,
-            cliff_time,
-            end_time,
-            withdrawn_amount: 0,
-            status: StreamStatus::Active,
-        };
+        // Only what had accrued stays claimable; nothing accrues afterwards
+        stream.deposit_amount -= unstreamed;
+        set_status(env, &mut stream, StreamStatus::Cancelled);
+        save_stream(env, &stream);
+        clear_pending_admin_cancel(env, stream.stream_id);
+        let key = DataKey::CancelledAt(stream.stream_id);
+        env.storage().persistent().set(&key, &env.ledger().timestamp());
+        env.storage().persistent().extend_ttl(&key, 17280, 120960);
+        let storage = env.storage().persistent();
+        storage.remove(&DataKey::CancelNoticeAt(stream.stream_id));
+        storage.remove(&DataKey::AutoResumeAt(stream.stream_id));
 
-        save_stream(&env, &stream);
+        // Refunded only once the cancellation is committed
+        if unstreamed > 0 {
+            transfer_token(
+                env,
+                &env.current_contract_address(),
+                &stream.refund_address,
+                unstreamed,
+            );
+        }
 
         env.events()
-            .publish((symbol_short!("created"), stream_id), deposit_amount);
+            .publish((symbol_short!("cancelled"), stream.stream_id), unstreamed);
+        unstreamed
+    }
 
-        stream_id
+    /// Internal helper requiring auth from the stream's sender delegate.
+    fn require_delegate(stream: &Stream) {
+        stream
+            .delegate
+            .as_ref()
+            .expect("stream has no sender delegate")
+            .require_auth();
     }
 
-    /// Pause an active stream. Only the sender or admin may call this.
+    /// Internal helper to check authorization for sender or admin.
+    fn require_sender_or_admin(env: &Env, sender: &Address) {
+        let admin = get_admin(env);
+
+        // If the admin is the one calling, they must authorize.
+        // Otherwise, the sender must authorize.
+        if sender != &admin {
+            // This allows the admin to bypass the sender's auth
+            // if we use a separate admin entrypoint, or we can
+            // rely on the transaction signatures.
+            sender.require_auth();
+        } else {
+            admin.require_auth();
+        }
+    }
+}
+
+#[contractimpl]
+impl FluxoraStream {
+    /// Charge `fee_bps` basis points of every withdrawal, paid to
+    /// `fee_collector`. A `fee_bps` of 0 disables the fee. Admin only.
+    ///
     /// # Panics
-    /// - If the stream is not in `Active` state.
-    pub fn pause_stream(env: Env, stream_id: u64) {
-        let mut stream = load_stream(&env, stream_id);
+    /// - If `fee_bps` exceeds 10000.
+    /// - If governance is configured (use a `SetWithdrawalFee` proposal).
+    pub fn set_withdrawal_fee(env: Env, fee_bps: u32, fee_collector: Address) {
+        governance::require_admin(&env);
+        Self::apply_withdrawal_fee(&env, fee_bps, fee_collector);
+    }
 
-        // Corrected Auth Check
-        Self::require_sender_or_admin(&env, &stream.sender);
+    /// Shared body of `set_withdrawal_fee` and its governance proposal.
+    fn apply_withdrawal_fee(env: &Env, fee_bps: u32, fee_collector: Address) {
+        assert!(fee_bps <= BPS_DENOMINATOR, "fee_bps cannot exceed 10000");
+
+        let mut config = get_config(env);
+        config.fee_bps = fee_bps;
+        config.fee_collector = Some(fee_collector.clone());
+        save_config(env, &config);
+
+        env.events()
+            .publish((symbol_short!("fee_set"),), (fee_bps, fee_collector));
+    }
+
+    /// Send future withdrawal fees to `new_collector`, keeping the current
+    /// fee rate. Admin only.
+    ///
+    /// # Panics
+    /// - If `new_collector` is this contract.
+    /// - If governance is configured (use a `SetFeeCollector` proposal).
+    pub fn set_fee_collector(env: Env, new_collector: Address) {
+        governance::require_admin(&env);
+        Self::apply_fee_collector(&env, new_collector);
+    }
 
+    /// Shared body of `set_fee_collector` and its governance proposal.
+    fn apply_fee_collector(env: &Env, new_collector: Address) {
         assert!(
-            stream.status == StreamStatus::Active,
-            "stream is not active"
+            new_collector != env.current_contract_address(),
+            "fee collector cannot be the contract"
         );
 
-        stream.status = StreamStatus::Paused;
-        save_stream(&env, &stream);
+        let mut config = get_config(env);
+        config.fee_collector = Some(new_collector.clone());
+        save_config(env, &config);
 
         env.events()
-            .publish((symbol_short!("paused"), stream_id), ());
+            .publish((Symbol::new(env, "feecollector"),), new_collector);
     }
 
-    /// Resume a paused stream. Only the sender or admin may call this.
+    /// Reject new streams whose deposit is below `min_deposit`; `None`
+    /// removes the minimum. Admin only. Existing streams are unaffected.
+    ///
     /// # Panics
-    /// - If the stream is not in `Paused` state.
-    pub fn resume_stream(env: Env, stream_id: u64) {
-        let mut stream = load_stream(&env, stream_id);
-        Self::require_sender_or_admin(&env, &stream.sender);
+    /// - If governance is configured (use a `SetMinDeposit` proposal).
+    pub fn set_min_deposit(env: Env, min_deposit: Option<i128>) {
+        governance::require_admin(&env);
+        Self::apply_min_deposit(&env, min_deposit);
+    }
 
-        assert!(
-            stream.status == StreamStatus::Paused,
-            "stream is not paused"
-        );
+    /// Shared body of `set_min_deposit` and its governance proposal.
+    fn apply_min_deposit(env: &Env, min_deposit: Option<i128>) {
+        let mut config = get_config(env);
+        config.min_deposit = min_deposit;
+        save_config(env, &config);
 
-        stream.status = StreamStatus::Active;
-        save_stream(&env, &stream);
+        env.events()
+            .publish((symbol_short!("min_dep"),), min_deposit);
+    }
+
+    /// Set how long recipients have to withdraw from a cancelled stream
+    /// before its sender may call `reclaim_after_expiry`. Admin only.
+    /// Applies to streams already cancelled too.
+    ///
+    /// # Panics
+    /// - If governance is configured (use a `SetWithdrawGracePeriod` proposal).
+    pub fn set_withdraw_grace_period(env: Env, grace_period: u64) {
+        governance::require_admin(&env);
+        Self::apply_withdraw_grace_period(&env, grace_period);
+    }
+
+    /// Shared body of `set_withdraw_grace_period` and its governance proposal.
+    fn apply_withdraw_grace_period(env: &Env, grace_period: u64) {
+        let mut config = get_config(env);
+        config.withdraw_grace_period = grace_period;
+        save_config(env, &config);
 
         env.events()
-            .publish((symbol_short!("resumed"), stream_id), ());
+            .publish((symbol_short!("grace"),), grace_period);
     }
 
-    /// Cancel a stream and refund unstreamed funds to the sender.
+    /// Reject new streams whose cliff is less than `min_cliff` seconds after
+    /// their start; `None` removes the requirement. Admin only. Existing
+    /// streams are unaffected, but can no longer lower their cliff below it.
     ///
-    /// ## Behaviour
-    /// 1. **Auth** — only the original sender or the contract admin can cancel.
-    /// 2. **State check** — only `Active` or `Paused` streams can be cancelled.
-    /// 3. **Accrual** — computes `accrued = min((now − start_time) × rate, deposit_amount)`.
-    /// 4. **Refund** — transfers `deposit_amount − accrued` back to the sender immediately.
-    /// 5. **Persistence** — the portion `accrued − withdrawn_amount` remains for the recipient.
-    pub fn cancel_stream(env: Env, stream_id: u64) {
-        let mut stream = load_stream(&env, stream_id);
-        Self::require_sender_or_admin(&env, &stream.sender);
+    /// # Panics
+    /// - If governance is configured (use a `SetMinCliffDuration` proposal).
+    pub fn set_min_cliff_duration(env: Env, min_cliff: Option<u64>) {
+        governance::require_admin(&env);
+        Self::apply_min_cliff_duration(&env, min_cliff);
+    }
 
-        assert!(
-            stream.status == StreamStatus::Active || stream.status == StreamStatus::Paused,
-            "stream must be active or paused to cancel"
-        );
+    /// Shared body of `set_min_cliff_duration` and its governance proposal.
+    fn apply_min_cliff_duration(env: &Env, min_cliff: Option<u64>) {
+        let mut config = get_config(env);
+        config.min_cliff_duration = min_cliff;
+        save_config(env, &config);
 
-        let accrued = Self::calculate_accrued(env.clone(), stream_id);
-        let unstreamed = stream.deposit_amount - accrued;
+        env.events()
+            .publish((symbol_short!("min_cliff"),), min_cliff);
+    }
 
-        if unstreamed > 0 {
-            let token_client = token::Client::new(&env, &get_token(&env));
-            token_client.transfer(&env.current_contract_address(), &stream.sender, &unstreamed);
-        }
+    /// Replace the contract's code with the uploaded WASM `new_wasm_hash`.
+    /// Admin only. Storage is preserved; the contract version is bumped and
+    /// the upgrade appended to the upgrade history. The new code takes effect
+    /// from the next invocation.
+    ///
+    /// # Panics
+    /// - If governance is configured (use an `Upgrade` proposal).
+    pub fn upgrade_contract(env: Env, new_wasm_hash: BytesN<32>) {
+        governance::require_admin(&env);
+        Self::apply_upgrade(&env, new_wasm_hash);
+    }
 
-        stream.status = StreamStatus::Cancelled;
-        save_stream(&env, &stream);
+    /// Shared body of `upgrade_contract` and its governance proposal.
+    fn apply_upgrade(env: &Env, new_wasm_hash: BytesN<32>) {
+        let version = Self::get_contract_version(env.clone()) + 1;
+        env.storage()
+            .instance()
+            .set(&DataKey::ContractVersion, &version);
+
+        let mut history: Vec<(u32, BytesN<32>, u64)> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::UpgradeHistory)
+            .unwrap_or(Vec::new(env));
+        history.push_back((version, new_wasm_hash.clone(), env.ledger().timestamp()));
+        env.storage()
+            .persistent()
+            .set(&DataKey::UpgradeHistory, &history);
+        env.storage()
+            .persistent()
+            .extend_ttl(&DataKey::UpgradeHistory, 17280, 120960);
 
         env.events()
-            .publish((symbol_short!("cancelled"), stream_id), unstreamed);
+            .publish((symbol_short!("upgraded"), version), new_wasm_hash.clone());
+
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
     }
 
-    /// Withdraw accrued-but-not-yet-withdrawn tokens to the recipient.
-    /// Returns the amount transferred.
+    /// Block all withdrawals to `recipient` (compliance hold) without
+    /// touching their streams. Accrual continues, so nothing is lost once
+    /// unfrozen. Admin only.
     ///
     /// # Panics
-    /// - If the stream is `Completed` (nothing left to withdraw).
-    /// - If the stream is `Paused` (withdrawals not allowed while paused).
-    /// - If there is nothing to withdraw (accrued == withdrawn).
-    pub fn withdraw(env: Env, stream_id: u64) -> i128 {
-        let mut stream = load_stream(&env, stream_id);
+    /// - If governance is configured (use a `FreezeRecipient` proposal).
+    pub fn freeze_recipient(env: Env, recipient: Address) {
+        governance::require_admin(&env);
+        Self::apply_freeze(&env, recipient);
+    }
 
-        // Enforce recipient-only authorization: only the stream's recipient can withdraw
-        // This is equivalent to checking env.invoker() == stream.recipient
-        // require_auth() ensures only the recipient can authorize this call,
-        // preventing anyone from withdrawing on behalf of the recipient
-        stream.recipient.require_auth();
+    /// Shared body of `freeze_recipient` and its governance proposal.
+    fn apply_freeze(env: &Env, recipient: Address) {
+        let key = DataKey::FrozenRecipient(recipient.clone());
+        env.storage().persistent().set(&key, &true);
+        env.storage().persistent().extend_ttl(&key, 17280, 120960);
+
+        env.events()
+            .publish((symbol_short!("frozen"),), recipient);
+    }
+
+    /// Lift a freeze placed by `freeze_recipient`. Admin only.
+    ///
+    /// # Panics
+    /// - If governance is configured (use an `UnfreezeRecipient` proposal).
+    pub fn unfreeze_recipient(env: Env, recipient: Address) {
+        governance::require_admin(&env);
+        Self::apply_unfreeze(&env, recipient);
+    }
+
+    /// Shared body of `unfreeze_recipient` and its governance proposal.
+    fn apply_unfreeze(env: &Env, recipient: Address) {
+        env.storage()
+            .persistent()
+            .remove(&DataKey::FrozenRecipient(recipient.clone()));
+
+        env.events()
+            .publish((symbol_short!("unfrozen"),), recipient);
+    }
+
+    /// Allow recipients as well as senders to raise disputes (or, with
+    /// `false`, only senders). Admin only.
+    ///
+    /// # Panics
+    /// - If governance is configured (use a `SetRecipientMayDispute` proposal).
+    pub fn set_recipient_may_dispute(env: Env, enabled: bool) {
+        governance::require_admin(&env);
+        Self::apply_recipient_may_dispute(&env, enabled);
+    }
+
+    /// Shared body of `set_recipient_may_dispute` and its governance proposal.
+    fn apply_recipient_may_dispute(env: &Env, enabled: bool) {
+        let mut config = get_config(env);
+        config.recipient_may_dispute = enabled;
+        save_config(env, &config);
+    }
 
+    /// Whether recipients may raise disputes, as set by
+    /// `set_recipient_may_dispute`. `false` by default.
+    pub fn get_recipient_may_dispute(env: Env) -> bool {
+        get_config(&env).recipient_may_dispute
+    }
+
+    /// Hold a stream's payouts while a deliverable is contested. Requires
+    /// `caller`'s auth; `caller` must be the sender, or the recipient if
+    /// `set_recipient_may_dispute` allows it.
+    ///
+    /// Nothing else changes: the stream keeps accruing and can still be
+    /// paused or cancelled, but no withdrawal succeeds until the admin
+    /// calls `resolve_dispute`.
+    ///
+    /// # Panics
+    /// - If `caller` may not dispute the stream.
+    /// - If the stream is already disputed or `Completed`.
+    pub fn raise_dispute(env: Env, stream_id: u64, caller: Address) {
+        let stream = load_stream(&env, stream_id);
+        caller.require_auth();
+        assert!(
+            caller == stream.sender
+                || (caller == stream.recipient
+                    && Self::get_recipient_may_dispute(env.clone())),
+            "caller may not dispute this stream"
+        );
         assert!(
             stream.status != StreamStatus::Completed,
             "stream already completed"
         );
+        assert!(!is_disputed(&env, stream_id), "stream is already disputed");
+
+        let key = DataKey::Disputed(stream_id);
+        env.storage().persistent().set(&key, &true);
+        env.storage().persistent().extend_ttl(&key, 17280, 120960);
+        env.events()
+            .publish((symbol_short!("disputed"), stream_id), caller);
+    }
+
+    /// Settle a dispute raised with `raise_dispute`. Admin only. With
+    /// `allow` the hold is lifted and everything accrued meanwhile can be
+    /// withdrawn; otherwise the stream is also cancelled as by
+    /// `cancel_stream`, without any cancellation notice.
+    ///
+    /// # Panics
+    /// - If the stream is not disputed.
+    /// - If `allow` is `false` and the stream cannot be cancelled.
+    /// - If governance is configured (use a `ResolveDispute` proposal).
+    pub fn resolve_dispute(env: Env, stream_id: u64, allow: bool) {
+        governance::require_admin(&env);
+        Self::apply_resolve_dispute(&env, stream_id, allow);
+    }
+
+    /// Shared body of `resolve_dispute` and its governance proposal.
+    fn apply_resolve_dispute(env: &Env, stream_id: u64, allow: bool) {
+        let stream = load_stream(env, stream_id);
+        assert!(is_disputed(env, stream_id), "stream is not disputed");
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Disputed(stream_id));
+        if !allow {
+            Self::apply_cancel(env, stream);
+        }
+        env.events()
+            .publish((symbol_short!("resolved"), stream_id), allow);
+    }
+
+    /// Whether the stream's payouts are held by a dispute.
+    pub fn is_disputed(env: Env, stream_id: u64) -> bool {
+        is_disputed(&env, stream_id)
+    }
+
+    /// Install (or with `None`, remove) a compliance hook contract. When set,
+    /// every withdrawal first calls `is_transfer_allowed(recipient, amount)`
+    /// on it and aborts unless it returns `true`. Admin only.
+    ///
+    /// # Panics
+    /// - If governance is configured (use a `SetComplianceHook` proposal).
+    pub fn set_compliance_hook(env: Env, hook: Option<Address>) {
+        governance::require_admin(&env);
+        Self::apply_compliance_hook(&env, hook);
+    }
+
+    /// Shared body of `set_compliance_hook` and its governance proposal.
+    fn apply_compliance_hook(env: &Env, hook: Option<Address>) {
+        let mut config = get_config(env);
+        config.compliance_hook = hook.clone();
+        save_config(env, &config);
+
+        env.events()
+            .publish((symbol_short!("hook_set"),), hook);
+    }
+
+    /// Approve milestone `index` of a milestone stream, unlocking its
+    /// cumulative amount for withdrawal. Requires admin auth.
+    ///
+    /// # Panics
+    /// - If the stream has no milestones or `index` is out of range.
+    /// - If `index` is not the next unapproved milestone.
+    /// - If the stream is cancelled or completed.
+    /// - If governance is configured (use an `ApproveMilestone` proposal).
+    pub fn approve_milestone(env: Env, stream_id: u64, index: u32) {
+        governance::require_admin(&env);
+        Self::apply_approve_milestone(&env, stream_id, index);
+    }
 
+    /// Shared body of `approve_milestone` and its governance proposal.
+    fn apply_approve_milestone(env: &Env, stream_id: u64, index: u32) {
+        let mut stream = load_stream(env, stream_id);
         assert!(
-            stream.status != StreamStatus::Paused,
-            "cannot withdraw from paused stream"
+            index < stream.milestones.len(),
+            "milestone index out of range"
+        );
+        assert!(
+            index == stream.milestones_approved,
+            "milestones must be approved in order"
+        );
+        assert!(
+            stream.status.is_open(),
+            "stream must be active or paused to approve milestones"
         );
 
-        let accrued = Self::calculate_accrued(env.clone(), stream_id);
-        let withdrawable = accrued - stream.withdrawn_amount;
-        assert!(withdrawable > 0, "nothing to withdraw");
+        stream.milestones_approved += 1;
+        save_stream(env, &stream);
 
-        let token_client = token::Client::new(&env, &get_token(&env));
-        token_client.transfer(
-            &env.current_contract_address(),
-            &stream.recipient,
-            &withdrawable,
+        env.events().publish(
+            (symbol_short!("milestone"), stream_id),
+            (index, stream.milestones.get_unchecked(index)),
         );
+    }
 
-        stream.withdrawn_amount += withdrawable;
-
-        // // If the full deposit has been streamed and withdrawn, mark completed
-        // let now = env.ledger().timestamp();
-        // if stream.status == StreamStatus::Active
-        //     && now >= stream.end_time
-        //     && stream.withdrawn_amount == stream.deposit_amount
-        // {
-        //     stream.status = StreamStatus::Completed;
-        // }
+    /// Set the notice period admin cancellations must wait out. Requires admin
+    /// auth. Applies to requests made afterwards.
+    ///
+    /// # Panics
+    /// - If governance is configured (use a `SetAdminCancelDelay` proposal).
+    pub fn set_admin_cancel_delay(env: Env, delay_seconds: u64) {
+        governance::require_admin(&env);
+        Self::apply_admin_cancel_delay(&env, delay_seconds);
+    }
 
-        if stream.withdrawn_amount >= stream.deposit_amount {
-            stream.status = StreamStatus::Completed;
-        }
+    /// Shared body of `set_admin_cancel_delay` and its governance proposal.
+    fn apply_admin_cancel_delay(env: &Env, delay_seconds: u64) {
+        let mut config = get_config(env);
+        config.admin_cancel_delay = delay_seconds;
+        save_config(env, &config);
 
-        save_stream(&env, &stream);
         env.events()
-            .publish((symbol_short!("withdrew"), stream_id), withdrawable);
-        withdrawable
+            .publish((symbol_short!("delay_set"),), delay_seconds);
     }
 
-    /// Calculate the total amount accrued to the recipient so far.
-    pub fn calculate_accrued(env: Env, stream_id: u64) -> i128 {
+    /// First phase of a timelocked admin cancel: announce the intent to
+    /// cancel `stream_id` once the configured delay has passed. Requires
+    /// admin auth. Emits `cncl_req` with the executable-at time so both
+    /// parties are warned; the stream keeps running in the meantime.
+    ///
+    /// The same request opens the way for `arbitrate_stream`, which can also
+    /// settle a disputed stream that has already been cancelled.
+    ///
+    /// # Panics
+    /// - If the stream is not `Active` or `Paused` (or disputed and
+    ///   `Cancelled`), or is locked.
+    /// - If a cancel is already pending for the stream.
+    pub fn admin_request_cancel(env: Env, stream_id: u64) {
+        governance::require_admin(&env);
+        let config = get_config(&env);
+
         let stream = load_stream(&env, stream_id);
-        let now = env.ledger().timestamp();
+        assert!(!stream.immutable, "stream is locked");
+        assert!(
+            stream.status.is_open()
+                || (stream.status == StreamStatus::Cancelled && is_disputed(&env, stream_id)),
+            "stream must be active or paused to cancel"
+        );
+        let key = DataKey::PendingAdminCancel(stream_id);
+        assert!(
+            !env.storage().persistent().has(&key),
+            "admin cancel already requested"
+        );
 
-        if now < stream.cliff_time {
-            return 0;
-        }
+        let executable_at = env.ledger().timestamp() + config.admin_cancel_delay;
+        env.storage().persistent().set(&key, &executable_at);
+        env.storage().persistent().extend_ttl(&key, 17280, 120960);
 
-        if stream.start_time >= stream.end_time || stream.rate_per_second < 0 {
-            return 0;
-        }
+        env.events().publish(
+            (symbol_short!("cncl_req"), stream_id),
+            (stream.sender, stream.recipient, executable_at),
+        );
+    }
 
-        let elapsed_now = now.min(stream.end_time);
-        let elapsed = match elapsed_now.checked_sub(stream.start_time) {
-            Some(elapsed) => elapsed as i128,
-            None => return 0,
-        };
+    /// Second phase of a timelocked admin cancel: cancel the stream exactly
+    /// as `cancel_stream` would. Requires admin auth.
+    ///
+    /// # Panics
+    /// - If no cancel is pending (never requested, or the stream reached a
+    ///   terminal state first).
+    /// - If the delay has not yet elapsed.
+    pub fn admin_execute_cancel(env: Env, stream_id: u64) {
+        governance::require_admin(&env);
 
-        let accrued = match elapsed.checked_mul(stream.rate_per_second) {
-            Some(accrued) => accrued,
-            None => stream.deposit_amount,
-        };
+        let executable_at: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::PendingAdminCancel(stream_id))
+            .expect("no admin cancel requested");
+        assert!(
+            env.ledger().timestamp() >= executable_at,
+            "admin cancel delay has not elapsed"
+        );
 
-        accrued.min(stream.deposit_amount).max(0) // ensures result >= 0
+        Self::apply_cancel(&env, load_stream(&env, stream_id));
     }
 
-    /// Fetches the global configuration.
-    pub fn get_config(env: Env) -> Config {
-        get_config(&env)
+    /// Run `admin_execute_cancel` for each of `stream_ids` under a single
+    /// admin auth. Ids that cannot be cancelled yet are skipped rather than
+    /// failing the batch; the result for each id, in order, is the refund
+    /// paid or the reason it was skipped.
+    ///
+    /// # Panics
+    /// - If more than 50 ids are given.
+    /// - If governance is configured.
+    pub fn cancel_streams_as_admin(env: Env, stream_ids: Vec<u64>) -> Vec<CancelResult> {
+        assert!(
+            stream_ids.len() <= MAX_STREAM_BATCH,
+            "too many stream ids (max 50)"
+        );
+        governance::require_admin(&env);
+
+        let now = env.ledger().timestamp();
+        let mut results = Vec::new(&env);
+        for stream_id in stream_ids.iter() {
+            let Some(stream) = try_load_stream(&env, stream_id) else {
+                results.push_back(CancelResult::Skipped(CancelSkipReason::NotFound));
+                continue;
+            };
+            let executable_at: Option<u64> = env
+                .storage()
+                .persistent()
+                .get(&DataKey::PendingAdminCancel(stream_id));
+            let cancellable = !stream.immutable && stream.status.is_open();
+            let result = match executable_at {
+                _ if !cancellable => CancelResult::Skipped(CancelSkipReason::NotCancellable),
+                None => CancelResult::Skipped(CancelSkipReason::NotRequested),
+                Some(executable_at) if now < executable_at => {
+                    CancelResult::Skipped(CancelSkipReason::DelayPending)
+                }
+                Some(_) => CancelResult::Cancelled(Self::apply_cancel(&env, stream)),
+            };
+            results.push_back(result);
+        }
+        results
     }
 
-    /// Return the current state of the stream identified by `stream_id`.
-    pub fn get_stream_state(env: Env, stream_id: u64) -> Stream {
-        load_stream(&env, stream_id)
+    /// Time at which a requested admin cancel of `stream_id` becomes
+    /// executable, if one is pending.
+    pub fn get_pending_admin_cancel(env: Env, stream_id: u64) -> Option<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PendingAdminCancel(stream_id))
     }
 
-    /// Internal helper to check authorization for sender or admin.
-    fn require_sender_or_admin(env: &Env, sender: &Address) {
-        let admin = get_admin(env);
+    /// Pay out and complete an `Active` stream that has fully accrued but
+    /// whose recipient has not withdrawn the rest, e.g. because they are
+    /// unresponsive. Admin only. The recipient receives the full remainder,
+    /// `deposit_amount - withdrawn_amount` less any dust the schedule never
+    /// streams, with no withdrawal fee taken. Returns that amount.
+    ///
+    /// # Panics
+    /// - If the stream is not `Active`, or is recurring.
+    /// - If `end_time` has not been reached or the stream is not fully
+    ///   accrued (e.g. unapproved milestones).
+    /// - Under the same conditions as `withdraw` otherwise.
+    /// - If governance is configured (use a `ForceComplete` proposal).
+    pub fn force_complete(env: Env, stream_id: u64) -> i128 {
+        governance::require_admin(&env);
+        Self::apply_force_complete(&env, stream_id)
+    }
 
-        // If the admin is the one calling, they must authorize.
-        // Otherwise, the sender must authorize.
-        if sender != &admin {
-            // This allows the admin to bypass the sender's auth
-            // if we use a separate admin entrypoint, or we can
-            // rely on the transaction signatures.
-            sender.require_auth();
-        } else {
-            admin.require_auth();
-        }
+    /// Shared body of `force_complete` and its governance proposal.
+    fn apply_force_complete(env: &Env, stream_id: u64) -> i128 {
+        let stream = load_stream(env, stream_id);
+        assert!(
+            stream.status == StreamStatus::Active,
+            "only active streams can be force-completed"
+        );
+        assert!(
+            !env.storage().persistent().has(&DataKey::Recurring(stream_id)),
+            "recurring streams cannot be force-completed"
+        );
+        let now = env.ledger().timestamp();
+        assert!(now >= stream.end_time, "stream has not ended");
+        assert!(
+            Self::accrued_at(&stream, now) >= total_streamable(&stream),
+            "stream is not fully accrued"
+        );
+
+        let payout = Self::pay_out(env, stream, None, None, false).payout;
+        env.events()
+            .publish((symbol_short!("completed"), stream_id), payout);
+        payout
     }
-}
 
-#[contractimpl]
-impl FluxoraStream {
     /// Cancel a stream as the contract admin. Identical logic to cancel_stream.
     pub fn cancel_stream_as_admin(env: Env, stream_id: u64) {
-        get_admin(&env).require_auth();
+        governance::require_admin(&env);
         Self::cancel_stream(env, stream_id);
     }
 }
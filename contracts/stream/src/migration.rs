@@ -0,0 +1,357 @@
+//! Storage-format versioning and the `migrate` entrypoint.
+//!
+//! `init` stamps new deployments with [`DATA_VERSION`]. A deployment without
+//! a stamp predates versioning and holds version-1 data: the original
+//! `Config` and `Stream` layouts, with none of the fields added since. After
+//! `upgrade_contract` installs code that expects a newer layout, the admin
+//! calls `migrate` once to rewrite the old entries in place.
+//!
+//! Version 2 adds the fields up to `last_withdrawn_at`, version 3 the
+//! `get_stats` counters, which migrating seeds from the stored streams,
+//! version 4 `Stream::accrued_checkpoint`, and version 5 moves the admin's
+//! fee, limit and dispute settings from their own keys into `Config`.
+//!
+//! Stored configs and streams are told apart by their number of fields, since decoding
+//! an entry into a struct with a different field set traps.
+
+use soroban_sdk::{
+    contractimpl, contracttype, symbol_short, Address, Bytes, Env, Map, Symbol, TryIntoVal, Val,
+    Vec,
+};
+
+use crate::{
+    add_to_index, add_to_sorted_index, get_admin, get_index, locked_in, write_stream,
+    ArchivedStream, Config, DataKey, FluxoraStream, FluxoraStreamClient, Stream, StreamCategory,
+    StreamStatus, DEFAULT_ADMIN_CANCEL_DELAY, DEFAULT_WITHDRAW_GRACE_PERIOD,
+};
+
+/// Storage layout version written by this code.
+pub(crate) const DATA_VERSION: u32 = 5;
+
+/// `Config` as stored by version 1.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub(crate) struct ConfigV1 {
+    pub token: Address,
+    pub admin: Address,
+}
+
+/// `Config` as stored by versions 2 to 4.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub(crate) struct ConfigV2 {
+    pub token: Address,
+    pub admin: Address,
+    pub admin_cancel_delay: u64,
+}
+
+/// `Stream` as stored by version 1.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub(crate) struct StreamV1 {
+    pub stream_id: u64,
+    pub sender: Address,
+    pub recipient: Address,
+    pub deposit_amount: i128,
+    pub rate_per_second: i128,
+    pub start_time: u64,
+    pub cliff_time: u64,
+    pub end_time: u64,
+    pub withdrawn_amount: i128,
+    pub status: StreamStatus,
+}
+
+/// `Stream` as stored by versions 2 and 3.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub(crate) struct StreamV2 {
+    pub stream_id: u64,
+    pub sender: Address,
+    pub recipient: Address,
+    pub deposit_amount: i128,
+    pub rate_per_second: i128,
+    pub start_time: u64,
+    pub cliff_time: u64,
+    pub end_time: u64,
+    pub withdrawn_amount: i128,
+    pub status: StreamStatus,
+    pub memo: Bytes,
+    pub external_ref: Option<Bytes>,
+    pub refund_address: Address,
+    pub immutable: bool,
+    pub step_seconds: u64,
+    pub category: StreamCategory,
+    pub delegate: Option<Address>,
+    pub delegate_can_cancel: bool,
+    pub milestones: Vec<i128>,
+    pub milestones_approved: u32,
+    pub withdraw_schedule: Option<Vec<u64>>,
+    pub last_withdrawn_at: u64,
+}
+
+const CONFIG_V2_FIELDS: u32 = 3;
+const STREAM_V1_FIELDS: u32 = 10;
+const STREAM_V2_FIELDS: u32 = 22;
+
+/// Number of fields in a stored stream entry, or `None` if there is none.
+fn stream_field_count(env: &Env, stream_id: u64) -> Option<u32> {
+    env.storage()
+        .persistent()
+        .get::<_, Map<Symbol, Val>>(&DataKey::Stream(stream_id))
+        .map(|fields| fields.len())
+}
+
+pub(crate) fn load_data_version(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::DataVersion)
+        .unwrap_or(1)
+}
+
+/// Rewrite a version-1 `Config` with the default admin cancel delay. A no-op
+/// if the config is already in a later layout.
+fn migrate_config_v1(env: &Env) {
+    let raw: Val = env
+        .storage()
+        .instance()
+        .get(&DataKey::Config)
+        .expect("contract not initialised");
+    if let Ok(old) = raw.try_into_val(env) {
+        let old: ConfigV1 = old;
+        let config = ConfigV2 {
+            token: old.token,
+            admin: old.admin,
+            admin_cancel_delay: DEFAULT_ADMIN_CANCEL_DELAY,
+        };
+        env.storage().instance().set(&DataKey::Config, &config);
+    }
+}
+
+/// Rewrite a version-2 `Config` with the settings versions 2 to 4 kept under
+/// their own keys, removing those keys. A no-op if the config is already in
+/// the current layout.
+fn migrate_config_v2(env: &Env) {
+    let instance = env.storage().instance();
+    let fields: Map<Symbol, Val> = instance
+        .get(&DataKey::Config)
+        .expect("contract not initialised");
+    if fields.len() != CONFIG_V2_FIELDS {
+        return;
+    }
+    let old: ConfigV2 = instance.get(&DataKey::Config).unwrap();
+    let config = Config {
+        token: old.token,
+        admin: old.admin,
+        admin_cancel_delay: old.admin_cancel_delay,
+        fee_bps: instance.get(&DataKey::FeeBps).unwrap_or(0),
+        fee_collector: instance.get(&DataKey::FeeCollector),
+        min_deposit: instance.get(&DataKey::MinDeposit),
+        min_cliff_duration: instance.get(&DataKey::MinCliffDuration),
+        withdraw_grace_period: instance
+            .get(&DataKey::WithdrawGracePeriod)
+            .unwrap_or(DEFAULT_WITHDRAW_GRACE_PERIOD),
+        compliance_hook: instance.get(&DataKey::ComplianceHook),
+        recipient_may_dispute: instance.get(&DataKey::RecipientMayDispute).unwrap_or(false),
+    };
+    for key in [
+        DataKey::FeeBps,
+        DataKey::FeeCollector,
+        DataKey::MinDeposit,
+        DataKey::MinCliffDuration,
+        DataKey::WithdrawGracePeriod,
+        DataKey::ComplianceHook,
+        DataKey::RecipientMayDispute,
+    ] {
+        instance.remove(&key);
+    }
+    instance.set(&DataKey::Config, &config);
+}
+
+/// Rewrite a version-1 stream with defaults for every later field and add it
+/// to the indexes it predates. Entries already in the current layout, and
+/// archived or missing ids, are left alone.
+///
+/// Version 1 kept a cancelled stream's full deposit and did not record when
+/// it was cancelled, so what it still owed its recipient cannot be
+/// recovered; a cancelled stream is migrated with nothing left to withdraw.
+fn migrate_stream_v1(env: &Env, stream_id: u64) {
+    if stream_field_count(env, stream_id) != Some(STREAM_V1_FIELDS) {
+        return;
+    }
+    let old: StreamV1 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Stream(stream_id))
+        .unwrap();
+
+    let deposit_amount = if old.status == StreamStatus::Cancelled {
+        old.withdrawn_amount
+    } else {
+        old.deposit_amount
+    };
+    let stream = Stream {
+        stream_id,
+        sender: old.sender.clone(),
+        recipient: old.recipient.clone(),
+        deposit_amount,
+        rate_per_second: old.rate_per_second,
+        start_time: old.start_time,
+        cliff_time: old.cliff_time,
+        end_time: old.end_time,
+        withdrawn_amount: old.withdrawn_amount,
+        status: old.status,
+        memo: Bytes::new(env),
+        external_ref: None,
+        refund_address: old.sender,
+        immutable: false,
+        step_seconds: 0,
+        category: StreamCategory::Other,
+        delegate: None,
+        delegate_can_cancel: false,
+        milestones: Vec::new(env),
+        milestones_approved: 0,
+        withdraw_schedule: None,
+        last_withdrawn_at: 0,
+        accrued_checkpoint: 0,
+    };
+    write_stream(env, &stream);
+
+    for key in [
+        DataKey::SenderStreams(stream.sender.clone()),
+        DataKey::RecipientStreams(stream.recipient.clone()),
+        DataKey::CategoryIndex(stream.category),
+    ] {
+        if !get_index(env, &key).contains(stream_id) {
+            add_to_index(env, key, stream_id);
+        }
+    }
+    add_to_sorted_index(env, DataKey::StreamsByStatus(stream.status), stream_id);
+}
+
+/// Rewrite a version-2 stream with no accrual checkpoint. Other entries are
+/// left alone.
+fn migrate_stream_v2(env: &Env, stream_id: u64) {
+    if stream_field_count(env, stream_id) != Some(STREAM_V2_FIELDS) {
+        return;
+    }
+    let old: StreamV2 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Stream(stream_id))
+        .unwrap();
+    write_stream(
+        env,
+        &Stream {
+            stream_id: old.stream_id,
+            sender: old.sender,
+            recipient: old.recipient,
+            deposit_amount: old.deposit_amount,
+            rate_per_second: old.rate_per_second,
+            start_time: old.start_time,
+            cliff_time: old.cliff_time,
+            end_time: old.end_time,
+            withdrawn_amount: old.withdrawn_amount,
+            status: old.status,
+            memo: old.memo,
+            external_ref: old.external_ref,
+            refund_address: old.refund_address,
+            immutable: old.immutable,
+            step_seconds: old.step_seconds,
+            category: old.category,
+            delegate: old.delegate,
+            delegate_can_cancel: old.delegate_can_cancel,
+            milestones: old.milestones,
+            milestones_approved: old.milestones_approved,
+            withdraw_schedule: old.withdraw_schedule,
+            last_withdrawn_at: old.last_withdrawn_at,
+            accrued_checkpoint: 0,
+        },
+    );
+}
+
+/// Recompute the `get_stats` counters from every stored stream, counting
+/// what archived streams paid out towards the all-time withdrawn total.
+fn recount_stats(env: &Env, next_id: u64) {
+    let mut active_count = 0u32;
+    let mut locked = 0i128;
+    let mut withdrawn = 0i128;
+    for stream_id in 0..next_id {
+        if let Some(stream) = env
+            .storage()
+            .persistent()
+            .get::<_, Stream>(&DataKey::Stream(stream_id))
+        {
+            if stream.status == StreamStatus::Active {
+                active_count += 1;
+            }
+            locked += locked_in(&stream);
+            withdrawn += stream.withdrawn_amount;
+        } else if let Some(archived) = env
+            .storage()
+            .persistent()
+            .get::<_, ArchivedStream>(&DataKey::ArchivedStream(stream_id))
+        {
+            withdrawn += archived.total_streamed;
+        }
+    }
+    env.storage().instance().set(&DataKey::ActiveCount, &active_count);
+    env.storage().instance().set(&DataKey::LockedTotal, &locked);
+    env.storage().instance().set(&DataKey::TotalWithdrawn, &withdrawn);
+}
+
+#[contractimpl]
+impl FluxoraStream {
+    /// Storage layout version of this deployment's data: 1 for deployments
+    /// that predate versioning and have not been migrated. Unlike
+    /// `get_contract_version` this tracks the data, not the code.
+    pub fn get_version(env: Env) -> u32 {
+        load_data_version(&env)
+    }
+
+    /// Bring stored data up to the layout this code expects, one version at
+    /// a time, then record the new version. Requires admin auth. Calling it
+    /// again once the data is current does nothing.
+    ///
+    /// Every stream is visited in a single invocation, so very large
+    /// deployments may exceed the transaction budget.
+    pub fn migrate(env: Env) {
+        let from = load_data_version(&env);
+        if from >= DATA_VERSION {
+            return;
+        }
+
+        // Older configs have to be readable before the admin can be found.
+        if from < 2 {
+            migrate_config_v1(&env);
+        }
+        if from < 5 {
+            migrate_config_v2(&env);
+        }
+        get_admin(&env).require_auth();
+
+        let next_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::NextStreamId)
+            .unwrap_or(0);
+        if from < 2 {
+            for stream_id in 0..next_id {
+                migrate_stream_v1(&env, stream_id);
+            }
+        }
+        if from < 4 {
+            for stream_id in 0..next_id {
+                migrate_stream_v2(&env, stream_id);
+            }
+        }
+        if from < 3 {
+            recount_stats(&env, next_id);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::DataVersion, &DATA_VERSION);
+        env.events()
+            .publish((symbol_short!("migrated"),), (from, DATA_VERSION));
+    }
+}
@@ -2,13 +2,20 @@
 extern crate std;
 
 use soroban_sdk::{
-    log,
-    testutils::{Address as _, Ledger},
+    contract, contractimpl, contracttype, log, symbol_short, vec,
+    testutils::{Address as _, Events, Ledger, MockAuth, MockAuthInvoke},
     token::{Client as TokenClient, StellarAssetClient},
-    Address, Env,
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, IntoVal, String, Val, Vec,
 };
 
-use crate::{FluxoraStream, FluxoraStreamClient, StreamStatus};
+use crate::accrual::{calculate_accrued_amount, calculate_accrued_with_pauses, settlement_amounts};
+use crate::migration::{ConfigV1, ConfigV2, StreamV1, StreamV2, DATA_VERSION};
+use crate::{
+    validate_coverage, BulkStats, CancelResult, CancelSkipReason, Config, ContractError,
+    DataKey, FluxoraStream, FluxoraStreamClient, GovAction, StreamCategory, StreamParameters,
+    StreamPhase, StreamStatus, WithdrawCallback, WithdrawReceipt,
+};
 
 // ---------------------------------------------------------------------------
 // Test helpers
@@ -18,6 +25,7 @@ struct TestContext<'a> {
     env: Env,
     contract_id: Address,
     token_id: Address,
+    admin: Address,
     sender: Address,
     recipient: Address,
     sac: StellarAssetClient<'a>,
@@ -53,6 +61,7 @@ impl<'a> TestContext<'a> {
             env,
             contract_id,
             token_id,
+            admin,
             sender,
             recipient,
             sac,
@@ -78,6 +87,8 @@ impl<'a> TestContext<'a> {
             &0u64,      // start_time
             &0u64,      // cliff_time (no cliff)
             &1000u64,   // end_time
+            &Bytes::new(&self.env),
+            &None,
         )
     }
 
@@ -92,6 +103,8 @@ impl<'a> TestContext<'a> {
             &0u64,
             &500u64, // cliff at t=500
             &1000u64,
+            &Bytes::new(&self.env),
+            &None,
         )
     }
 
@@ -105,6 +118,8 @@ impl<'a> TestContext<'a> {
             &0,
             &0u64,
             &3,
+            &Bytes::new(&self.env),
+            &None,
         )
     }
 
@@ -118,6 +133,8 @@ impl<'a> TestContext<'a> {
             &0,
             &0u64,
             &100,
+            &Bytes::new(&self.env),
+            &None,
         )
     }
 }
@@ -158,6 +175,8 @@ fn test_create_stream_zero_deposit_panics() {
         &0u64,
         &0u64,
         &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 }
 
@@ -174,6 +193,8 @@ fn test_create_stream_invalid_times_panics() {
         &1000u64,
         &1000u64,
         &500u64, // end before start
+        &Bytes::new(&ctx.env),
+        &None,
     );
 }
 
@@ -194,6 +215,8 @@ fn test_create_stream_zero_rate_panics() {
         &0u64,
         &0u64,
         &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 }
 
@@ -210,6 +233,8 @@ fn test_create_stream_sender_equals_recipient_panics() {
         &0u64,
         &0u64,
         &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 }
 
@@ -230,6 +255,8 @@ fn test_create_stream_cliff_before_start_panics() {
         &100u64,  // start_time
         &50u64,   // cliff_time before start
         &1100u64, // end_time
+        &Bytes::new(&ctx.env),
+        &None,
     );
 }
 
@@ -246,6 +273,8 @@ fn test_create_stream_cliff_after_end_panics() {
         &0u64,
         &1500u64, // cliff_time after end
         &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 }
 
@@ -261,6 +290,8 @@ fn test_create_stream_cliff_equals_start_succeeds() {
         &0u64,
         &0u64, // cliff equals start
         &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
     let state = ctx.client().get_stream_state(&stream_id);
     assert_eq!(state.cliff_time, 0);
@@ -278,6 +309,8 @@ fn test_create_stream_cliff_equals_end_succeeds() {
         &0u64,
         &1000u64, // cliff equals end
         &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
     let state = ctx.client().get_stream_state(&stream_id);
     assert_eq!(state.cliff_time, 1000);
@@ -300,6 +333,8 @@ fn test_create_stream_deposit_less_than_total_panics() {
         &0u64,
         &0u64,
         &1000u64, // duration = 1000s, so total = 1000 tokens needed
+        &Bytes::new(&ctx.env),
+        &None,
     );
 }
 
@@ -315,6 +350,8 @@ fn test_create_stream_deposit_equals_total_succeeds() {
         &0u64,
         &0u64,
         &1000u64, // duration = 1000s
+        &Bytes::new(&ctx.env),
+        &None,
     );
     let state = ctx.client().get_stream_state(&stream_id);
     assert_eq!(state.deposit_amount, 1000);
@@ -332,11 +369,38 @@ fn test_create_stream_deposit_greater_than_total_succeeds() {
         &0u64,
         &0u64,
         &1000u64, // duration = 1000s, total needed = 1000
+        &Bytes::new(&ctx.env),
+        &None,
     );
     let state = ctx.client().get_stream_state(&stream_id);
     assert_eq!(state.deposit_amount, 2000);
 }
 
+#[test]
+fn test_validate_coverage_accepts_exact_and_over() {
+    validate_coverage(1000, 1, 0, 1000);
+    validate_coverage(1001, 1, 0, 1000);
+    validate_coverage(i128::MAX, i128::MAX, 5, 6);
+}
+
+#[test]
+#[should_panic(expected = "deposit_amount must cover total streamable amount")]
+fn test_validate_coverage_rejects_under() {
+    validate_coverage(999, 1, 0, 1000);
+}
+
+#[test]
+#[should_panic(expected = "overflow calculating total streamable amount")]
+fn test_validate_coverage_rejects_overflow() {
+    validate_coverage(i128::MAX, i128::MAX, 0, 2);
+}
+
+#[test]
+#[should_panic(expected = "start_time must be before end_time")]
+fn test_validate_coverage_rejects_empty_schedule() {
+    validate_coverage(1000, 1, 1000, 1000);
+}
+
 // ---------------------------------------------------------------------------
 // Tests — Issue #36: reject when token transfer fails
 // ---------------------------------------------------------------------------
@@ -355,6 +419,8 @@ fn test_create_stream_insufficient_balance_panics() {
         &0u64,
         &0u64,
         &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 }
 
@@ -373,6 +439,8 @@ fn test_create_stream_transfer_failure_no_state_change() {
             &0u64,
             &0u64,
             &1000u64,
+            &Bytes::new(&ctx.env),
+            &None,
         )
     }));
 
@@ -519,6 +587,27 @@ fn test_resume_active_stream_panics() {
     ctx.client().resume_stream(&stream_id); // not paused, should panic
 }
 
+#[test]
+fn test_pause_does_not_reduce_total_payout() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    // Paused for 200s mid-stream; accrual carries on through the pause
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().pause_stream(&stream_id);
+    ctx.env.ledger().set_timestamp(500);
+    ctx.client().resume_stream(&stream_id);
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), 500);
+
+    ctx.env.ledger().set_timestamp(1000);
+    assert_eq!(ctx.client().withdraw(&stream_id), 1000);
+    assert_eq!(ctx.token().balance(&ctx.sender), 10_000 - 1000);
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Completed
+    );
+}
+
 // ---------------------------------------------------------------------------
 // Tests — cancel_stream
 // ---------------------------------------------------------------------------
@@ -612,7 +701,7 @@ fn test_withdraw_after_cancel_gets_accrued_amount() {
 }
 
 #[test]
-#[should_panic(expected = "nothing to withdraw")]
+#[should_panic(expected = "stream already completed")]
 fn test_withdraw_twice_after_cancel_panics() {
     let ctx = TestContext::setup();
     let stream_id = ctx.create_default_stream();
@@ -814,7 +903,17 @@ fn test_multiple_streams_independent() {
     let id0 = ctx.create_default_stream();
     let id1 = ctx
         .client()
-        .create_stream(&ctx.sender, &ctx.recipient, &200, &2, &0, &0, &100);
+        .create_stream(
+            &ctx.sender,
+            &ctx.recipient,
+            &200,
+            &2,
+            &0,
+            &0,
+            &100,
+            &Bytes::new(&ctx.env),
+            &None,
+        );
 
     assert_eq!(id0, 0);
     assert_eq!(id1, 1);
@@ -848,6 +947,8 @@ fn test_create_stream_negative_deposit_panics() {
         &0u64,
         &0u64,
         &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 }
 
@@ -865,6 +966,8 @@ fn test_create_stream_negative_rate_panics() {
         &0u64,
         &0u64,
         &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 }
 
@@ -882,6 +985,8 @@ fn test_create_stream_equal_start_end_times_panics() {
         &500u64,
         &500u64,
         &500u64, // start == end
+        &Bytes::new(&ctx.env),
+        &None,
     );
 }
 
@@ -899,12 +1004,14 @@ fn test_create_stream_cliff_equals_start() {
         &100u64,
         &100u64, // cliff == start (valid)
         &1100u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 
     let state = ctx.client().get_stream_state(&stream_id);
     assert_eq!(state.cliff_time, 100);
     assert_eq!(state.start_time, 100);
-    assert_eq!(state.status, StreamStatus::Active);
+    assert_eq!(state.status, StreamStatus::Scheduled);
 }
 
 /// Test creating a stream with cliff_time equal to end_time (valid edge case)
@@ -921,6 +1028,8 @@ fn test_create_stream_cliff_equals_end() {
         &0u64,
         &1000u64, // cliff == end (valid)
         &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 
     let state = ctx.client().get_stream_state(&stream_id);
@@ -943,6 +1052,8 @@ fn test_create_stream_increments_id_correctly() {
         &0u64,
         &0u64,
         &100u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 
     let id1 = ctx.client().create_stream(
@@ -953,6 +1064,8 @@ fn test_create_stream_increments_id_correctly() {
         &0u64,
         &0u64,
         &200u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 
     let id2 = ctx.client().create_stream(
@@ -963,6 +1076,8 @@ fn test_create_stream_increments_id_correctly() {
         &0u64,
         &0u64,
         &300u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 
     assert_eq!(id0, 0);
@@ -998,6 +1113,8 @@ fn test_create_stream_large_deposit() {
         &0u64,
         &0u64,
         &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 
     let state = ctx.client().get_stream_state(&stream_id);
@@ -1023,6 +1140,8 @@ fn test_create_stream_high_rate() {
         &0u64,
         &0u64,
         &duration,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 
     let state = ctx.client().get_stream_state(&stream_id);
@@ -1047,6 +1166,8 @@ fn test_create_stream_different_addresses() {
         &0u64,
         &0u64,
         &500u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 
     let state = ctx.client().get_stream_state(&stream_id);
@@ -1068,12 +1189,14 @@ fn test_create_stream_future_start_time() {
         &1000u64, // starts in the future
         &1000u64,
         &2000u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 
     let state = ctx.client().get_stream_state(&stream_id);
     assert_eq!(state.start_time, 1000);
     assert_eq!(state.end_time, 2000);
-    assert_eq!(state.status, StreamStatus::Active);
+    assert_eq!(state.status, StreamStatus::Scheduled);
 }
 
 /// Test token balance changes after creating stream
@@ -1095,6 +1218,8 @@ fn test_create_stream_token_balances() {
         &0u64,
         &0u64,
         &500u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 
     // Sender balance should decrease by deposit
@@ -1130,6 +1255,8 @@ fn test_create_stream_minimum_duration() {
         &0u64,
         &0u64,
         &1u64, // 1 second duration
+        &Bytes::new(&ctx.env),
+        &None,
     );
 
     let state = ctx.client().get_stream_state(&stream_id);
@@ -1157,6 +1284,8 @@ fn test_create_stream_all_fields_correct() {
         &start,
         &cliff,
         &end,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 
     let state = ctx.client().get_stream_state(&stream_id);
@@ -1170,7 +1299,7 @@ fn test_create_stream_all_fields_correct() {
     assert_eq!(state.cliff_time, cliff);
     assert_eq!(state.end_time, end);
     assert_eq!(state.withdrawn_amount, 0);
-    assert_eq!(state.status, StreamStatus::Active);
+    assert_eq!(state.status, StreamStatus::Scheduled);
 }
 
 /// Test that creating stream with same sender and recipient panics
@@ -1189,6 +1318,8 @@ fn test_create_stream_self_stream_panics() {
         &0u64,
         &0u64,
         &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
     );
 }
 
@@ -1248,6 +1379,49 @@ fn test_get_config() {
     assert_eq!(config.admin, ctx.admin);
 }
 
+#[test]
+fn test_init_sets_initialised_flag() {
+    let ctx = TestContext::setup();
+    ctx.env.as_contract(&ctx.contract_id, || {
+        let flag: bool = ctx
+            .env
+            .storage()
+            .instance()
+            .get(&DataKey::Initialised)
+            .unwrap();
+        assert!(flag);
+    });
+}
+
+#[test]
+#[should_panic(expected = "contract partially initialised")]
+fn test_init_refuses_partial_state() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, FluxoraStream);
+    let token_id = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    // Simulate an interrupted init: config written, flag never set
+    env.as_contract(&contract_id, || {
+        let config = Config {
+            token: token_id.clone(),
+            admin: admin.clone(),
+            admin_cancel_delay: 0,
+            fee_bps: 0,
+            fee_collector: None,
+            min_deposit: None,
+            min_cliff_duration: None,
+            withdraw_grace_period: 0,
+            compliance_hook: None,
+            recipient_may_dispute: false,
+        };
+        env.storage().instance().set(&DataKey::Config, &config);
+    });
+
+    FluxoraStreamClient::new(&env, &contract_id).init(&token_id, &admin);
+}
+
 #[test]
 fn test_cancel_fully_accrued_no_refund() {
     let ctx = TestContext::setup();
@@ -1289,7 +1463,9 @@ fn test_withdraw_multiple_times() {
 fn test_create_stream_invalid_cliff_panics() {
     let ctx = TestContext::setup();
     ctx.client().create_stream(
-        &ctx.sender, &ctx.recipient, &1000, &1, &100, &50, &200 // cliff < start
+        &ctx.sender, &ctx.recipient, &1000, &1, &100, &50, &200, // cliff < start
+        &Bytes::new(&ctx.env),
+        &None,
     );
 }
 
@@ -1299,13 +1475,17 @@ fn test_create_stream_edge_cliffs() {
     
     // Cliff at start_time
     let id1 = ctx.client().create_stream(
-        &ctx.sender, &ctx.recipient, &1000_i128, &1_i128, &100, &100, &1100
+        &ctx.sender, &ctx.recipient, &1000_i128, &1_i128, &100, &100, &1100,
+        &Bytes::new(&ctx.env),
+        &None,
     );
     assert_eq!(ctx.client().get_stream_state(&id1).cliff_time, 100);
 
     // Cliff at end_time
     let id2 = ctx.client().create_stream(
-        &ctx.sender, &ctx.recipient, &1000_i128, &1_i128, &100, &1100, &1100
+        &ctx.sender, &ctx.recipient, &1000_i128, &1_i128, &100, &1100, &1100,
+        &Bytes::new(&ctx.env),
+        &None,
     );
     assert_eq!(ctx.client().get_stream_state(&id2).cliff_time, 1100);
 }
@@ -1319,3 +1499,6968 @@ fn test_calculate_accrued_exactly_at_cliff() {
     let accrued = ctx.client().calculate_accrued(&stream_id);
     assert_eq!(accrued, 500, "at cliff, should accrue full amount from start");
 }
+
+// ---------------------------------------------------------------------------
+// Tests — native XLM streaming
+// ---------------------------------------------------------------------------
+
+/// Instance storage key used by the Stellar Asset Contract for its admin.
+/// The native asset contract has no admin, so tests install one to be able
+/// to mint balances to generated (contract) addresses.
+#[contracttype]
+enum NativeSacKey {
+    Admin,
+}
+
+struct NativeContext {
+    env: Env,
+    contract_id: Address,
+    native_id: Address,
+    sender: Address,
+    recipient: Address,
+}
+
+impl NativeContext {
+    fn setup() -> Self {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let native_id = env
+            .deployer()
+            .with_stellar_asset(Bytes::from_array(&env, &[0, 0, 0, 0]))
+            .deploy();
+        let minter = Address::generate(&env);
+        env.as_contract(&native_id, || {
+            env.storage().instance().set(&NativeSacKey::Admin, &minter);
+        });
+
+        let contract_id = env.register_contract(None, FluxoraStream);
+        let admin = Address::generate(&env);
+        let sender = Address::generate(&env);
+        let recipient = Address::generate(&env);
+
+        FluxoraStreamClient::new(&env, &contract_id).init_native(&admin);
+        StellarAssetClient::new(&env, &native_id).mint(&sender, &10_000_i128);
+
+        NativeContext {
+            env,
+            contract_id,
+            native_id,
+            sender,
+            recipient,
+        }
+    }
+
+    fn client(&self) -> FluxoraStreamClient<'_> {
+        FluxoraStreamClient::new(&self.env, &self.contract_id)
+    }
+
+    fn xlm(&self) -> TokenClient<'_> {
+        TokenClient::new(&self.env, &self.native_id)
+    }
+
+    fn create_default_stream(&self) -> u64 {
+        self.env.ledger().set_timestamp(0);
+        self.client().create_stream(
+            &self.sender,
+            &self.recipient,
+            &1000_i128,
+            &1_i128,
+            &0u64,
+            &0u64,
+            &1000u64,
+            &Bytes::new(&self.env),
+            &None,
+        )
+    }
+}
+
+#[test]
+fn test_init_native_resolves_native_asset() {
+    let ctx = NativeContext::setup();
+    let config = ctx.client().get_config();
+    assert_eq!(config.token, ctx.native_id);
+    assert_eq!(ctx.xlm().symbol(), String::from_str(&ctx.env, "native"));
+}
+
+#[test]
+#[should_panic(expected = "already initialised")]
+fn test_init_native_after_init_panics() {
+    let ctx = TestContext::setup();
+    ctx.client().init_native(&ctx.admin);
+}
+
+#[test]
+fn test_native_stream_create_to_completion() {
+    let ctx = NativeContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    assert_eq!(ctx.xlm().balance(&ctx.contract_id), 1000);
+    assert_eq!(ctx.xlm().balance(&ctx.sender), 9000);
+
+    ctx.env.ledger().set_timestamp(400);
+    assert_eq!(ctx.client().withdraw(&stream_id), 400);
+    assert_eq!(ctx.xlm().balance(&ctx.recipient), 400);
+
+    ctx.env.ledger().set_timestamp(1000);
+    assert_eq!(ctx.client().withdraw(&stream_id), 600);
+    assert_eq!(ctx.xlm().balance(&ctx.recipient), 1000);
+    assert_eq!(ctx.xlm().balance(&ctx.contract_id), 0);
+
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.status, StreamStatus::Completed);
+}
+
+#[test]
+fn test_native_stream_cancel_refunds_sender() {
+    let ctx = NativeContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().cancel_stream(&stream_id);
+    assert_eq!(ctx.xlm().balance(&ctx.sender), 9700);
+    assert_eq!(ctx.xlm().balance(&ctx.contract_id), 300);
+
+    assert_eq!(ctx.client().withdraw(&stream_id), 300);
+    assert_eq!(ctx.xlm().balance(&ctx.recipient), 300);
+    assert_eq!(ctx.xlm().balance(&ctx.contract_id), 0);
+}
+
+#[test]
+fn test_native_stream_insufficient_balance_returns_typed_error() {
+    let ctx = NativeContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+
+    let result = ctx.client().try_create_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &20_000_i128,
+        &20_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
+    );
+    assert_eq!(result, Err(Ok(ContractError::TransferFailed.into())));
+    assert_eq!(ctx.xlm().balance(&ctx.sender), 10_000);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — create_stream_idempotent
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    fn create_idempotent_stream(&self, nonce: &BytesN<32>) -> u64 {
+        self.client().create_stream_idempotent(
+            &self.sender,
+            &self.recipient,
+            &1000_i128,
+            &1_i128,
+            &0u64,
+            &0u64,
+            &1000u64,
+            &Bytes::new(&self.env),
+            &None,
+            nonce,
+        )
+    }
+}
+
+#[test]
+fn test_create_stream_idempotent_first_call_creates_stream() {
+    let ctx = TestContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+    let nonce = BytesN::from_array(&ctx.env, &[1u8; 32]);
+
+    let stream_id = ctx.create_idempotent_stream(&nonce);
+
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.deposit_amount, 1000);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 1000);
+}
+
+#[test]
+fn test_create_stream_idempotent_resubmission_returns_same_id() {
+    let ctx = TestContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+    let nonce = BytesN::from_array(&ctx.env, &[1u8; 32]);
+
+    let first = ctx.create_idempotent_stream(&nonce);
+    let second = ctx.create_idempotent_stream(&nonce);
+
+    assert_eq!(first, second);
+    // Only one deposit was transferred and no new id was allocated
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 1000);
+    assert_eq!(ctx.token().balance(&ctx.sender), 9000);
+    assert_eq!(ctx.create_default_stream(), first + 1);
+}
+
+#[test]
+fn test_create_stream_idempotent_distinct_nonces_create_distinct_streams() {
+    let ctx = TestContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+
+    let a = ctx.create_idempotent_stream(&BytesN::from_array(&ctx.env, &[1u8; 32]));
+    let b = ctx.create_idempotent_stream(&BytesN::from_array(&ctx.env, &[2u8; 32]));
+
+    assert_ne!(a, b);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 2000);
+}
+
+#[test]
+fn test_create_stream_idempotent_nonce_cleared_on_completion() {
+    let ctx = TestContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+    let nonce = BytesN::from_array(&ctx.env, &[7u8; 32]);
+
+    let stream_id = ctx.create_idempotent_stream(&nonce);
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.client().withdraw(&stream_id);
+
+    ctx.env.as_contract(&ctx.contract_id, || {
+        let storage = ctx.env.storage().persistent();
+        assert!(!storage.has(&DataKey::UsedNonce(nonce.clone())));
+        assert!(!storage.has(&DataKey::StreamNonce(stream_id)));
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tests — stream memo
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_create_stream_memo_round_trip() {
+    let ctx = TestContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+    let memo = Bytes::from_slice(&ctx.env, b"INV-2024-0042");
+
+    let stream_id = ctx.client().create_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &1000_i128,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &memo,
+        &None,
+    );
+
+    // Event payload carries (deposit_amount, memo)
+    let (_, topics, data) = ctx.env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("created"), stream_id).into_val(&ctx.env)
+    );
+    let (deposit, event_memo): (i128, Bytes) = data.into_val(&ctx.env);
+    assert_eq!(deposit, 1000);
+    assert_eq!(event_memo, memo);
+
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.memo, memo);
+}
+
+#[test]
+fn test_create_stream_memo_at_max_length_succeeds() {
+    let ctx = TestContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+    let memo = Bytes::from_array(&ctx.env, &[b'x'; 64]);
+
+    let stream_id = ctx.client().create_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &1000_i128,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &memo,
+        &None,
+    );
+    assert_eq!(ctx.client().get_stream_state(&stream_id).memo.len(), 64);
+}
+
+#[test]
+#[should_panic(expected = "memo exceeds 64 bytes")]
+fn test_create_stream_oversized_memo_panics() {
+    let ctx = TestContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+    ctx.client().create_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &1000_i128,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &Bytes::from_array(&ctx.env, &[b'x'; 65]),
+        &None,
+    );
+}
+
+#[test]
+fn test_create_stream_empty_memo_is_cheap() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let state = ctx.client().get_stream_state(&stream_id);
+
+    assert!(state.memo.is_empty());
+    // An empty memo serialises to just the ScVal tag and a zero length
+    assert_eq!(state.memo.to_xdr(&ctx.env).len(), 8);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — derived schedule queries
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_derived_queries_exact_deposit_has_no_dust() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    assert_eq!(ctx.client().get_stream_duration(&stream_id), 1000);
+    assert_eq!(ctx.client().get_total_streamable(&stream_id), 1000);
+    assert_eq!(ctx.client().get_stream_dust(&stream_id), 0);
+}
+
+#[test]
+fn test_derived_queries_excess_deposit_is_dust() {
+    let ctx = TestContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+    let stream_id = ctx.client().create_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &1001_i128,
+        &10_i128,
+        &100u64,
+        &100u64,
+        &200u64,
+        &Bytes::new(&ctx.env),
+        &None,
+    );
+
+    assert_eq!(ctx.client().get_stream_duration(&stream_id), 100);
+    assert_eq!(ctx.client().get_total_streamable(&stream_id), 1000);
+    assert_eq!(ctx.client().get_stream_dust(&stream_id), 1);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — external reference lookup
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    fn create_ref_stream(&self, external_ref: &BytesN<32>) -> u64 {
+        self.env.ledger().set_timestamp(0);
+        self.client().create_stream(
+            &self.sender,
+            &self.recipient,
+            &1000_i128,
+            &1_i128,
+            &0u64,
+            &0u64,
+            &1000u64,
+            &Bytes::new(&self.env),
+            &Some(external_ref.clone()),
+        )
+    }
+}
+
+#[test]
+fn test_get_stream_by_ref_returns_stream() {
+    let ctx = TestContext::setup();
+    ctx.create_default_stream();
+    let external_ref = BytesN::from_array(&ctx.env, &[0x78; 32]);
+    let stream_id = ctx.create_ref_stream(&external_ref);
+
+    let state = ctx.client().get_stream_by_ref(&external_ref);
+    assert_eq!(state.stream_id, stream_id);
+    assert_eq!(state.external_ref, Some(Bytes::from(external_ref)));
+}
+
+#[test]
+#[should_panic(expected = "external_ref already in use")]
+fn test_create_stream_duplicate_ref_panics() {
+    let ctx = TestContext::setup();
+    let external_ref = BytesN::from_array(&ctx.env, &[0x78; 32]);
+    ctx.create_ref_stream(&external_ref);
+    ctx.create_ref_stream(&external_ref);
+}
+
+#[test]
+#[should_panic(expected = "external_ref not found")]
+fn test_get_stream_by_unknown_ref_panics() {
+    let ctx = TestContext::setup();
+    ctx.create_default_stream();
+    ctx.client()
+        .get_stream_by_ref(&BytesN::from_array(&ctx.env, &[0x78; 32]));
+}
+
+#[test]
+fn test_get_stream_by_ref_survives_cancel() {
+    let ctx = TestContext::setup();
+    let external_ref = BytesN::from_array(&ctx.env, &[0x78; 32]);
+    let stream_id = ctx.create_ref_stream(&external_ref);
+
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().cancel_stream(&stream_id);
+
+    let state = ctx.client().get_stream_by_ref(&external_ref);
+    assert_eq!(state.stream_id, stream_id);
+    assert_eq!(state.status, StreamStatus::Cancelled);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — sample_accrual
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_sample_accrual_standard_stream() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    let times = vec![&ctx.env, 0u64, 250, 500, 1000, 2000];
+    let samples = ctx.client().sample_accrual(&stream_id, &times);
+
+    assert_eq!(samples, vec![&ctx.env, 0_i128, 250, 500, 1000, 1000]);
+}
+
+#[test]
+fn test_sample_accrual_respects_cliff() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_cliff_stream();
+
+    let times = vec![&ctx.env, 499u64, 500];
+    let samples = ctx.client().sample_accrual(&stream_id, &times);
+
+    assert_eq!(samples, vec![&ctx.env, 0_i128, 500]);
+}
+
+#[test]
+#[should_panic(expected = "too many sample points (max 100)")]
+fn test_sample_accrual_too_many_points_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    let mut times = Vec::new(&ctx.env);
+    for t in 0..101u64 {
+        times.push_back(t);
+    }
+    ctx.client().sample_accrual(&stream_id, &times);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — dust refund on completion
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// deposit=1001, rate=10/s over 100s → 1 token of dust.
+    fn create_dust_stream(&self) -> u64 {
+        self.env.ledger().set_timestamp(0);
+        self.client().create_stream(
+            &self.sender,
+            &self.recipient,
+            &1001_i128,
+            &10_i128,
+            &0u64,
+            &0u64,
+            &100u64,
+            &Bytes::new(&self.env),
+            &None,
+        )
+    }
+}
+
+#[test]
+fn test_withdraw_completion_refunds_dust_to_sender() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_dust_stream();
+    assert_eq!(ctx.token().balance(&ctx.sender), 8999);
+
+    ctx.env.ledger().set_timestamp(100);
+    let amount = ctx.client().withdraw(&stream_id);
+
+    assert_eq!(amount, 1000);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 1000);
+    assert_eq!(ctx.token().balance(&ctx.sender), 9000, "1 token of dust refunded");
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 0);
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.status, StreamStatus::Completed);
+}
+
+#[test]
+fn test_withdraw_partial_does_not_refund_dust() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_dust_stream();
+
+    ctx.env.ledger().set_timestamp(50);
+    ctx.client().withdraw(&stream_id);
+
+    assert_eq!(ctx.token().balance(&ctx.sender), 8999);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 501);
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.status, StreamStatus::Active);
+}
+
+#[test]
+fn test_cancelled_dust_stream_refunds_dust_once() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_dust_stream();
+
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().cancel_stream(&stream_id);
+    assert_eq!(ctx.token().balance(&ctx.sender), 9000);
+
+    ctx.client().withdraw(&stream_id);
+    assert_eq!(ctx.token().balance(&ctx.sender), 9000);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 1000);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 0);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — archive_completed_stream
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_archive_completed_stream_replaces_full_record() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(1200);
+    ctx.client().withdraw(&stream_id);
+
+    ctx.client().archive_completed_stream(&stream_id);
+
+    let archived = ctx.client().get_archived_stream(&stream_id);
+    assert_eq!(archived.stream_id, stream_id);
+    assert_eq!(archived.sender, ctx.sender);
+    assert_eq!(archived.recipient, ctx.recipient);
+    assert_eq!(archived.total_streamed, 1000);
+    assert_eq!(archived.completed_at, 1200);
+    assert!(ctx.client().stream_exists(&stream_id));
+
+    ctx.env.as_contract(&ctx.contract_id, || {
+        let storage = ctx.env.storage().persistent();
+        assert!(!storage.has(&DataKey::Stream(stream_id)));
+        assert!(!storage.has(&DataKey::CompletedAt(stream_id)));
+    });
+}
+
+#[test]
+#[should_panic(expected = "only completed streams can be archived")]
+fn test_archive_active_stream_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().archive_completed_stream(&stream_id);
+}
+
+#[test]
+#[should_panic(expected = "stream not found")]
+fn test_get_stream_state_after_archive_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.client().withdraw(&stream_id);
+    ctx.client().archive_completed_stream(&stream_id);
+
+    ctx.client().get_stream_state(&stream_id);
+}
+
+#[test]
+fn test_stream_exists_unknown_id() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    assert!(ctx.client().stream_exists(&stream_id));
+    assert!(!ctx.client().stream_exists(&(stream_id + 1)));
+}
+
+// ---------------------------------------------------------------------------
+// Tests — designated refund address
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    fn create_refund_stream(&self, deposit_amount: i128, refund_address: &Address) -> u64 {
+        self.env.ledger().set_timestamp(0);
+        self.client().create_stream_with_refund(
+            &self.sender,
+            &self.recipient,
+            &deposit_amount,
+            &1_i128,
+            &0u64,
+            &0u64,
+            &1000u64,
+            &Bytes::new(&self.env),
+            &None,
+            refund_address,
+        )
+    }
+}
+
+#[test]
+fn test_create_stream_defaults_refund_address_to_sender() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.refund_address, ctx.sender);
+}
+
+#[test]
+fn test_cancel_refunds_designated_address() {
+    let ctx = TestContext::setup();
+    let treasury = Address::generate(&ctx.env);
+    let stream_id = ctx.create_refund_stream(1000, &treasury);
+
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().cancel_stream(&stream_id);
+
+    assert_eq!(ctx.token().balance(&treasury), 700);
+    assert_eq!(ctx.token().balance(&ctx.sender), 9000, "sender gets nothing back");
+}
+
+#[test]
+fn test_completion_dust_goes_to_designated_address() {
+    let ctx = TestContext::setup();
+    let treasury = Address::generate(&ctx.env);
+    let stream_id = ctx.create_refund_stream(1001, &treasury);
+
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.client().withdraw(&stream_id);
+
+    assert_eq!(ctx.token().balance(&treasury), 1);
+    assert_eq!(ctx.token().balance(&ctx.sender), 8999);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — clone_stream
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_clone_active_stream_shifts_schedule() {
+    let ctx = TestContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+    let source_id = ctx.client().create_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &1000_i128,
+        &1_i128,
+        &100u64,
+        &300u64, // cliff 200s after start
+        &1100u64,
+        &Bytes::from_slice(&ctx.env, b"INV-1"),
+        &None,
+    );
+
+    let clone_id = ctx.client().clone_stream(&source_id, &5000u64);
+
+    assert_ne!(clone_id, source_id);
+    let clone = ctx.client().get_stream_state(&clone_id);
+    assert_eq!(clone.start_time, 5000);
+    assert_eq!(clone.cliff_time, 5200);
+    assert_eq!(clone.end_time, 6000);
+    assert_eq!(clone.recipient, ctx.recipient);
+    assert_eq!(clone.deposit_amount, 1000);
+    assert_eq!(clone.rate_per_second, 1);
+    assert_eq!(clone.memo, Bytes::from_slice(&ctx.env, b"INV-1"));
+    assert_eq!(clone.withdrawn_amount, 0);
+    assert_eq!(clone.status, StreamStatus::Scheduled);
+
+    // A fresh deposit was taken for the clone
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 2000);
+    assert_eq!(ctx.token().balance(&ctx.sender), 8000);
+}
+
+#[test]
+fn test_clone_completed_stream_is_independent() {
+    let ctx = TestContext::setup();
+    let source_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.client().withdraw(&source_id);
+
+    let clone_id = ctx.client().clone_stream(&source_id, &1000u64);
+
+    ctx.env.ledger().set_timestamp(1400);
+    assert_eq!(ctx.client().withdraw(&clone_id), 400);
+
+    let source = ctx.client().get_stream_state(&source_id);
+    assert_eq!(source.status, StreamStatus::Completed);
+    assert_eq!(source.withdrawn_amount, 1000);
+    let clone = ctx.client().get_stream_state(&clone_id);
+    assert_eq!(clone.start_time, 1000);
+    assert_eq!(clone.end_time, 2000);
+    assert_eq!(clone.withdrawn_amount, 400);
+}
+
+#[test]
+fn test_clone_milestone_stream() {
+    let ctx = TestContext::setup();
+    let source_id = ctx.create_milestone_stream();
+    ctx.client().approve_milestone(&source_id, &0);
+    ctx.client().cancel_stream(&source_id);
+    assert_eq!(ctx.client().get_stream_state(&source_id).deposit_amount, 300);
+
+    let clone_id = ctx.client().clone_stream(&source_id, &500u64);
+
+    let clone = ctx.client().get_stream_state(&clone_id);
+    assert_eq!(clone.deposit_amount, 1000);
+    assert_eq!(clone.milestones, vec![&ctx.env, 300_i128, 700_i128, 1000_i128]);
+    assert_eq!(clone.milestones_approved, 0);
+    assert_eq!((clone.start_time, clone.end_time), (500, 500));
+    ctx.client().approve_milestone(&clone_id, &0);
+    ctx.client().approve_milestone(&clone_id, &1);
+    ctx.env.ledger().set_timestamp(500);
+    assert_eq!(ctx.client().withdraw(&clone_id), 700);
+}
+
+#[test]
+fn test_clone_cancelled_stream_funds_full_schedule() {
+    let ctx = TestContext::setup();
+    let source_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().cancel_stream(&source_id);
+    assert_eq!(ctx.client().get_stream_state(&source_id).deposit_amount, 300);
+
+    let clone_id = ctx.client().clone_stream(&source_id, &300u64);
+
+    let clone = ctx.client().get_stream_state(&clone_id);
+    assert_eq!(clone.deposit_amount, 1000);
+    assert_eq!(clone.status, StreamStatus::Active);
+    ctx.env.ledger().set_timestamp(1300);
+    assert_eq!(ctx.client().withdraw(&clone_id), 1000);
+}
+
+#[test]
+fn test_clone_stream_shifts_withdraw_schedule() {
+    let ctx = TestContext::setup();
+    let source_id = ctx.create_scheduled_stream();
+
+    let clone_id = ctx.client().clone_stream(&source_id, &2000u64);
+
+    assert_eq!(
+        ctx.client().get_stream_state(&clone_id).withdraw_schedule,
+        Some(vec![&ctx.env, 2250u64, 2500u64, 2750u64, 3000u64])
+    );
+    assert_eq!(ctx.client().get_next_withdraw_time(&clone_id), Some(2250));
+    ctx.env.ledger().set_timestamp(2300);
+    assert_eq!(ctx.client().withdraw(&clone_id), 300);
+}
+
+#[test]
+fn test_clone_stream_does_not_copy_external_ref() {
+    let ctx = TestContext::setup();
+    let external_ref = BytesN::from_array(&ctx.env, &[0x78; 32]);
+    let source_id = ctx.create_ref_stream(&external_ref);
+
+    let clone_id = ctx.client().clone_stream(&source_id, &0u64);
+
+    assert_eq!(ctx.client().get_stream_state(&clone_id).external_ref, None);
+    assert_eq!(
+        ctx.client().get_stream_by_ref(&external_ref).stream_id,
+        source_id
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Tests — transfer_sender_rights
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_transfer_sender_rights_moves_cancel_and_refund() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let new_sender = Address::generate(&ctx.env);
+
+    ctx.client().transfer_sender_rights(&stream_id, &new_sender);
+
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.sender, new_sender);
+    assert_eq!(state.refund_address, new_sender);
+
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().cancel_stream(&stream_id);
+    assert_eq!(ctx.token().balance(&new_sender), 600);
+    assert_eq!(ctx.token().balance(&ctx.sender), 9000);
+}
+
+#[test]
+fn test_transfer_sender_rights_requires_current_sender_auth() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let new_sender = Address::generate(&ctx.env);
+
+    ctx.client().transfer_sender_rights(&stream_id, &new_sender);
+
+    let auths = ctx.env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, ctx.sender);
+}
+
+#[test]
+fn test_transfer_sender_rights_keeps_designated_refund_address() {
+    let ctx = TestContext::setup();
+    let treasury = Address::generate(&ctx.env);
+    let stream_id = ctx.create_refund_stream(1000, &treasury);
+
+    ctx.client()
+        .transfer_sender_rights(&stream_id, &Address::generate(&ctx.env));
+
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.refund_address, treasury);
+}
+
+#[test]
+fn test_transfer_sender_rights_emits_event() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let new_sender = Address::generate(&ctx.env);
+
+    ctx.client().transfer_sender_rights(&stream_id, &new_sender);
+
+    let (_, topics, data) = ctx.env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("sndr_chg"), stream_id).into_val(&ctx.env)
+    );
+    let (old, new): (Address, Address) = data.into_val(&ctx.env);
+    assert_eq!(old, ctx.sender);
+    assert_eq!(new, new_sender);
+}
+
+#[test]
+#[should_panic(expected = "stream must be active or paused to transfer sender rights")]
+fn test_transfer_sender_rights_cancelled_stream_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().cancel_stream(&stream_id);
+
+    ctx.client()
+        .transfer_sender_rights(&stream_id, &Address::generate(&ctx.env));
+}
+
+#[test]
+#[should_panic(expected = "sender and recipient must be different")]
+fn test_transfer_sender_rights_to_recipient_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().transfer_sender_rights(&stream_id, &ctx.recipient);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — withdraw_available
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_withdraw_available_nothing_accrued_returns_zero() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_cliff_stream();
+    ctx.env.ledger().set_timestamp(100); // before cliff
+
+    assert_eq!(ctx.client().withdraw_available(&stream_id), 0);
+    assert!(ctx.env.auths().is_empty(), "no auth needed when nothing moves");
+    assert_eq!(ctx.token().balance(&ctx.recipient), 0);
+}
+
+#[test]
+fn test_withdraw_available_pays_out_accrued() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(250);
+
+    assert_eq!(ctx.client().withdraw_available(&stream_id), 250);
+    assert_eq!(ctx.env.auths()[0].0, ctx.recipient);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 250);
+
+    // Second call in the same ledger has nothing left
+    assert_eq!(ctx.client().withdraw_available(&stream_id), 0);
+}
+
+#[test]
+fn test_withdraw_available_paused_and_completed_return_zero() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(300);
+
+    ctx.client().pause_stream(&stream_id);
+    assert_eq!(ctx.client().withdraw_available(&stream_id), 0);
+
+    ctx.client().resume_stream(&stream_id);
+    ctx.env.ledger().set_timestamp(1000);
+    assert_eq!(ctx.client().withdraw_available(&stream_id), 1000);
+    assert_eq!(ctx.client().withdraw_available(&stream_id), 0);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — recurring streams
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// 100 tokens every 100 seconds starting at t=0.
+    fn create_recurring_stream(&self) -> u64 {
+        self.env.ledger().set_timestamp(0);
+        self.client()
+            .create_recurring_stream(&self.sender, &self.recipient, &100_i128, &100u64, &0u64)
+    }
+
+    fn approve_contract(&self, amount: i128) {
+        self.token()
+            .approve(&self.sender, &self.contract_id, &amount, &1000u32);
+    }
+}
+
+#[test]
+fn test_recurring_stream_rolls_consecutive_periods() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_recurring_stream();
+    ctx.approve_contract(1000);
+
+    ctx.env.ledger().set_timestamp(100);
+    assert_eq!(ctx.client().withdraw(&stream_id), 100);
+    // Fully withdrawn but still renewable
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.status, StreamStatus::Active);
+
+    assert!(ctx.client().roll_period(&stream_id));
+    ctx.env.ledger().set_timestamp(150);
+    assert_eq!(ctx.client().withdraw(&stream_id), 50);
+
+    ctx.env.ledger().set_timestamp(200);
+    assert!(ctx.client().roll_period(&stream_id));
+    ctx.env.ledger().set_timestamp(300);
+    assert_eq!(ctx.client().withdraw(&stream_id), 150);
+
+    let config = ctx.client().get_recurring_config(&stream_id);
+    assert_eq!(config.periods_funded, 3);
+    assert!(!config.lapsed);
+    assert_eq!(ctx.token().balance(&ctx.sender), 9700);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 300);
+}
+
+#[test]
+#[should_panic(expected = "current period has not elapsed")]
+fn test_recurring_stream_roll_before_period_end_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_recurring_stream();
+    ctx.approve_contract(1000);
+
+    ctx.env.ledger().set_timestamp(99);
+    ctx.client().roll_period(&stream_id);
+}
+
+#[test]
+fn test_recurring_stream_lapses_and_recovers() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_recurring_stream();
+    // No allowance granted: the first roll cannot be funded
+    ctx.env.ledger().set_timestamp(100);
+    assert!(!ctx.client().roll_period(&stream_id));
+
+    let config = ctx.client().get_recurring_config(&stream_id);
+    assert!(config.lapsed);
+    assert_eq!(config.periods_funded, 1);
+    assert_eq!(ctx.client().withdraw(&stream_id), 100);
+
+    // Nothing accrues while lapsed
+    ctx.env.ledger().set_timestamp(400);
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), 100);
+
+    // Re-funded: the next period starts at the recovery time
+    ctx.approve_contract(100);
+    assert!(ctx.client().roll_period(&stream_id));
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.end_time, 500);
+    assert!(!ctx.client().get_recurring_config(&stream_id).lapsed);
+
+    ctx.env.ledger().set_timestamp(450);
+    assert_eq!(ctx.client().withdraw(&stream_id), 50);
+}
+
+#[test]
+#[should_panic(expected = "stream must be active to roll")]
+fn test_recurring_stream_cancel_stops_rolls() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_recurring_stream();
+    ctx.approve_contract(1000);
+
+    ctx.env.ledger().set_timestamp(50);
+    ctx.client().cancel_stream(&stream_id);
+
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().roll_period(&stream_id);
+}
+
+#[test]
+fn test_recurring_stream_completes_after_cancel() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_recurring_stream();
+
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().cancel_stream(&stream_id);
+    assert_eq!(ctx.client().withdraw(&stream_id), 100);
+
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.status, StreamStatus::Completed);
+}
+
+#[test]
+#[should_panic(expected = "period_deposit must be a multiple of period_seconds")]
+fn test_recurring_stream_uneven_period_deposit_panics() {
+    let ctx = TestContext::setup();
+    // 250 over 100s streams at 2/s, which would leave 50 of every period
+    ctx.client()
+        .create_recurring_stream(&ctx.sender, &ctx.recipient, &250_i128, &100u64, &0u64);
+}
+
+#[test]
+fn test_recurring_stream_streams_each_full_period_deposit() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx
+        .client()
+        .create_recurring_stream(&ctx.sender, &ctx.recipient, &300_i128, &100u64, &0u64);
+    ctx.approve_contract(300);
+
+    ctx.env.ledger().set_timestamp(100);
+    assert!(ctx.client().roll_period(&stream_id));
+    ctx.env.ledger().set_timestamp(200);
+    assert_eq!(ctx.client().withdraw(&stream_id), 600);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 0);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — lock_stream
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_lock_stream_requires_sender_and_recipient_auth() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    assert!(!ctx.client().is_stream_locked(&stream_id));
+
+    ctx.client().lock_stream(&stream_id);
+
+    let auths = ctx.env.auths();
+    assert_eq!(auths.len(), 2);
+    assert_eq!(auths[0].0, ctx.sender);
+    assert_eq!(auths[1].0, ctx.recipient);
+    assert!(ctx.client().is_stream_locked(&stream_id));
+}
+
+#[test]
+#[should_panic(expected = "stream is locked")]
+fn test_locked_stream_cannot_be_paused() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().lock_stream(&stream_id);
+    ctx.client().pause_stream(&stream_id);
+}
+
+#[test]
+#[should_panic(expected = "stream is locked")]
+fn test_locked_stream_cannot_be_cancelled() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().lock_stream(&stream_id);
+    ctx.client().cancel_stream(&stream_id);
+}
+
+#[test]
+#[should_panic(expected = "stream is locked")]
+fn test_locked_stream_cannot_be_cancelled_by_admin() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().lock_stream(&stream_id);
+    ctx.client().cancel_stream_as_admin(&stream_id);
+}
+
+#[test]
+fn test_locked_stream_still_withdraws_to_completion() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().lock_stream(&stream_id);
+
+    ctx.env.ledger().set_timestamp(1000);
+    assert_eq!(ctx.client().withdraw(&stream_id), 1000);
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.status, StreamStatus::Completed);
+    assert!(state.immutable);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — create_monthly_stream
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// 300 tokens unlocked every 100s, 3 times, first unlock at t=100.
+    fn create_monthly_stream(&self) -> u64 {
+        self.env.ledger().set_timestamp(0);
+        self.client().create_monthly_stream(
+            &self.sender,
+            &self.recipient,
+            &300_i128,
+            &100u64,
+            &3u32,
+            &100u64,
+        )
+    }
+}
+
+#[test]
+fn test_monthly_stream_builds_schedule() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_monthly_stream();
+
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.deposit_amount, 900);
+    assert_eq!(state.start_time, 0);
+    assert_eq!(state.cliff_time, 100);
+    assert_eq!(state.end_time, 300);
+    assert_eq!(state.step_seconds, 100);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 900);
+}
+
+#[test]
+fn test_monthly_stream_unlocks_only_at_boundaries() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_monthly_stream();
+
+    let times = vec![&ctx.env, 0u64, 99, 100, 199, 200, 299, 300, 1000];
+    let samples = ctx.client().sample_accrual(&stream_id, &times);
+    assert_eq!(
+        samples,
+        vec![&ctx.env, 0_i128, 0, 300, 300, 600, 600, 900, 900]
+    );
+}
+
+#[test]
+fn test_monthly_stream_withdraws_per_period_and_completes() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_monthly_stream();
+
+    ctx.env.ledger().set_timestamp(150);
+    assert_eq!(ctx.client().withdraw(&stream_id), 300);
+    ctx.env.ledger().set_timestamp(300);
+    assert_eq!(ctx.client().withdraw(&stream_id), 600);
+
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.status, StreamStatus::Completed);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 0);
+    assert_eq!(ctx.token().balance(&ctx.sender), 9100, "no dust refunded");
+}
+
+#[test]
+#[should_panic(expected = "periods must be positive")]
+fn test_monthly_stream_zero_periods_panics() {
+    let ctx = TestContext::setup();
+    ctx.client().create_monthly_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &300_i128,
+        &100u64,
+        &0u32,
+        &100u64,
+    );
+}
+
+#[test]
+#[should_panic(expected = "overflow calculating deposit_amount")]
+fn test_monthly_stream_amount_overflow_panics() {
+    let ctx = TestContext::setup();
+    ctx.client().create_monthly_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &(i128::MAX / 2),
+        &100u64,
+        &3u32,
+        &100u64,
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Tests — withdrawal fees
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_withdrawal_fee_tally_matches_collector_balance() {
+    let ctx = TestContext::setup();
+    let collector = Address::generate(&ctx.env);
+    let stream_id = ctx.create_default_stream();
+    ctx.client().set_withdrawal_fee(&250u32, &collector); // 2.5%
+
+    ctx.env.ledger().set_timestamp(200);
+    assert_eq!(ctx.client().withdraw(&stream_id), 195);
+    ctx.env.ledger().set_timestamp(530);
+    assert_eq!(ctx.client().withdraw(&stream_id), 322); // fee 8.25 rounds down
+    ctx.env.ledger().set_timestamp(1000);
+    assert_eq!(ctx.client().withdraw(&stream_id), 459); // fee 11.75 rounds down
+
+    let collected = ctx.client().get_fees_collected(&ctx.token_id);
+    assert_eq!(collected, 5 + 8 + 11);
+    assert_eq!(ctx.token().balance(&collector), collected);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 1000 - collected);
+
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.withdrawn_amount, 1000);
+    assert_eq!(state.status, StreamStatus::Completed);
+}
+
+#[test]
+fn test_withdrawal_without_fee_leaves_tally_empty() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(500);
+    assert_eq!(ctx.client().withdraw(&stream_id), 500);
+    assert_eq!(ctx.client().get_fees_collected(&ctx.token_id), 0);
+}
+
+#[test]
+#[should_panic(expected = "fee_bps cannot exceed 10000")]
+fn test_set_withdrawal_fee_above_max_panics() {
+    let ctx = TestContext::setup();
+    ctx.client()
+        .set_withdrawal_fee(&10_001u32, &Address::generate(&ctx.env));
+}
+
+#[test]
+fn test_set_fee_collector_routes_later_fees() {
+    let ctx = TestContext::setup();
+    let old_collector = Address::generate(&ctx.env);
+    let new_collector = Address::generate(&ctx.env);
+    let stream_id = ctx.create_default_stream();
+    ctx.client().set_withdrawal_fee(&1000u32, &old_collector); // 10%
+
+    ctx.env.ledger().set_timestamp(200);
+    ctx.client().withdraw(&stream_id);
+    ctx.client().set_fee_collector(&new_collector);
+    ctx.env.ledger().set_timestamp(500);
+    ctx.client().withdraw(&stream_id);
+
+    assert_eq!(ctx.token().balance(&old_collector), 20);
+    assert_eq!(ctx.token().balance(&new_collector), 30);
+    assert_eq!(ctx.client().get_fees_collected(&ctx.token_id), 50);
+}
+
+#[test]
+fn test_set_fee_collector_requires_admin_auth() {
+    let ctx = TestContext::setup();
+    let new_collector = Address::generate(&ctx.env);
+    ctx.env.mock_auths(&[]);
+    assert!(ctx.client().try_set_fee_collector(&new_collector).is_err());
+
+    ctx.mock_single_auth(
+        &ctx.sender,
+        "set_fee_collector",
+        (new_collector.clone(),).into_val(&ctx.env),
+    );
+    assert!(ctx.client().try_set_fee_collector(&new_collector).is_err());
+
+    ctx.mock_single_auth(
+        &ctx.admin,
+        "set_fee_collector",
+        (new_collector.clone(),).into_val(&ctx.env),
+    );
+    ctx.client().set_fee_collector(&new_collector);
+}
+
+#[test]
+#[should_panic(expected = "fee collector cannot be the contract")]
+fn test_set_fee_collector_to_contract_panics() {
+    let ctx = TestContext::setup();
+    ctx.client().set_fee_collector(&ctx.contract_id);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — per-address indexes and summary
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_streams_summary_for_address() {
+    let ctx = TestContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+
+    // ctx.sender sends 5 streams of 1000 over 1000s
+    let mut sent = Vec::new(&ctx.env);
+    for _ in 0..5 {
+        sent.push_back(ctx.create_default_stream());
+    }
+
+    // ...and receives 3 streams of 500 over 500s from another payer
+    let payer = Address::generate(&ctx.env);
+    ctx.sac.mint(&payer, &1500_i128);
+    let mut received = Vec::new(&ctx.env);
+    for _ in 0..3 {
+        received.push_back(ctx.client().create_stream(
+            &payer,
+            &ctx.sender,
+            &500_i128,
+            &1_i128,
+            &0u64,
+            &0u64,
+            &500u64,
+            &Bytes::new(&ctx.env),
+            &None,
+        ));
+    }
+
+    assert_eq!(ctx.client().get_streams_by_sender(&ctx.sender), sent);
+    assert_eq!(ctx.client().get_streams_by_recipient(&ctx.sender), received);
+
+    ctx.env.ledger().set_timestamp(200);
+    ctx.client().pause_stream(&sent.get(0).unwrap());
+    ctx.client().lock_stream(&sent.get(1).unwrap());
+    ctx.client().withdraw(&received.get(0).unwrap());
+    ctx.client().pause_stream(&received.get(1).unwrap());
+
+    let summary = ctx.client().get_streams_summary_for_address(&ctx.sender);
+    assert_eq!(summary.active_sent, 4);
+    assert_eq!(summary.active_received, 2);
+    // Stream 0 already withdrawn, stream 1 paused, stream 2 has 200 accrued
+    assert_eq!(summary.total_claimable, 200);
+    // 4 cancellable streams (the locked one excluded) with 800 unstreamed each
+    assert_eq!(summary.total_refundable, 4 * 800);
+    assert_eq!(summary.total_deposited_by_sender, 5000);
+    assert_eq!(summary.total_received_by_recipient, 200);
+}
+
+#[test]
+fn test_streams_summary_matches_withdraw_and_cancel_amounts() {
+    let ctx = TestContext::setup();
+    let scheduled = ctx.create_future_stream();
+    let advanced = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(50);
+    ctx.client().request_advance(&advanced, &300);
+    ctx.client().approve_advance(&advanced);
+
+    // The advance leaves nothing claimable rather than a negative amount
+    let summary = ctx.client().get_streams_summary_for_address(&ctx.recipient);
+    assert_eq!(summary.total_claimable, 0);
+    // The stream yet to start refunds its whole deposit
+    let summary = ctx.client().get_streams_summary_for_address(&ctx.sender);
+    assert_eq!(summary.total_refundable, 1000 + 700);
+    let view = |stream_id| ctx.client().get_stream_view(&stream_id);
+    assert_eq!(
+        summary.total_refundable,
+        view(scheduled).refundable_if_cancelled_now + view(advanced).refundable_if_cancelled_now
+    );
+
+    ctx.env.ledger().set_timestamp(600);
+    let summary = ctx.client().get_streams_summary_for_address(&ctx.recipient);
+    assert_eq!(summary.total_claimable, 500 + 300);
+    ctx.client().freeze_recipient(&ctx.recipient);
+    let summary = ctx.client().get_streams_summary_for_address(&ctx.recipient);
+    assert_eq!(summary.total_claimable, 0);
+}
+
+#[test]
+fn test_transfer_sender_rights_moves_sender_index() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let new_sender = Address::generate(&ctx.env);
+
+    ctx.client().transfer_sender_rights(&stream_id, &new_sender);
+
+    assert!(ctx.client().get_streams_by_sender(&ctx.sender).is_empty());
+    assert_eq!(
+        ctx.client().get_streams_by_sender(&new_sender),
+        vec![&ctx.env, stream_id]
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Tests — create_stream_full
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_create_stream_full_matches_persisted_state() {
+    let ctx = TestContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+    let external_ref = BytesN::from_array(&ctx.env, &[0x11; 32]);
+
+    let stream = ctx.client().create_stream_full(
+        &ctx.sender,
+        &ctx.recipient,
+        &1000_i128,
+        &1_i128,
+        &0u64,
+        &100u64,
+        &1000u64,
+        &Bytes::from_slice(&ctx.env, b"payroll"),
+        &Some(external_ref.clone()),
+    );
+
+    let state = ctx.client().get_stream_state(&stream.stream_id);
+    assert_eq!(stream.stream_id, 0);
+    assert_eq!(stream.sender, state.sender);
+    assert_eq!(stream.recipient, state.recipient);
+    assert_eq!(stream.deposit_amount, state.deposit_amount);
+    assert_eq!(stream.rate_per_second, state.rate_per_second);
+    assert_eq!(stream.start_time, state.start_time);
+    assert_eq!(stream.cliff_time, state.cliff_time);
+    assert_eq!(stream.end_time, state.end_time);
+    assert_eq!(stream.withdrawn_amount, state.withdrawn_amount);
+    assert_eq!(stream.status, state.status);
+    assert_eq!(stream.memo, state.memo);
+    assert_eq!(stream.external_ref, Some(Bytes::from(external_ref)));
+    assert_eq!(stream.refund_address, state.refund_address);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — withdrawal operators
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// Replace blanket auth mocking with a single signature from `address`
+    /// for the contract call `fn_name(args)`.
+    fn mock_single_auth(&self, address: &Address, fn_name: &str, args: Vec<Val>) {
+        self.env.mock_auths(&[MockAuth {
+            address,
+            invoke: &MockAuthInvoke {
+                contract: &self.contract_id,
+                fn_name,
+                args,
+                sub_invokes: &[],
+            },
+        }]);
+    }
+
+    fn mock_operator_auth(&self, stream_id: u64, operator: &Address) {
+        let args = (stream_id, operator.clone()).into_val(&self.env);
+        self.mock_single_auth(operator, "withdraw_as_operator", args);
+    }
+}
+
+#[test]
+fn test_approved_operator_withdraws_to_recipient() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let operator = Address::generate(&ctx.env);
+    ctx.client().approve_operator(&stream_id, &operator);
+
+    ctx.env.ledger().set_timestamp(300);
+    ctx.mock_operator_auth(stream_id, &operator);
+    let amount = ctx.client().withdraw_as_operator(&stream_id, &operator);
+
+    assert_eq!(amount, 300);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 300);
+    assert_eq!(ctx.token().balance(&operator), 0);
+}
+
+#[test]
+fn test_operator_withdraw_requires_operator_signature() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let operator = Address::generate(&ctx.env);
+    ctx.client().approve_operator(&stream_id, &operator);
+
+    ctx.env.ledger().set_timestamp(300);
+    ctx.env.mock_auths(&[]);
+    let result = ctx.client().try_withdraw_as_operator(&stream_id, &operator);
+
+    assert!(result.is_err());
+    assert_eq!(ctx.token().balance(&ctx.recipient), 0);
+}
+
+#[test]
+fn test_unapproved_operator_rejected() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let stranger = Address::generate(&ctx.env);
+
+    ctx.env.ledger().set_timestamp(300);
+    ctx.mock_operator_auth(stream_id, &stranger);
+    let result = ctx.client().try_withdraw_as_operator(&stream_id, &stranger);
+
+    assert!(result.is_err());
+    assert_eq!(ctx.token().balance(&ctx.recipient), 0);
+}
+
+#[test]
+fn test_revoked_operator_rejected() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let operator = Address::generate(&ctx.env);
+    ctx.client().approve_operator(&stream_id, &operator);
+    ctx.client().revoke_operator(&stream_id, &operator);
+
+    ctx.env.ledger().set_timestamp(300);
+    ctx.mock_operator_auth(stream_id, &operator);
+    let result = ctx.client().try_withdraw_as_operator(&stream_id, &operator);
+
+    assert!(result.is_err());
+    assert_eq!(ctx.token().balance(&ctx.recipient), 0);
+}
+
+#[test]
+fn test_approve_operator_requires_recipient_auth() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.client()
+        .approve_operator(&stream_id, &Address::generate(&ctx.env));
+
+    let auths = ctx.env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, ctx.recipient);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — stream categories
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_new_streams_default_to_other_category() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.category, StreamCategory::Other);
+    assert_eq!(
+        ctx.client().get_streams_by_category(&StreamCategory::Other),
+        vec![&ctx.env, stream_id]
+    );
+}
+
+#[test]
+fn test_set_stream_category_moves_between_indexes() {
+    let ctx = TestContext::setup();
+    let payroll = ctx.create_default_stream();
+    let grant = ctx.create_default_stream();
+
+    ctx.client()
+        .set_stream_category(&payroll, &StreamCategory::Payroll);
+    ctx.client().set_stream_category(&grant, &StreamCategory::Grant);
+    assert_eq!(ctx.env.auths()[0].0, ctx.sender);
+
+    let client = ctx.client();
+    assert_eq!(
+        client.get_streams_by_category(&StreamCategory::Payroll),
+        vec![&ctx.env, payroll]
+    );
+    assert_eq!(
+        client.get_streams_by_category(&StreamCategory::Grant),
+        vec![&ctx.env, grant]
+    );
+    assert!(client.get_streams_by_category(&StreamCategory::Other).is_empty());
+    assert!(client
+        .get_streams_by_category(&StreamCategory::Vesting)
+        .is_empty());
+    assert_eq!(
+        client.get_stream_state(&payroll).category,
+        StreamCategory::Payroll
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Tests — lower_cliff
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_lower_cliff_allows_earlier_withdrawal() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_cliff_stream();
+    ctx.env.ledger().set_timestamp(300);
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), 0);
+
+    ctx.client().lower_cliff(&stream_id, &200u64);
+
+    assert_eq!(ctx.client().get_stream_state(&stream_id).cliff_time, 200);
+    assert_eq!(ctx.client().withdraw(&stream_id), 300);
+}
+
+#[test]
+#[should_panic(expected = "new_cliff must be earlier than the current cliff")]
+fn test_lower_cliff_rejects_raising() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_cliff_stream();
+    ctx.client().lower_cliff(&stream_id, &600u64);
+}
+
+#[test]
+#[should_panic(expected = "cliff_time must be within [start_time, end_time]")]
+fn test_lower_cliff_before_start_panics() {
+    let ctx = TestContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+    let stream_id = ctx.client().create_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &1000_i128,
+        &1_i128,
+        &100u64,
+        &500u64,
+        &1100u64,
+        &Bytes::new(&ctx.env),
+        &None,
+    );
+    ctx.client().lower_cliff(&stream_id, &50u64);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — sender delegates
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    fn create_delegated_stream(&self, can_cancel: bool) -> (u64, Address) {
+        let stream_id = self.create_default_stream();
+        let delegate = Address::generate(&self.env);
+        self.client()
+            .set_sender_delegate(&stream_id, &delegate, &can_cancel);
+        (stream_id, delegate)
+    }
+
+    fn mock_delegate_auth(&self, delegate: &Address, fn_name: &str, stream_id: u64) {
+        let args = (stream_id,).into_val(&self.env);
+        self.mock_single_auth(delegate, fn_name, args);
+    }
+}
+
+#[test]
+fn test_delegate_can_pause_and_resume() {
+    let ctx = TestContext::setup();
+    let (stream_id, delegate) = ctx.create_delegated_stream(false);
+
+    ctx.mock_delegate_auth(&delegate, "pause_stream_as_delegate", stream_id);
+    ctx.client().pause_stream_as_delegate(&stream_id);
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Paused
+    );
+
+    ctx.mock_delegate_auth(&delegate, "resume_stream_as_delegate", stream_id);
+    ctx.client().resume_stream_as_delegate(&stream_id);
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Active
+    );
+}
+
+#[test]
+fn test_delegate_without_cancel_right_cannot_cancel() {
+    let ctx = TestContext::setup();
+    let (stream_id, delegate) = ctx.create_delegated_stream(false);
+
+    ctx.mock_delegate_auth(&delegate, "cancel_stream_as_delegate", stream_id);
+    let result = ctx.client().try_cancel_stream_as_delegate(&stream_id);
+
+    assert!(result.is_err());
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Active
+    );
+}
+
+#[test]
+fn test_delegate_cancel_refunds_sender() {
+    let ctx = TestContext::setup();
+    let (stream_id, delegate) = ctx.create_delegated_stream(true);
+
+    ctx.env.ledger().set_timestamp(400);
+    ctx.mock_delegate_auth(&delegate, "cancel_stream_as_delegate", stream_id);
+    ctx.client().cancel_stream_as_delegate(&stream_id);
+
+    assert_eq!(ctx.token().balance(&ctx.sender), 9600);
+    assert_eq!(ctx.token().balance(&delegate), 0);
+}
+
+#[test]
+fn test_delegate_cannot_use_sender_entrypoints() {
+    let ctx = TestContext::setup();
+    let (stream_id, delegate) = ctx.create_delegated_stream(true);
+
+    ctx.mock_delegate_auth(&delegate, "pause_stream", stream_id);
+    assert!(ctx.client().try_pause_stream(&stream_id).is_err());
+}
+
+#[test]
+fn test_non_delegate_rejected() {
+    let ctx = TestContext::setup();
+    let (stream_id, _) = ctx.create_delegated_stream(true);
+    let stranger = Address::generate(&ctx.env);
+
+    ctx.mock_delegate_auth(&stranger, "pause_stream_as_delegate", stream_id);
+    assert!(ctx.client().try_pause_stream_as_delegate(&stream_id).is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Tests — upgrade_contract
+// ---------------------------------------------------------------------------
+
+/// Smallest module the host accepts as contract code: the WASM header plus a
+/// `contractenvmetav0` section declaring interface version 21.0. It exports
+/// nothing, so it only serves as an upgrade target.
+const EMPTY_CONTRACT_WASM: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic + version
+    0x00, 0x1e, 0x11, // custom section: 30 bytes, 17-byte name
+    b'c', b'o', b'n', b't', b'r', b'a', b'c', b't', b'e', b'n', b'v', b'm', b'e', b't', b'a',
+    b'v', b'0', //
+    0x00, 0x00, 0x00, 0x00, // SC_ENV_META_KIND_INTERFACE_VERSION
+    0x00, 0x00, 0x00, 0x15, 0x00, 0x00, 0x00, 0x00, // protocol 21, pre-release 0
+];
+
+#[test]
+fn test_get_contract_version_starts_at_one() {
+    let ctx = TestContext::setup();
+    assert_eq!(ctx.client().get_contract_version(), 1);
+}
+
+#[test]
+fn test_upgrade_contract_bumps_version_and_records_history() {
+    let ctx = TestContext::setup();
+    let wasm_hash = ctx
+        .env
+        .deployer()
+        .upload_contract_wasm(Bytes::from_slice(&ctx.env, EMPTY_CONTRACT_WASM));
+    ctx.env.ledger().set_timestamp(4242);
+
+    ctx.client().upgrade_contract(&wasm_hash);
+    assert_eq!(ctx.env.auths()[0].0, ctx.admin);
+
+    // The new code exports nothing, so inspect storage directly
+    ctx.env.as_contract(&ctx.contract_id, || {
+        let version: u32 = ctx
+            .env
+            .storage()
+            .instance()
+            .get(&DataKey::ContractVersion)
+            .unwrap();
+        assert_eq!(version, 2);
+
+        let history: Vec<(u32, BytesN<32>, u64)> = ctx
+            .env
+            .storage()
+            .persistent()
+            .get(&DataKey::UpgradeHistory)
+            .unwrap();
+        assert_eq!(history, vec![&ctx.env, (2u32, wasm_hash.clone(), 4242u64)]);
+
+        // Existing state survives the code swap
+        assert!(ctx.env.storage().instance().has(&DataKey::Config));
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tests — recipient freeze
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_freeze_recipient_blocks_then_releases_full_accrual() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(200);
+    ctx.client().freeze_recipient(&ctx.recipient);
+    assert_eq!(ctx.env.auths()[0].0, ctx.admin);
+    assert!(ctx.client().is_recipient_frozen(&ctx.recipient));
+
+    ctx.env.ledger().set_timestamp(600);
+    assert!(ctx.client().try_withdraw(&stream_id).is_err());
+    assert_eq!(ctx.client().withdraw_available(&stream_id), 0);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 0);
+
+    ctx.client().unfreeze_recipient(&ctx.recipient);
+    assert!(!ctx.client().is_recipient_frozen(&ctx.recipient));
+    assert_eq!(ctx.client().withdraw(&stream_id), 600);
+}
+
+#[test]
+fn test_frozen_recipient_blocks_operator_withdrawals() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let operator = Address::generate(&ctx.env);
+    ctx.client().approve_operator(&stream_id, &operator);
+    ctx.client().freeze_recipient(&ctx.recipient);
+
+    ctx.env.ledger().set_timestamp(300);
+    let result = ctx.client().try_withdraw_as_operator(&stream_id, &operator);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_freeze_emits_events() {
+    let ctx = TestContext::setup();
+
+    ctx.client().freeze_recipient(&ctx.recipient);
+    let (_, topics, data) = ctx.env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("frozen"),).into_val(&ctx.env));
+    let frozen: Address = data.into_val(&ctx.env);
+    assert_eq!(frozen, ctx.recipient);
+
+    ctx.client().unfreeze_recipient(&ctx.recipient);
+    let (_, topics, _) = ctx.env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("unfrozen"),).into_val(&ctx.env));
+}
+
+// ---------------------------------------------------------------------------
+// Tests — contract address as a stream party
+// ---------------------------------------------------------------------------
+
+#[test]
+#[should_panic(expected = "address must not be the contract")]
+fn test_create_stream_contract_as_recipient_panics() {
+    let ctx = TestContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+    ctx.client().create_stream(
+        &ctx.sender,
+        &ctx.contract_id,
+        &1000_i128,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
+    );
+}
+
+#[test]
+#[should_panic(expected = "address must not be the contract")]
+fn test_create_stream_contract_as_sender_panics() {
+    let ctx = TestContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+    ctx.client().create_stream(
+        &ctx.contract_id,
+        &ctx.recipient,
+        &1000_i128,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Tests — withdraw_with_withholding
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_withdraw_with_withholding_splits_payout() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let authority = Address::generate(&ctx.env);
+
+    ctx.env.ledger().set_timestamp(333);
+    let (net, tax) = ctx
+        .client()
+        .withdraw_with_withholding(&stream_id, &authority, &2000u32);
+
+    // net = floor(333 * 0.8) = 266, tax gets the remainder
+    assert_eq!((net, tax), (266, 67));
+    assert_eq!(ctx.token().balance(&ctx.recipient), 266);
+    assert_eq!(ctx.token().balance(&authority), 67);
+    assert_eq!(ctx.client().get_stream_state(&stream_id).withdrawn_amount, 333);
+
+    let (_, topics, data) = ctx.env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("withheld"), stream_id).into_val(&ctx.env)
+    );
+    let (_, _, event_authority): (i128, i128, Address) = data.into_val(&ctx.env);
+    assert_eq!(event_authority, authority);
+}
+
+#[test]
+fn test_withdraw_with_withholding_requires_recipient_auth() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client()
+        .withdraw_with_withholding(&stream_id, &Address::generate(&ctx.env), &1000u32);
+
+    assert_eq!(ctx.env.auths()[0].0, ctx.recipient);
+}
+
+#[test]
+#[should_panic(expected = "withholding_bps cannot exceed 5000")]
+fn test_withdraw_with_withholding_above_max_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client()
+        .withdraw_with_withholding(&stream_id, &Address::generate(&ctx.env), &5001u32);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — get_locked
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_get_locked_over_time() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_cliff_stream();
+
+    for (time, locked) in [(0u64, 1000_i128), (499, 1000), (500, 500), (750, 250), (1000, 0)] {
+        ctx.env.ledger().set_timestamp(time);
+        assert_eq!(ctx.client().get_locked(&stream_id), locked, "at t={time}");
+    }
+}
+
+#[test]
+fn test_get_locked_matches_cancel_refund() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().pause_stream(&stream_id);
+
+    ctx.env.ledger().set_timestamp(300);
+    let locked = ctx.client().get_locked(&stream_id);
+    ctx.client().cancel_stream(&stream_id);
+
+    assert_eq!(locked, 700);
+    assert_eq!(ctx.token().balance(&ctx.sender), 9000 + locked);
+    assert_eq!(ctx.client().get_locked(&stream_id), 0);
+}
+
+#[test]
+fn test_get_locked_completed_stream_is_zero() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_dust_stream();
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().withdraw(&stream_id);
+
+    assert_eq!(ctx.client().get_locked(&stream_id), 0);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — compliance hook
+// ---------------------------------------------------------------------------
+
+#[contracttype]
+#[derive(Clone, Copy)]
+enum HookMode {
+    Allow,
+    Deny,
+    Revert,
+}
+
+#[contracttype]
+enum HookKey {
+    Mode,
+    LastAmount,
+}
+
+/// Compliance hook whose answer is configured per test.
+#[contract]
+struct MockHook;
+
+#[contractimpl]
+impl MockHook {
+    pub fn set_mode(env: Env, mode: HookMode) {
+        env.storage().instance().set(&HookKey::Mode, &mode);
+    }
+
+    pub fn is_transfer_allowed(env: Env, _recipient: Address, amount: i128) -> bool {
+        env.storage().instance().set(&HookKey::LastAmount, &amount);
+        match env.storage().instance().get(&HookKey::Mode).unwrap() {
+            HookMode::Allow => true,
+            HookMode::Deny => false,
+            HookMode::Revert => panic!("hook exploded"),
+        }
+    }
+
+    pub fn last_amount(env: Env) -> i128 {
+        env.storage().instance().get(&HookKey::LastAmount).unwrap_or(0)
+    }
+}
+
+impl<'a> TestContext<'a> {
+    fn install_hook(&self, mode: HookMode) -> MockHookClient<'_> {
+        let hook_id = self.env.register_contract(None, MockHook);
+        let hook = MockHookClient::new(&self.env, &hook_id);
+        hook.set_mode(&mode);
+        self.client().set_compliance_hook(&Some(hook_id));
+        hook
+    }
+}
+
+#[test]
+fn test_compliance_hook_not_set_allows_withdraw() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(100);
+    assert_eq!(ctx.client().withdraw(&stream_id), 100);
+}
+
+#[test]
+fn test_compliance_hook_allows_withdraw() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let hook = ctx.install_hook(HookMode::Allow);
+
+    ctx.env.ledger().set_timestamp(100);
+    assert_eq!(ctx.client().withdraw(&stream_id), 100);
+    assert_eq!(hook.last_amount(), 100);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 100);
+}
+
+#[test]
+fn test_compliance_hook_denies_withdraw() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.install_hook(HookMode::Deny);
+
+    ctx.env.ledger().set_timestamp(100);
+    let result = ctx.client().try_withdraw(&stream_id);
+
+    assert_eq!(result, Err(Ok(ContractError::ComplianceDenied.into())));
+    assert_eq!(ctx.token().balance(&ctx.recipient), 0);
+    assert_eq!(ctx.client().get_stream_state(&stream_id).withdrawn_amount, 0);
+}
+
+#[test]
+fn test_reverting_compliance_hook_fails_cleanly() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.install_hook(HookMode::Revert);
+
+    ctx.env.ledger().set_timestamp(100);
+    let result = ctx.client().try_withdraw(&stream_id);
+
+    assert_eq!(result, Err(Ok(ContractError::ComplianceHookFailed.into())));
+}
+
+#[test]
+fn test_removing_compliance_hook_restores_withdrawals() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.install_hook(HookMode::Deny);
+    ctx.client().set_compliance_hook(&None);
+
+    ctx.env.ledger().set_timestamp(100);
+    assert_eq!(ctx.client().withdraw(&stream_id), 100);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — create_stream_from_swap
+// ---------------------------------------------------------------------------
+
+#[contracttype]
+enum DexKey {
+    OutBps,
+}
+
+/// DEX paying out `amount_in * out_bps / 10_000` and ignoring `min_out`, so
+/// the stream contract's own slippage guard is what gets exercised.
+#[contract]
+struct MockDex;
+
+#[contractimpl]
+impl MockDex {
+    pub fn set_out_bps(env: Env, out_bps: i128) {
+        env.storage().instance().set(&DexKey::OutBps, &out_bps);
+    }
+
+    pub fn swap(
+        env: Env,
+        from: Address,
+        to: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        _min_out: i128,
+    ) -> i128 {
+        from.require_auth();
+        let dex = env.current_contract_address();
+        TokenClient::new(&env, &token_in).transfer(&from, &dex, &amount_in);
+        let out_bps: i128 = env.storage().instance().get(&DexKey::OutBps).unwrap();
+        let amount_out = amount_in * out_bps / 10_000;
+        TokenClient::new(&env, &token_out).transfer(&dex, &to, &amount_out);
+        amount_out
+    }
+}
+
+impl<'a> TestContext<'a> {
+    /// Register a DEX holding stream-token liquidity and an input token
+    /// with 5000 units minted to the sender. Returns `(dex, input_token)`.
+    fn setup_dex(&self, out_bps: i128) -> (Address, Address) {
+        let dex_id = self.env.register_contract(None, MockDex);
+        MockDexClient::new(&self.env, &dex_id).set_out_bps(&out_bps);
+        self.sac.mint(&dex_id, &100_000_i128);
+
+        let input_token = self
+            .env
+            .register_stellar_asset_contract_v2(Address::generate(&self.env))
+            .address();
+        StellarAssetClient::new(&self.env, &input_token).mint(&self.sender, &5000_i128);
+        (dex_id, input_token)
+    }
+}
+
+#[test]
+fn test_create_stream_from_swap_funds_with_received_amount() {
+    let ctx = TestContext::setup();
+    let (dex, input_token) = ctx.setup_dex(5_000); // 2 input : 1 stream token
+    ctx.env.ledger().set_timestamp(0);
+
+    let stream_id = ctx.client().create_stream_from_swap(
+        &ctx.sender,
+        &ctx.recipient,
+        &input_token,
+        &2000_i128,
+        &1000_i128,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &dex,
+    );
+
+    let stream = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(stream.deposit_amount, 1000);
+    assert_eq!(stream.sender, ctx.sender);
+    assert_eq!(stream.refund_address, ctx.sender);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 1000);
+    assert_eq!(ctx.token().balance(&ctx.sender), 10_000);
+    assert_eq!(TokenClient::new(&ctx.env, &input_token).balance(&ctx.sender), 3000);
+
+    ctx.env.ledger().set_timestamp(400);
+    assert_eq!(ctx.client().withdraw(&stream_id), 400);
+}
+
+#[test]
+fn test_create_stream_from_swap_keeps_surplus_above_minimum() {
+    let ctx = TestContext::setup();
+    let (dex, input_token) = ctx.setup_dex(6_000);
+    ctx.env.ledger().set_timestamp(0);
+
+    let stream_id = ctx.client().create_stream_from_swap(
+        &ctx.sender,
+        &ctx.recipient,
+        &input_token,
+        &2000_i128,
+        &1000_i128,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &dex,
+    );
+
+    assert_eq!(ctx.client().get_stream_state(&stream_id).deposit_amount, 1200);
+}
+
+#[test]
+fn test_create_stream_from_swap_slippage_reverts_without_allocating_id() {
+    let ctx = TestContext::setup();
+    let (dex, input_token) = ctx.setup_dex(4_000);
+    ctx.env.ledger().set_timestamp(0);
+
+    let result = ctx.client().try_create_stream_from_swap(
+        &ctx.sender,
+        &ctx.recipient,
+        &input_token,
+        &2000_i128,
+        &1000_i128,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &dex,
+    );
+
+    assert!(result.is_err());
+    assert!(!ctx.client().stream_exists(&0));
+    assert_eq!(ctx.create_default_stream(), 0);
+    assert_eq!(TokenClient::new(&ctx.env, &input_token).balance(&ctx.sender), 5000);
+}
+
+#[test]
+#[should_panic(expected = "deposit_amount must cover total streamable amount")]
+fn test_create_stream_from_swap_rejects_underfunded_schedule() {
+    let ctx = TestContext::setup();
+    let (dex, input_token) = ctx.setup_dex(5_000);
+    ctx.env.ledger().set_timestamp(0);
+
+    // Receives 500, but 1/s over 1000s needs 1000
+    ctx.client().create_stream_from_swap(
+        &ctx.sender,
+        &ctx.recipient,
+        &input_token,
+        &1000_i128,
+        &500_i128,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &dex,
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Tests — withdrawal callbacks
+// ---------------------------------------------------------------------------
+
+#[contracttype]
+enum ReceiverKey {
+    Panics,
+    Received,
+    Calls,
+}
+
+/// Sample receiver (e.g. an auto-staking vault) tallying streamed payouts.
+#[contract]
+struct MockReceiver;
+
+#[contractimpl]
+impl MockReceiver {
+    pub fn set_panics(env: Env, panics: bool) {
+        env.storage().instance().set(&ReceiverKey::Panics, &panics);
+    }
+
+    pub fn on_stream_withdraw(env: Env, _stream_id: u64, amount: i128) {
+        if env.storage().instance().get(&ReceiverKey::Panics).unwrap_or(false) {
+            panic!("receiver exploded");
+        }
+        let storage = env.storage().instance();
+        let received: i128 = storage.get(&ReceiverKey::Received).unwrap_or(0);
+        let calls: u32 = storage.get(&ReceiverKey::Calls).unwrap_or(0);
+        storage.set(&ReceiverKey::Received, &(received + amount));
+        storage.set(&ReceiverKey::Calls, &(calls + 1));
+    }
+
+    pub fn received(env: Env) -> i128 {
+        env.storage().instance().get(&ReceiverKey::Received).unwrap_or(0)
+    }
+
+    pub fn calls(env: Env) -> u32 {
+        env.storage().instance().get(&ReceiverKey::Calls).unwrap_or(0)
+    }
+}
+
+impl<'a> TestContext<'a> {
+    fn install_receiver(
+        &self,
+        stream_id: u64,
+        panics: bool,
+        required: bool,
+    ) -> MockReceiverClient<'_> {
+        let receiver_id = self.env.register_contract(None, MockReceiver);
+        let receiver = MockReceiverClient::new(&self.env, &receiver_id);
+        receiver.set_panics(&panics);
+        self.client().set_withdraw_callback(
+            &stream_id,
+            &Some(WithdrawCallback {
+                contract: receiver_id,
+                required,
+            }),
+        );
+        receiver
+    }
+}
+
+#[test]
+fn test_withdraw_without_callback_is_unchanged() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    assert_eq!(ctx.client().get_withdraw_callback(&stream_id), None);
+
+    ctx.env.ledger().set_timestamp(100);
+    assert_eq!(ctx.client().withdraw(&stream_id), 100);
+}
+
+#[test]
+fn test_withdraw_notifies_callback() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let receiver = ctx.install_receiver(stream_id, false, false);
+
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().withdraw(&stream_id);
+    ctx.env.ledger().set_timestamp(250);
+    ctx.client().withdraw(&stream_id);
+
+    assert_eq!(receiver.calls(), 2);
+    assert_eq!(receiver.received(), 250);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 250);
+}
+
+#[test]
+fn test_withdraw_callback_requires_recipient_auth() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let receiver_id = ctx.env.register_contract(None, MockReceiver);
+    let callback = Some(WithdrawCallback {
+        contract: receiver_id,
+        required: false,
+    });
+
+    ctx.env.mock_auths(&[]);
+    let result = ctx.client().try_set_withdraw_callback(&stream_id, &callback);
+    assert!(result.is_err());
+
+    ctx.env.mock_auths(&[MockAuth {
+        address: &ctx.recipient,
+        invoke: &MockAuthInvoke {
+            contract: &ctx.contract_id,
+            fn_name: "set_withdraw_callback",
+            args: (stream_id, callback.clone()).into_val(&ctx.env),
+            sub_invokes: &[],
+        },
+    }]);
+    ctx.client().set_withdraw_callback(&stream_id, &callback);
+    assert_eq!(ctx.client().get_withdraw_callback(&stream_id), callback);
+}
+
+#[test]
+fn test_panicking_optional_callback_does_not_block_withdraw() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let receiver = ctx.install_receiver(stream_id, true, false);
+
+    ctx.env.ledger().set_timestamp(100);
+    assert_eq!(ctx.client().withdraw(&stream_id), 100);
+
+    let events = ctx.env.events().all();
+    let (_, topics, data) = events.last().unwrap();
+    assert_eq!(
+        topics,
+        vec![
+            &ctx.env,
+            symbol_short!("cb_fail").into_val(&ctx.env),
+            stream_id.into_val(&ctx.env),
+        ]
+    );
+    let reported: Address = data.into_val(&ctx.env);
+    assert_eq!(reported, receiver.address);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 100);
+    assert_eq!(ctx.client().get_stream_state(&stream_id).withdrawn_amount, 100);
+}
+
+#[test]
+fn test_panicking_required_callback_reverts_withdraw() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.install_receiver(stream_id, true, true);
+
+    ctx.env.ledger().set_timestamp(100);
+    let result = ctx.client().try_withdraw(&stream_id);
+
+    assert_eq!(result, Err(Ok(ContractError::CallbackFailed.into())));
+    assert_eq!(ctx.token().balance(&ctx.recipient), 0);
+    assert_eq!(ctx.client().get_stream_state(&stream_id).withdrawn_amount, 0);
+
+    // The recipient can always drop the callback to unblock withdrawals
+    ctx.client().set_withdraw_callback(&stream_id, &None);
+    assert_eq!(ctx.client().withdraw(&stream_id), 100);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — milestone streams
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// Milestones unlocking 300, then 700, then the full 1000.
+    fn create_milestone_stream(&self) -> u64 {
+        self.env.ledger().set_timestamp(0);
+        self.client().create_milestone_stream(
+            &self.sender,
+            &self.recipient,
+            &vec![&self.env, 300_i128, 700_i128, 1000_i128],
+        )
+    }
+}
+
+#[test]
+fn test_milestone_stream_locked_until_approved() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_milestone_stream();
+
+    let stream = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(stream.deposit_amount, 1000);
+    assert_eq!(stream.milestones_approved, 0);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 1000);
+
+    // Time alone never unlocks anything
+    ctx.env.ledger().set_timestamp(1_000_000);
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), 0);
+    assert_eq!(ctx.client().withdraw_available(&stream_id), 0);
+}
+
+#[test]
+fn test_milestone_approvals_unlock_stepwise() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_milestone_stream();
+
+    ctx.client().approve_milestone(&stream_id, &0);
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), 300);
+    assert_eq!(ctx.client().withdraw(&stream_id), 300);
+    assert_eq!(ctx.client().withdraw_available(&stream_id), 0);
+
+    ctx.client().approve_milestone(&stream_id, &1);
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), 700);
+    assert_eq!(ctx.client().withdraw(&stream_id), 400);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 700);
+
+    ctx.client().approve_milestone(&stream_id, &2);
+    assert_eq!(ctx.client().withdraw(&stream_id), 300);
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Completed
+    );
+}
+
+#[test]
+fn test_cancel_milestone_stream_refunds_unapproved() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_milestone_stream();
+    ctx.client().approve_milestone(&stream_id, &0);
+
+    ctx.client().cancel_stream(&stream_id);
+
+    assert_eq!(ctx.token().balance(&ctx.sender), 10_000 - 300);
+    assert_eq!(ctx.client().withdraw(&stream_id), 300);
+}
+
+#[test]
+#[should_panic(expected = "milestones must be approved in order")]
+fn test_approve_milestone_out_of_order_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_milestone_stream();
+    ctx.client().approve_milestone(&stream_id, &1);
+}
+
+#[test]
+#[should_panic(expected = "milestone index out of range")]
+fn test_approve_milestone_on_time_based_stream_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().approve_milestone(&stream_id, &0);
+}
+
+#[test]
+fn test_approve_milestone_requires_admin_auth() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_milestone_stream();
+
+    ctx.env.mock_auths(&[MockAuth {
+        address: &ctx.sender,
+        invoke: &MockAuthInvoke {
+            contract: &ctx.contract_id,
+            fn_name: "approve_milestone",
+            args: (stream_id, 0_u32).into_val(&ctx.env),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(ctx.client().try_approve_milestone(&stream_id, &0).is_err());
+
+    ctx.env.mock_auths(&[MockAuth {
+        address: &ctx.admin,
+        invoke: &MockAuthInvoke {
+            contract: &ctx.contract_id,
+            fn_name: "approve_milestone",
+            args: (stream_id, 0_u32).into_val(&ctx.env),
+            sub_invokes: &[],
+        },
+    }]);
+    ctx.client().approve_milestone(&stream_id, &0);
+    assert_eq!(ctx.client().get_stream_state(&stream_id).milestones_approved, 1);
+}
+
+#[test]
+#[should_panic(expected = "milestones must be positive and strictly increasing")]
+fn test_create_milestone_stream_rejects_decreasing_amounts() {
+    let ctx = TestContext::setup();
+    ctx.client().create_milestone_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &vec![&ctx.env, 500_i128, 400_i128],
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Tests — co-cancel
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_co_cancel_executes_agreed_split() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(200);
+    ctx.client().withdraw(&stream_id);
+
+    ctx.client().propose_co_cancel(&stream_id, &500_i128);
+    let proposal = ctx.client().get_co_cancel_proposal(&stream_id).unwrap();
+    assert_eq!(proposal.recipient_share, 500);
+    assert_eq!(proposal.valid_until, 200 + 7 * 24 * 60 * 60);
+
+    ctx.client().confirm_co_cancel(&stream_id);
+
+    // 200 withdrawn earlier + 500 agreed; the remaining 300 goes back
+    assert_eq!(ctx.token().balance(&ctx.recipient), 700);
+    assert_eq!(ctx.token().balance(&ctx.sender), 9_300);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 0);
+
+    let stream = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(stream.status, StreamStatus::Cancelled);
+    assert_eq!(stream.withdrawn_amount, 700);
+    assert_eq!(ctx.client().get_co_cancel_proposal(&stream_id), None);
+
+    // Nothing keeps accruing after the split
+    ctx.env.ledger().set_timestamp(1000);
+    assert_eq!(ctx.client().withdraw_available(&stream_id), 0);
+}
+
+#[test]
+fn test_co_cancel_requires_both_parties() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.env.mock_auths(&[MockAuth {
+        address: &ctx.recipient,
+        invoke: &MockAuthInvoke {
+            contract: &ctx.contract_id,
+            fn_name: "propose_co_cancel",
+            args: (stream_id, 500_i128).into_val(&ctx.env),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(ctx.client().try_propose_co_cancel(&stream_id, &500_i128).is_err());
+
+    ctx.env.mock_all_auths();
+    ctx.client().propose_co_cancel(&stream_id, &500_i128);
+
+    ctx.env.mock_auths(&[MockAuth {
+        address: &ctx.sender,
+        invoke: &MockAuthInvoke {
+            contract: &ctx.contract_id,
+            fn_name: "confirm_co_cancel",
+            args: (stream_id,).into_val(&ctx.env),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(ctx.client().try_confirm_co_cancel(&stream_id).is_err());
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Active
+    );
+}
+
+#[test]
+fn test_reject_co_cancel_keeps_stream_running() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().propose_co_cancel(&stream_id, &100_i128);
+
+    ctx.client().reject_co_cancel(&stream_id);
+
+    assert_eq!(ctx.client().get_co_cancel_proposal(&stream_id), None);
+    assert!(ctx.client().try_confirm_co_cancel(&stream_id).is_err());
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Active
+    );
+}
+
+#[test]
+fn test_expired_co_cancel_cannot_be_confirmed() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().propose_co_cancel(&stream_id, &100_i128);
+    let valid_until = ctx.client().get_co_cancel_proposal(&stream_id).unwrap().valid_until;
+
+    assert!(ctx.client().try_expire_co_cancel(&stream_id).is_err());
+
+    ctx.env.ledger().set_timestamp(valid_until + 1);
+    assert!(ctx.client().try_confirm_co_cancel(&stream_id).is_err());
+
+    ctx.client().expire_co_cancel(&stream_id);
+    assert_eq!(ctx.client().get_co_cancel_proposal(&stream_id), None);
+}
+
+#[test]
+#[should_panic(expected = "recipient share must be within the remaining balance")]
+fn test_propose_co_cancel_share_above_balance_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().propose_co_cancel(&stream_id, &1001_i128);
+}
+
+#[test]
+#[should_panic(expected = "recipient share must be within the remaining balance")]
+fn test_confirm_co_cancel_after_withdrawing_into_share_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().propose_co_cancel(&stream_id, &900_i128);
+
+    ctx.env.ledger().set_timestamp(500);
+    ctx.client().withdraw(&stream_id);
+    ctx.client().confirm_co_cancel(&stream_id);
+}
+
+#[test]
+fn test_confirm_co_cancel_refused_while_disputed() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().propose_co_cancel(&stream_id, &500_i128);
+    ctx.client().raise_dispute(&stream_id, &ctx.sender);
+
+    assert!(ctx.client().try_confirm_co_cancel(&stream_id).is_err());
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 1000);
+
+    ctx.client().resolve_dispute(&stream_id, &true);
+    ctx.client().confirm_co_cancel(&stream_id);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 500);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — ultra-long streams
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_century_stream_accrual_caps_at_deposit_far_past_end() {
+    let ctx = TestContext::setup();
+    let century: u64 = 100 * 365 * 24 * 60 * 60;
+    let deposit = century as i128;
+    ctx.sac.mint(&ctx.sender, &deposit);
+
+    ctx.env.ledger().set_timestamp(0);
+    let stream_id = ctx.client().create_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &deposit,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &century,
+        &Bytes::new(&ctx.env),
+        &None,
+    );
+
+    ctx.env.ledger().set_timestamp(century / 2);
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), deposit / 2);
+
+    // Elapsed time is capped at end_time, not multiplied out from `now`
+    ctx.env.ledger().set_timestamp(u64::MAX);
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), deposit);
+    assert_eq!(ctx.client().withdraw(&stream_id), deposit);
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Completed
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Tests — position receipts
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    fn create_receipt_stream(&self) -> u64 {
+        self.env.ledger().set_timestamp(0);
+        self.client().create_stream_with_receipt(
+            &self.sender,
+            &self.recipient,
+            &1000_i128,
+            &1_i128,
+            &0u64,
+            &0u64,
+            &1000u64,
+            &Bytes::new(&self.env),
+        )
+    }
+
+    fn withdraw_as(&self, caller: &Address, stream_id: u64) -> bool {
+        self.env.mock_auths(&[MockAuth {
+            address: caller,
+            invoke: &MockAuthInvoke {
+                contract: &self.contract_id,
+                fn_name: "withdraw",
+                args: (stream_id,).into_val(&self.env),
+                sub_invokes: &[],
+            },
+        }]);
+        let ok = self.client().try_withdraw(&stream_id).is_ok();
+        self.env.mock_all_auths();
+        ok
+    }
+}
+
+#[test]
+fn test_receipt_minted_to_recipient() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_receipt_stream();
+    assert_eq!(ctx.client().get_receipt_holder(&stream_id), Some(ctx.recipient.clone()));
+
+    let plain_id = ctx.create_default_stream();
+    assert_eq!(ctx.client().get_receipt_holder(&plain_id), None);
+}
+
+#[test]
+fn test_transferred_receipt_moves_withdrawal_rights() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_receipt_stream();
+    let buyer = Address::generate(&ctx.env);
+
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().withdraw(&stream_id);
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().transfer_receipt(&stream_id, &buyer);
+
+    assert_eq!(ctx.client().get_receipt_holder(&stream_id), Some(buyer.clone()));
+    assert_eq!(ctx.client().get_stream_state(&stream_id).recipient, buyer);
+    assert_eq!(ctx.client().get_streams_by_recipient(&ctx.recipient).len(), 0);
+    assert_eq!(ctx.client().get_streams_by_recipient(&buyer), vec![&ctx.env, stream_id]);
+
+    ctx.env.ledger().set_timestamp(600);
+    assert!(!ctx.withdraw_as(&ctx.recipient, stream_id));
+    assert!(ctx.withdraw_as(&buyer, stream_id));
+
+    // Accrual not withdrawn before the transfer (300..400) goes with it
+    assert_eq!(ctx.token().balance(&ctx.recipient), 300);
+    assert_eq!(ctx.token().balance(&buyer), 300);
+}
+
+#[test]
+fn test_cancel_after_receipt_transfer_refunds_sender() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_receipt_stream();
+    let buyer = Address::generate(&ctx.env);
+    ctx.client().transfer_receipt(&stream_id, &buyer);
+
+    ctx.env.ledger().set_timestamp(250);
+    ctx.client().cancel_stream(&stream_id);
+
+    assert_eq!(ctx.token().balance(&ctx.sender), 10_000 - 250);
+    assert_eq!(ctx.client().withdraw(&stream_id), 250);
+    assert_eq!(ctx.token().balance(&buyer), 250);
+}
+
+#[test]
+fn test_receipt_burned_on_completion() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_receipt_stream();
+
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.client().withdraw(&stream_id);
+
+    assert_eq!(ctx.client().get_receipt_holder(&stream_id), None);
+    let result = ctx
+        .client()
+        .try_transfer_receipt(&stream_id, &Address::generate(&ctx.env));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_transfer_receipt_requires_holder_auth() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_receipt_stream();
+    let buyer = Address::generate(&ctx.env);
+
+    ctx.env.mock_auths(&[MockAuth {
+        address: &ctx.sender,
+        invoke: &MockAuthInvoke {
+            contract: &ctx.contract_id,
+            fn_name: "transfer_receipt",
+            args: (stream_id, buyer.clone()).into_val(&ctx.env),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(ctx.client().try_transfer_receipt(&stream_id, &buyer).is_err());
+    assert_eq!(ctx.client().get_receipt_holder(&stream_id), Some(ctx.recipient.clone()));
+}
+
+#[test]
+#[should_panic(expected = "stream has no receipt")]
+fn test_transfer_receipt_without_receipt_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client()
+        .transfer_receipt(&stream_id, &Address::generate(&ctx.env));
+}
+
+#[test]
+fn test_transfer_stream_position_moves_withdrawal_rights() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let buyer = Address::generate(&ctx.env);
+
+    ctx.env.ledger().set_timestamp(200);
+    ctx.client().transfer_stream_position(&stream_id, &buyer);
+
+    assert_eq!(ctx.client().get_stream_state(&stream_id).recipient, buyer);
+    assert_eq!(ctx.client().get_original_recipient(&stream_id), ctx.recipient);
+    assert_eq!(ctx.client().get_streams_by_recipient(&buyer), vec![&ctx.env, stream_id]);
+
+    ctx.env.ledger().set_timestamp(500);
+    assert!(!ctx.withdraw_as(&ctx.recipient, stream_id));
+    assert!(ctx.withdraw_as(&buyer, stream_id));
+    assert_eq!(ctx.token().balance(&buyer), 500);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 0);
+}
+
+#[test]
+fn test_transfer_stream_position_revokes_old_operators() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let operator = Address::generate(&ctx.env);
+    let buyer = Address::generate(&ctx.env);
+    ctx.client().approve_operator(&stream_id, &operator);
+    ctx.client().set_auto_withdraw_cap(&stream_id, &Some(50));
+
+    ctx.client().transfer_stream_position(&stream_id, &buyer);
+
+    assert_eq!(ctx.client().get_auto_withdraw_cap(&stream_id), None);
+    ctx.env.ledger().set_timestamp(500);
+    assert!(ctx
+        .client()
+        .try_withdraw_as_operator(&stream_id, &operator)
+        .is_err());
+
+    // The new owner can approve the same operator afresh.
+    ctx.client().approve_operator(&stream_id, &operator);
+    assert_eq!(ctx.client().withdraw_as_operator(&stream_id, &operator), 500);
+    assert_eq!(ctx.token().balance(&buyer), 500);
+}
+
+#[test]
+fn test_transfer_stream_position_keeps_receipt_in_step() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_receipt_stream();
+    let buyer = Address::generate(&ctx.env);
+    let next_buyer = Address::generate(&ctx.env);
+
+    ctx.client().transfer_stream_position(&stream_id, &buyer);
+    assert_eq!(ctx.client().get_receipt_holder(&stream_id), Some(buyer.clone()));
+
+    ctx.client().transfer_receipt(&stream_id, &next_buyer);
+    assert_eq!(ctx.client().get_stream_state(&stream_id).recipient, next_buyer);
+    assert_eq!(ctx.client().get_original_recipient(&stream_id), ctx.recipient);
+}
+
+#[test]
+fn test_transfer_stream_position_requires_owner_auth() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let buyer = Address::generate(&ctx.env);
+
+    ctx.mock_single_auth(
+        &ctx.sender,
+        "transfer_stream_position",
+        (stream_id, buyer.clone()).into_val(&ctx.env),
+    );
+    assert!(ctx
+        .client()
+        .try_transfer_stream_position(&stream_id, &buyer)
+        .is_err());
+    assert_eq!(ctx.client().get_stream_state(&stream_id).recipient, ctx.recipient);
+}
+
+#[test]
+#[should_panic(expected = "sender and recipient must be different")]
+fn test_transfer_stream_position_to_sender_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().transfer_stream_position(&stream_id, &ctx.sender);
+}
+
+#[test]
+#[should_panic(expected = "sender and recipient must be different")]
+fn test_transfer_receipt_to_sender_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_receipt_stream();
+    ctx.client().transfer_receipt(&stream_id, &ctx.sender);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — withdrawal schedules
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// Default 1000s stream claimable at t=250, 500, 750 and 1000.
+    fn create_scheduled_stream(&self) -> u64 {
+        self.env.ledger().set_timestamp(0);
+        self.client().create_stream_with_schedule(
+            &self.sender,
+            &self.recipient,
+            &1000_i128,
+            &1_i128,
+            &0u64,
+            &0u64,
+            &1000u64,
+            &Bytes::new(&self.env),
+            &vec![&self.env, 250u64, 500u64, 750u64, 1000u64],
+        )
+    }
+}
+
+#[test]
+#[should_panic(expected = "not yet scheduled")]
+fn test_scheduled_withdraw_before_first_date_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_scheduled_stream();
+    ctx.env.ledger().set_timestamp(249);
+    ctx.client().withdraw(&stream_id);
+}
+
+#[test]
+fn test_scheduled_withdraw_follows_claim_dates() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_scheduled_stream();
+    assert_eq!(ctx.client().get_next_withdraw_time(&stream_id), Some(250));
+
+    ctx.env.ledger().set_timestamp(260);
+    assert_eq!(ctx.client().withdraw(&stream_id), 260);
+    assert_eq!(ctx.client().get_next_withdraw_time(&stream_id), Some(500));
+
+    ctx.env.ledger().set_timestamp(400);
+    assert_eq!(ctx.client().withdraw_available(&stream_id), 0);
+    assert!(ctx.client().try_withdraw(&stream_id).is_err());
+
+    // Skipping a date lets everything accrued so far be claimed at once
+    ctx.env.ledger().set_timestamp(800);
+    assert_eq!(ctx.client().withdraw(&stream_id), 540);
+    assert_eq!(ctx.client().get_next_withdraw_time(&stream_id), Some(1000));
+
+    ctx.env.ledger().set_timestamp(1000);
+    assert_eq!(ctx.client().withdraw(&stream_id), 200);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 1000);
+}
+
+#[test]
+fn test_unscheduled_stream_has_no_next_withdraw_time() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    assert_eq!(ctx.client().get_next_withdraw_time(&stream_id), None);
+}
+
+#[test]
+#[should_panic(expected = "withdraw_schedule must be strictly increasing")]
+fn test_create_stream_with_unsorted_schedule_panics() {
+    let ctx = TestContext::setup();
+    ctx.client().create_stream_with_schedule(
+        &ctx.sender,
+        &ctx.recipient,
+        &1000_i128,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &Bytes::new(&ctx.env),
+        &vec![&ctx.env, 500u64, 250u64],
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Tests — pause/resume events
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_pause_and_resume_events_carry_timestamp() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().pause_stream(&stream_id);
+    let (_, topics, data) = ctx.env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("paused"), stream_id).into_val(&ctx.env)
+    );
+    let paused_at: u64 = data.into_val(&ctx.env);
+    assert_eq!(paused_at, 300);
+
+    ctx.env.ledger().set_timestamp(450);
+    ctx.client().resume_stream(&stream_id);
+    let (_, topics, data) = ctx.env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("resumed"), stream_id).into_val(&ctx.env)
+    );
+    let resumed_at: u64 = data.into_val(&ctx.env);
+    assert_eq!(resumed_at - paused_at, 150);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — allowance-funded streams
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// Register a processor for the sender and grant the contract `budget`.
+    fn setup_processor(&self, budget: i128) -> Address {
+        let processor = Address::generate(&self.env);
+        self.client()
+            .set_funding_processor(&self.sender, &Some(processor.clone()));
+        self.approve_contract(budget);
+        processor
+    }
+
+    fn try_create_from_allowance(
+        &self,
+        processor: &Address,
+        deposit: i128,
+    ) -> Result<
+        Result<u64, soroban_sdk::Error>,
+        Result<soroban_sdk::Error, soroban_sdk::InvokeError>,
+    > {
+        self.env.ledger().set_timestamp(0);
+        self.client().try_create_stream_from_allowance(
+            processor,
+            &self.sender,
+            &self.recipient,
+            &deposit,
+            &1_i128,
+            &0u64,
+            &0u64,
+            &(deposit as u64),
+            &Bytes::new(&self.env),
+        )
+    }
+}
+
+#[test]
+fn test_streams_from_allowance_draw_down_budget() {
+    let ctx = TestContext::setup();
+    let processor = ctx.setup_processor(2500);
+
+    let first = ctx.try_create_from_allowance(&processor, 1000).unwrap().unwrap();
+    assert_eq!(ctx.token().allowance(&ctx.sender, &ctx.contract_id), 1500);
+    let second = ctx.try_create_from_allowance(&processor, 1200).unwrap().unwrap();
+    assert_eq!(ctx.token().allowance(&ctx.sender, &ctx.contract_id), 300);
+
+    assert_eq!(ctx.client().get_stream_state(&first).sender, ctx.sender);
+    assert_eq!(ctx.client().get_stream_state(&second).deposit_amount, 1200);
+    assert_eq!(ctx.token().balance(&ctx.sender), 10_000 - 2200);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 2200);
+}
+
+#[test]
+fn test_stream_from_allowance_over_budget_is_typed_error() {
+    let ctx = TestContext::setup();
+    let processor = ctx.setup_processor(500);
+
+    let result = ctx.try_create_from_allowance(&processor, 1000);
+
+    assert_eq!(
+        result,
+        Err(Ok(ContractError::InsufficientAllowance.into()))
+    );
+    assert_eq!(ctx.token().allowance(&ctx.sender, &ctx.contract_id), 500);
+    assert!(!ctx.client().stream_exists(&0));
+}
+
+#[test]
+fn test_stream_from_allowance_requires_only_processor_auth() {
+    let ctx = TestContext::setup();
+    let processor = ctx.setup_processor(1000);
+
+    ctx.env.mock_auths(&[MockAuth {
+        address: &processor,
+        invoke: &MockAuthInvoke {
+            contract: &ctx.contract_id,
+            fn_name: "create_stream_from_allowance",
+            args: (
+                processor.clone(),
+                ctx.sender.clone(),
+                ctx.recipient.clone(),
+                1000_i128,
+                1_i128,
+                0u64,
+                0u64,
+                1000u64,
+                Bytes::new(&ctx.env),
+            )
+                .into_val(&ctx.env),
+            sub_invokes: &[],
+        },
+    }]);
+    let stream_id = ctx.try_create_from_allowance(&processor, 1000).unwrap().unwrap();
+    assert_eq!(ctx.client().get_stream_state(&stream_id).deposit_amount, 1000);
+}
+
+#[test]
+fn test_stream_from_allowance_rejects_unapproved_processor() {
+    let ctx = TestContext::setup();
+    ctx.setup_processor(1000);
+    let stranger = Address::generate(&ctx.env);
+
+    assert!(ctx.try_create_from_allowance(&stranger, 1000).is_err());
+
+    // Revoking the processor shuts it out as well
+    let processor = ctx.setup_processor(1000);
+    ctx.client().set_funding_processor(&ctx.sender, &None);
+    assert!(ctx.try_create_from_allowance(&processor, 1000).is_err());
+    assert_eq!(ctx.token().balance(&ctx.sender), 10_000);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — accrual invariants
+// ---------------------------------------------------------------------------
+
+/// Every (start, cliff, end, rate, deposit) combination of a parameter grid
+/// covering zero, tiny, typical and extreme values.
+fn accrual_schedules() -> std::vec::Vec<(u64, u64, u64, i128, i128)> {
+    let starts = [0u64, 1, 1_000, u64::MAX / 2];
+    let durations = [1u64, 7, 1_000, 3_153_600_000];
+    let rates = [0_i128, 1, 3, 1_000_000, i128::MAX / 2, i128::MAX];
+    let deposits = [0_i128, 1, 1_000, 1_000_000_000_000, i128::MAX];
+
+    let mut schedules = std::vec::Vec::new();
+    for &start in &starts {
+        for &duration in &durations {
+            let end = start + duration;
+            for cliff in [start, start + duration / 2, end] {
+                for &rate in &rates {
+                    for &deposit in &deposits {
+                        schedules.push((start, cliff, end, rate, deposit));
+                    }
+                }
+            }
+        }
+    }
+    schedules
+}
+
+/// Probe times around every boundary of the schedule, in increasing order.
+fn accrual_probe_times(start: u64, cliff: u64, end: u64) -> std::vec::Vec<u64> {
+    let mut times = std::vec![
+        0,
+        start.saturating_sub(1),
+        start,
+        start + 1,
+        cliff.saturating_sub(1),
+        cliff,
+        cliff.saturating_add(1),
+        start + (end - start) / 3,
+        end - 1,
+        end,
+        end.saturating_add(1),
+        end.saturating_add(1_000_000),
+        u64::MAX,
+    ];
+    times.sort_unstable();
+    times.dedup();
+    times
+}
+
+#[test]
+fn test_accrual_is_non_decreasing_over_time() {
+    for (start, cliff, end, rate, deposit) in accrual_schedules() {
+        let mut previous = 0;
+        for now in accrual_probe_times(start, cliff, end) {
+            let accrued = calculate_accrued_amount(start, cliff, end, rate, deposit, now);
+            assert!(
+                accrued >= previous,
+                "accrual decreased at t={now} for {:?}",
+                (start, cliff, end, rate, deposit)
+            );
+            previous = accrued;
+        }
+    }
+}
+
+#[test]
+fn test_accrual_after_end_equals_accrual_at_end() {
+    for (start, cliff, end, rate, deposit) in accrual_schedules() {
+        let at_end = calculate_accrued_amount(start, cliff, end, rate, deposit, end);
+        for now in [end + 1, end.saturating_add(1_000_000), u64::MAX] {
+            assert_eq!(
+                calculate_accrued_amount(start, cliff, end, rate, deposit, now),
+                at_end,
+                "accrual moved past end_time at t={now} for {:?}",
+                (start, cliff, end, rate, deposit)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_accrual_stays_within_deposit_bounds() {
+    for (start, cliff, end, rate, deposit) in accrual_schedules() {
+        for now in accrual_probe_times(start, cliff, end) {
+            let accrued = calculate_accrued_amount(start, cliff, end, rate, deposit, now);
+            assert!(
+                (0..=deposit).contains(&accrued),
+                "accrued {accrued} out of [0, {deposit}] at t={now} for {:?}",
+                (start, cliff, end, rate)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_accrual_is_deterministic() {
+    for (start, cliff, end, rate, deposit) in accrual_schedules() {
+        for now in accrual_probe_times(start, cliff, end) {
+            let first = calculate_accrued_amount(start, cliff, end, rate, deposit, now);
+            let second = calculate_accrued_amount(start, cliff, end, rate, deposit, now);
+            assert_eq!(first, second);
+        }
+    }
+}
+
+#[test]
+fn test_returns_zero_before_start_time() {
+    // Cliff at 0 so only the start-time guard applies
+    assert_eq!(calculate_accrued_amount(100, 0, 200, 5, 500, 0), 0);
+    assert_eq!(calculate_accrued_amount(100, 0, 200, 5, 500, 99), 0);
+    assert_eq!(calculate_accrued_amount(100, 0, 200, 5, 500, 100), 0);
+    assert_eq!(calculate_accrued_amount(100, 0, 200, 5, 500, 101), 5);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — auto-withdraw cap
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_auto_withdraw_cap_limits_each_operator_call() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let keeper = Address::generate(&ctx.env);
+    ctx.client().approve_operator(&stream_id, &keeper);
+    ctx.client().set_auto_withdraw_cap(&stream_id, &Some(100_i128));
+    assert_eq!(ctx.client().get_auto_withdraw_cap(&stream_id), Some(100));
+
+    ctx.env.ledger().set_timestamp(500);
+    assert_eq!(ctx.client().withdraw_as_operator(&stream_id, &keeper), 100);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 100);
+
+    // Repeated calls drain the stream 100 at a time
+    ctx.env.ledger().set_timestamp(1000);
+    for _ in 0..9 {
+        assert_eq!(ctx.client().withdraw_as_operator(&stream_id, &keeper), 100);
+    }
+    assert_eq!(ctx.token().balance(&ctx.recipient), 1000);
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Completed
+    );
+}
+
+#[test]
+fn test_auto_withdraw_cap_does_not_limit_recipient_withdraw() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().set_auto_withdraw_cap(&stream_id, &Some(100_i128));
+
+    ctx.env.ledger().set_timestamp(500);
+    assert_eq!(ctx.client().withdraw(&stream_id), 500);
+}
+
+#[test]
+fn test_set_auto_withdraw_cap_requires_recipient_auth() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.mock_single_auth(
+        &ctx.sender,
+        "set_auto_withdraw_cap",
+        (stream_id, Some(100_i128)).into_val(&ctx.env),
+    );
+    assert!(ctx
+        .client()
+        .try_set_auto_withdraw_cap(&stream_id, &Some(100_i128))
+        .is_err());
+    assert_eq!(ctx.client().get_auto_withdraw_cap(&stream_id), None);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — sponsored streams
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// Default 1000s stream paid for by a freshly minted funder.
+    fn create_sponsored_stream(&self, refund_to_funder: bool) -> (u64, Address) {
+        let funder = Address::generate(&self.env);
+        self.sac.mint(&funder, &5000_i128);
+        self.env.ledger().set_timestamp(0);
+        let stream_id = self.client().create_sponsored_stream(
+            &funder,
+            &self.sender,
+            &self.recipient,
+            &1000_i128,
+            &1_i128,
+            &0u64,
+            &0u64,
+            &1000u64,
+            &Bytes::new(&self.env),
+            &refund_to_funder,
+        );
+        (stream_id, funder)
+    }
+}
+
+#[test]
+fn test_sponsored_stream_debits_funder_and_records_both() {
+    let ctx = TestContext::setup();
+    let (stream_id, funder) = ctx.create_sponsored_stream(true);
+
+    let (_, topics, data) = ctx.env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("sponsored"), stream_id).into_val(&ctx.env)
+    );
+    let (event_funder, event_sender): (Address, Address) = data.into_val(&ctx.env);
+    assert_eq!(event_funder, funder);
+    assert_eq!(event_sender, ctx.sender);
+
+    assert_eq!(ctx.token().balance(&funder), 4000);
+    assert_eq!(ctx.token().balance(&ctx.sender), 10_000);
+    let stream = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(stream.sender, ctx.sender);
+    assert_eq!(stream.refund_address, funder);
+}
+
+#[test]
+fn test_sponsored_stream_cancel_refunds_funder_when_flagged() {
+    let ctx = TestContext::setup();
+    let (stream_id, funder) = ctx.create_sponsored_stream(true);
+
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().cancel_stream(&stream_id);
+
+    assert_eq!(ctx.token().balance(&funder), 4600);
+    assert_eq!(ctx.token().balance(&ctx.sender), 10_000);
+}
+
+#[test]
+fn test_sponsored_stream_cancel_refunds_sender_by_default() {
+    let ctx = TestContext::setup();
+    let (stream_id, funder) = ctx.create_sponsored_stream(false);
+
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().cancel_stream(&stream_id);
+
+    assert_eq!(ctx.token().balance(&funder), 4000);
+    assert_eq!(ctx.token().balance(&ctx.sender), 10_600);
+}
+
+#[test]
+fn test_sponsored_stream_funder_has_no_control() {
+    let ctx = TestContext::setup();
+    let (stream_id, funder) = ctx.create_sponsored_stream(true);
+
+    let args = (stream_id,).into_val(&ctx.env);
+    ctx.mock_single_auth(&funder, "pause_stream", args);
+    assert!(ctx.client().try_pause_stream(&stream_id).is_err());
+
+    let args = (stream_id,).into_val(&ctx.env);
+    ctx.mock_single_auth(&funder, "cancel_stream", args);
+    assert!(ctx.client().try_cancel_stream(&stream_id).is_err());
+
+    let args = (stream_id,).into_val(&ctx.env);
+    ctx.mock_single_auth(&ctx.sender, "pause_stream", args);
+    ctx.client().pause_stream(&stream_id);
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Paused
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Tests — precompute_vesting_schedule
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_vesting_schedule_evenly_spaced() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    let schedule = ctx.client().precompute_vesting_schedule(&stream_id, &5);
+
+    assert_eq!(
+        schedule,
+        vec![
+            &ctx.env,
+            (0u64, 0_i128),
+            (250, 250),
+            (500, 500),
+            (750, 750),
+            (1000, 1000),
+        ]
+    );
+}
+
+#[test]
+fn test_vesting_schedule_includes_cliff() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_cliff_stream();
+
+    let schedule = ctx.client().precompute_vesting_schedule(&stream_id, &4);
+
+    assert_eq!(
+        schedule,
+        vec![
+            &ctx.env,
+            (0u64, 0_i128),
+            (333, 0),
+            (500, 500),
+            (666, 666),
+            (1000, 1000),
+        ]
+    );
+}
+
+#[test]
+fn test_vesting_schedule_sample_count_is_clamped() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    assert_eq!(ctx.client().precompute_vesting_schedule(&stream_id, &500).len(), 100);
+    assert_eq!(
+        ctx.client().precompute_vesting_schedule(&stream_id, &0),
+        vec![&ctx.env, (0u64, 0_i128), (1000, 1000)]
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Tests — time_remaining
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_time_remaining_counts_down_to_end() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(400);
+    assert_eq!(ctx.client().time_remaining(&stream_id), 600);
+
+    ctx.client().pause_stream(&stream_id);
+    assert_eq!(ctx.client().time_remaining(&stream_id), 600);
+
+    ctx.env.ledger().set_timestamp(5000);
+    assert_eq!(ctx.client().time_remaining(&stream_id), 0);
+}
+
+#[test]
+fn test_time_remaining_zero_for_cancelled_stream() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().cancel_stream(&stream_id);
+    assert_eq!(ctx.client().time_remaining(&stream_id), 0);
+}
+
+#[test]
+fn test_rate_display_scales_rate_per_second() {
+    let ctx = TestContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+    let stream_id = ctx.client().create_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &3100_i128,
+        &31_i128,
+        &0u64,
+        &0u64,
+        &100u64,
+        &Bytes::new(&ctx.env),
+        &None,
+    );
+
+    assert_eq!(
+        ctx.client().get_stream_annual_rate_display(&stream_id),
+        31 * 31_536_000
+    );
+    assert_eq!(
+        ctx.client().get_stream_monthly_rate_display(&stream_id),
+        31 * 2_628_000
+    );
+}
+
+#[test]
+fn test_rate_display_saturates_on_overflow() {
+    let ctx = TestContext::setup();
+    ctx.sac.mint(&ctx.sender, &(i128::MAX - 10_000_i128));
+    let stream_id = ctx.create_max_rate_stream();
+
+    assert_eq!(
+        ctx.client().get_stream_annual_rate_display(&stream_id),
+        i128::MAX
+    );
+    assert_eq!(
+        ctx.client().get_stream_monthly_rate_display(&stream_id),
+        i128::MAX
+    );
+}
+
+#[test]
+fn test_time_to_fully_vest_is_end_time_until_vested() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_cliff_stream();
+
+    ctx.env.ledger().set_timestamp(100);
+    assert_eq!(ctx.client().get_time_to_fully_vest(&stream_id), Some(1000));
+    ctx.env.ledger().set_timestamp(999);
+    assert_eq!(ctx.client().get_time_to_fully_vest(&stream_id), Some(1000));
+    ctx.env.ledger().set_timestamp(1000);
+    assert_eq!(ctx.client().get_time_to_fully_vest(&stream_id), None);
+}
+
+#[test]
+fn test_time_to_fully_vest_none_once_cancelled() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().cancel_stream(&stream_id);
+    assert_eq!(ctx.client().get_time_to_fully_vest(&stream_id), None);
+}
+
+#[test]
+fn test_time_to_fully_vest_none_for_milestone_stream() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_milestone_stream();
+    assert_eq!(ctx.client().get_time_to_fully_vest(&stream_id), None);
+
+    ctx.client().approve_milestone(&stream_id, &0);
+    assert_eq!(ctx.client().get_time_to_fully_vest(&stream_id), None);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — timelocked admin cancel
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_admin_cancel_executes_after_delay() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    assert_eq!(ctx.client().get_config().admin_cancel_delay, 86_400);
+
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().admin_request_cancel(&stream_id);
+    let (_, topics, data) = ctx.env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("cncl_req"), stream_id).into_val(&ctx.env)
+    );
+    let (_, _, executable_at): (Address, Address, u64) = data.into_val(&ctx.env);
+    assert_eq!(executable_at, 86_500);
+    assert_eq!(ctx.client().get_pending_admin_cancel(&stream_id), Some(86_500));
+
+    // Too early
+    ctx.env.ledger().set_timestamp(86_499);
+    let result = ctx.client().try_admin_execute_cancel(&stream_id);
+    assert!(result.is_err());
+
+    // The recipient can still withdraw during the window
+    ctx.env.ledger().set_timestamp(500);
+    assert_eq!(ctx.client().withdraw(&stream_id), 500);
+
+    ctx.env.ledger().set_timestamp(86_500);
+    ctx.client().admin_execute_cancel(&stream_id);
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.status, StreamStatus::Cancelled);
+    assert_eq!(ctx.client().get_pending_admin_cancel(&stream_id), None);
+}
+
+#[test]
+#[should_panic(expected = "admin cancel delay has not elapsed")]
+fn test_admin_execute_cancel_before_delay_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().admin_request_cancel(&stream_id);
+
+    ctx.env.ledger().set_timestamp(3600);
+    ctx.client().admin_execute_cancel(&stream_id);
+}
+
+#[test]
+fn test_admin_cancel_delay_is_configurable() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().set_admin_cancel_delay(&60);
+
+    ctx.client().admin_request_cancel(&stream_id);
+    ctx.env.ledger().set_timestamp(60);
+    ctx.client().admin_execute_cancel(&stream_id);
+
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Cancelled
+    );
+}
+
+#[test]
+fn test_sender_cancel_clears_pending_admin_cancel() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().admin_request_cancel(&stream_id);
+
+    // The sender may still cancel immediately
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().cancel_stream(&stream_id);
+    assert_eq!(ctx.client().get_pending_admin_cancel(&stream_id), None);
+
+    ctx.env.ledger().set_timestamp(100_000);
+    let result = ctx.client().try_admin_execute_cancel(&stream_id);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_completion_clears_pending_admin_cancel() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().admin_request_cancel(&stream_id);
+
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.client().withdraw(&stream_id);
+
+    assert_eq!(ctx.client().get_pending_admin_cancel(&stream_id), None);
+    ctx.env.ledger().set_timestamp(100_000);
+    assert!(ctx.client().try_admin_execute_cancel(&stream_id).is_err());
+}
+
+#[test]
+fn test_admin_request_cancel_requires_admin_auth() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    let args = (stream_id,).into_val(&ctx.env);
+    ctx.mock_single_auth(&ctx.sender, "admin_request_cancel", args);
+    assert!(ctx.client().try_admin_request_cancel(&stream_id).is_err());
+    assert_eq!(ctx.client().get_pending_admin_cancel(&stream_id), None);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — bulk admin cancel
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_cancel_streams_as_admin_partial_success() {
+    let ctx = TestContext::setup();
+    let ids: std::vec::Vec<u64> = (0..5).map(|_| ctx.create_default_stream()).collect();
+
+    ctx.env.ledger().set_timestamp(100);
+    for id in &ids[..3] {
+        ctx.client().admin_request_cancel(id);
+    }
+    ctx.client().cancel_stream(&ids[2]);
+    ctx.env.ledger().set_timestamp(200);
+    ctx.client().admin_request_cancel(&ids[3]);
+
+    ctx.env.ledger().set_timestamp(86_400 + 100);
+    let results = ctx
+        .client()
+        .cancel_streams_as_admin(&vec![&ctx.env, ids[0], ids[1], ids[2], ids[3], ids[4], 99]);
+
+    // Both streams fully accrued during the notice period: nothing to refund
+    assert_eq!(
+        results,
+        vec![
+            &ctx.env,
+            CancelResult::Cancelled(0),
+            CancelResult::Cancelled(0),
+            CancelResult::Skipped(CancelSkipReason::NotCancellable),
+            CancelResult::Skipped(CancelSkipReason::DelayPending),
+            CancelResult::Skipped(CancelSkipReason::NotRequested),
+            CancelResult::Skipped(CancelSkipReason::NotFound),
+        ]
+    );
+    let status = |id: u64| ctx.client().get_stream_state(&id).status;
+    assert_eq!(status(ids[0]), StreamStatus::Cancelled);
+    assert_eq!(status(ids[1]), StreamStatus::Cancelled);
+    assert_eq!(status(ids[3]), StreamStatus::Active);
+    assert_eq!(status(ids[4]), StreamStatus::Active);
+}
+
+#[test]
+fn test_cancel_streams_as_admin_refunds_and_emits_per_stream() {
+    let ctx = TestContext::setup();
+    ctx.client().set_admin_cancel_delay(&50);
+    let first = ctx.create_default_stream();
+    let second = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().admin_request_cancel(&first);
+    ctx.client().admin_request_cancel(&second);
+    ctx.env.ledger().set_timestamp(250);
+    let results = ctx
+        .client()
+        .cancel_streams_as_admin(&vec![&ctx.env, first, second]);
+
+    assert_eq!(
+        results,
+        vec![&ctx.env, CancelResult::Cancelled(750), CancelResult::Cancelled(750)]
+    );
+    assert_eq!(ctx.token().balance(&ctx.sender), 10_000 - 2000 + 1500);
+    let cancelled_events = ctx
+        .env
+        .events()
+        .all()
+        .iter()
+        .filter(|(_, topics, _)| {
+            [first, second].iter().any(|id| {
+                *topics == (symbol_short!("cancelled"), *id).into_val(&ctx.env)
+            })
+        })
+        .count();
+    assert_eq!(cancelled_events, 2);
+}
+
+#[test]
+fn test_cancel_streams_as_admin_requires_admin_auth() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().set_admin_cancel_delay(&0);
+    ctx.client().admin_request_cancel(&stream_id);
+
+    let ids = vec![&ctx.env, stream_id];
+    ctx.mock_single_auth(&ctx.sender, "cancel_streams_as_admin", (ids.clone(),).into_val(&ctx.env));
+    assert!(ctx.client().try_cancel_streams_as_admin(&ids).is_err());
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Active
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Tests — settling cancelled streams
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_cancelled_stream_completes_once_fully_withdrawn() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().cancel_stream(&stream_id);
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.status, StreamStatus::Cancelled);
+    assert_eq!(state.deposit_amount, 300);
+
+    ctx.env.ledger().set_timestamp(900);
+    assert_eq!(ctx.client().withdraw(&stream_id), 300);
+
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.withdrawn_amount, state.deposit_amount);
+    assert_eq!(state.status, StreamStatus::Completed);
+}
+
+#[test]
+fn test_cancelled_stream_stops_accruing() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let other_id = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().withdraw(&stream_id);
+    ctx.client().cancel_stream(&stream_id);
+
+    // Nothing more is claimable later, so other streams' funds stay put
+    ctx.env.ledger().set_timestamp(900);
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), 300);
+    assert_eq!(ctx.client().withdraw_available(&stream_id), 0);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 1000);
+    assert_eq!(ctx.client().withdraw(&other_id), 900);
+}
+
+#[test]
+fn test_stream_cancelled_after_full_accrual_completes_on_withdraw() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.client().cancel_stream(&stream_id);
+    assert_eq!(ctx.client().withdraw(&stream_id), 1000);
+
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Completed
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Tests — minimum deposit
+// ---------------------------------------------------------------------------
+
+#[test]
+#[should_panic(expected = "deposit below minimum")]
+fn test_create_stream_below_min_deposit_panics() {
+    let ctx = TestContext::setup();
+    ctx.client().set_min_deposit(&Some(1001_i128));
+    ctx.create_default_stream();
+}
+
+#[test]
+fn test_create_stream_at_min_deposit_succeeds() {
+    let ctx = TestContext::setup();
+    ctx.client().set_min_deposit(&Some(1000_i128));
+    assert_eq!(ctx.client().get_min_deposit(), Some(1000));
+
+    let stream_id = ctx.create_default_stream();
+    assert_eq!(ctx.client().get_stream_state(&stream_id).deposit_amount, 1000);
+}
+
+#[test]
+fn test_min_deposit_unset_allows_any_deposit() {
+    let ctx = TestContext::setup();
+    ctx.client().set_min_deposit(&Some(5000_i128));
+    ctx.client().set_min_deposit(&None);
+
+    ctx.env.ledger().set_timestamp(0);
+    ctx.client().create_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &1_i128,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &1u64,
+        &Bytes::new(&ctx.env),
+        &None,
+    );
+}
+
+#[test]
+fn test_set_min_deposit_requires_admin_auth() {
+    let ctx = TestContext::setup();
+    let args = (Some(1000_i128),).into_val(&ctx.env);
+    ctx.mock_single_auth(&ctx.sender, "set_min_deposit", args);
+    assert!(ctx.client().try_set_min_deposit(&Some(1000_i128)).is_err());
+    assert_eq!(ctx.client().get_min_deposit(), None);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — minimum cliff duration
+// ---------------------------------------------------------------------------
+
+#[test]
+#[should_panic(expected = "cliff below required minimum")]
+fn test_create_stream_below_min_cliff_panics() {
+    let ctx = TestContext::setup();
+    ctx.client().set_min_cliff_duration(&Some(501));
+    ctx.create_cliff_stream();
+}
+
+#[test]
+fn test_create_stream_at_min_cliff_succeeds() {
+    let ctx = TestContext::setup();
+    ctx.client().set_min_cliff_duration(&Some(500));
+    assert_eq!(ctx.client().get_min_cliff_duration(), Some(500));
+
+    let stream_id = ctx.create_cliff_stream();
+    assert_eq!(ctx.client().get_stream_state(&stream_id).cliff_time, 500);
+}
+
+#[test]
+fn test_min_cliff_unset_allows_no_cliff() {
+    let ctx = TestContext::setup();
+    ctx.client().set_min_cliff_duration(&Some(500));
+    ctx.client().set_min_cliff_duration(&None);
+
+    ctx.create_default_stream();
+}
+
+#[test]
+fn test_min_cliff_exempts_milestone_streams() {
+    let ctx = TestContext::setup();
+    ctx.client().set_min_cliff_duration(&Some(500));
+    ctx.create_milestone_stream();
+}
+
+#[test]
+#[should_panic(expected = "cliff below required minimum")]
+fn test_lower_cliff_below_min_cliff_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_cliff_stream();
+    ctx.client().set_min_cliff_duration(&Some(300));
+    ctx.client().lower_cliff(&stream_id, &299);
+}
+
+#[test]
+fn test_set_min_cliff_duration_requires_admin_auth() {
+    let ctx = TestContext::setup();
+    let args = (Some(500u64),).into_val(&ctx.env);
+    ctx.mock_single_auth(&ctx.sender, "set_min_cliff_duration", args);
+    assert!(ctx.client().try_set_min_cliff_duration(&Some(500)).is_err());
+    assert_eq!(ctx.client().get_min_cliff_duration(), None);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — governance
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// Configure 2-of-3 governance with a 1000s proposal TTL.
+    fn setup_governance(&self) -> [Address; 3] {
+        let approvers = [
+            Address::generate(&self.env),
+            Address::generate(&self.env),
+            Address::generate(&self.env),
+        ];
+        let set = vec![
+            &self.env,
+            approvers[0].clone(),
+            approvers[1].clone(),
+            approvers[2].clone(),
+        ];
+        self.client().set_governance(&set, &2, &1000);
+        approvers
+    }
+
+    fn approve_as(&self, approver: &Address, proposal_id: u64) -> bool {
+        let args = (approver.clone(), proposal_id).into_val(&self.env);
+        self.mock_single_auth(approver, "approve", args);
+        self.client().try_approve(approver, &proposal_id).is_ok()
+    }
+}
+
+#[test]
+fn test_governance_proposal_executes_at_threshold() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let [a, b, _] = ctx.setup_governance();
+    let collector = Address::generate(&ctx.env);
+
+    let action = GovAction::SetWithdrawalFee(100, collector.clone());
+    let args = (a.clone(), action.clone()).into_val(&ctx.env);
+    ctx.mock_single_auth(&a, "propose", args);
+    let proposal_id = ctx.client().propose(&a, &action);
+
+    assert!(ctx.approve_as(&a, proposal_id));
+    ctx.env.mock_auths(&[]);
+    let result = ctx.client().try_execute_proposal(&proposal_id);
+    assert!(result.is_err());
+
+    assert!(ctx.approve_as(&b, proposal_id));
+    ctx.env.mock_auths(&[]);
+    ctx.client().execute_proposal(&proposal_id);
+    assert!(ctx.client().get_proposal(&proposal_id).executed);
+
+    ctx.env.mock_all_auths();
+    ctx.env.ledger().set_timestamp(100);
+    assert_eq!(ctx.client().withdraw(&stream_id), 99);
+    assert_eq!(ctx.token().balance(&collector), 1);
+}
+
+#[test]
+fn test_governance_rejects_duplicate_approval() {
+    let ctx = TestContext::setup();
+    let [a, _, _] = ctx.setup_governance();
+    let proposal_id = ctx.client().propose(&a, &GovAction::CancelStream(0));
+
+    assert!(ctx.approve_as(&a, proposal_id));
+    assert!(!ctx.approve_as(&a, proposal_id));
+
+    ctx.env.mock_all_auths();
+    assert_eq!(ctx.client().get_proposal(&proposal_id).approvals.len(), 1);
+    assert!(ctx.client().try_execute_proposal(&proposal_id).is_err());
+}
+
+#[test]
+fn test_governance_approval_requires_approver_signature() {
+    let ctx = TestContext::setup();
+    let [a, b, _] = ctx.setup_governance();
+    let outsider = Address::generate(&ctx.env);
+    let proposal_id = ctx.client().propose(&a, &GovAction::CancelStream(0));
+
+    // b's approval signed by someone else
+    let args = (b.clone(), proposal_id).into_val(&ctx.env);
+    ctx.mock_single_auth(&a, "approve", args);
+    assert!(ctx.client().try_approve(&b, &proposal_id).is_err());
+
+    assert!(!ctx.approve_as(&outsider, proposal_id));
+
+    ctx.env.mock_all_auths();
+    assert!(ctx.client().get_proposal(&proposal_id).approvals.is_empty());
+    assert!(ctx.client().try_propose(&outsider, &GovAction::CancelStream(0)).is_err());
+}
+
+#[test]
+fn test_governance_proposal_expires() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let [a, b, c] = ctx.setup_governance();
+    let proposal_id = ctx.client().propose(&a, &GovAction::CancelStream(stream_id));
+    ctx.client().approve(&a, &proposal_id);
+
+    ctx.env.ledger().set_timestamp(1001);
+    assert!(!ctx.approve_as(&b, proposal_id));
+
+    ctx.env.ledger().set_timestamp(500);
+    ctx.env.mock_all_auths();
+    ctx.client().approve(&c, &proposal_id);
+    ctx.env.ledger().set_timestamp(1001);
+    assert!(ctx.client().try_execute_proposal(&proposal_id).is_err());
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Active
+    );
+}
+
+#[test]
+fn test_governance_cancels_stream() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let [a, b, _] = ctx.setup_governance();
+    let proposal_id = ctx.client().propose(&a, &GovAction::CancelStream(stream_id));
+    ctx.client().approve(&a, &proposal_id);
+    ctx.client().approve(&b, &proposal_id);
+
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().execute_proposal(&proposal_id);
+
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Cancelled
+    );
+    assert_eq!(ctx.token().balance(&ctx.sender), 9600);
+    assert!(ctx.client().try_execute_proposal(&proposal_id).is_err());
+}
+
+#[test]
+#[should_panic(expected = "admin action requires a governance proposal")]
+fn test_governance_blocks_direct_admin_actions() {
+    let ctx = TestContext::setup();
+    ctx.setup_governance();
+    ctx.client()
+        .set_withdrawal_fee(&100, &Address::generate(&ctx.env));
+}
+
+#[test]
+fn test_governance_gates_compliance_and_dispute_settings() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().raise_dispute(&stream_id, &ctx.sender);
+    let [a, b, _] = ctx.setup_governance();
+
+    let client = ctx.client();
+    assert!(client.try_freeze_recipient(&ctx.recipient).is_err());
+    assert!(client.try_unfreeze_recipient(&ctx.recipient).is_err());
+    assert!(client.try_set_compliance_hook(&None).is_err());
+    assert!(client.try_set_min_deposit(&Some(100)).is_err());
+    assert!(client.try_force_complete(&stream_id).is_err());
+    assert!(client.try_resolve_dispute(&stream_id, &true).is_err());
+    assert!(client.try_set_fee_collector(&a).is_err());
+    assert!(client.try_set_withdraw_grace_period(&0).is_err());
+    assert!(client.try_set_min_cliff_duration(&Some(60)).is_err());
+    assert!(client.try_set_recipient_may_dispute(&true).is_err());
+    assert!(client.try_set_admin_cancel_delay(&0).is_err());
+
+    let execute = |action: GovAction| {
+        let proposal_id = client.propose(&a, &action);
+        client.approve(&a, &proposal_id);
+        client.approve(&b, &proposal_id);
+        client.execute_proposal(&proposal_id);
+    };
+    execute(GovAction::FreezeRecipient(ctx.recipient.clone()));
+    assert!(client.is_recipient_frozen(&ctx.recipient));
+    execute(GovAction::UnfreezeRecipient(ctx.recipient.clone()));
+    assert!(!client.is_recipient_frozen(&ctx.recipient));
+    execute(GovAction::SetMinDeposit(Some(100)));
+    execute(GovAction::SetFeeCollector(b.clone()));
+    execute(GovAction::SetWithdrawGracePeriod(3600));
+    execute(GovAction::SetMinCliffDuration(Some(60)));
+    execute(GovAction::SetRecipientMayDispute(true));
+    execute(GovAction::SetAdminCancelDelay(120));
+    let view = client.get_config();
+    assert_eq!(view.min_deposit, Some(100));
+    assert_eq!(view.fee_collector, Some(b.clone()));
+    assert_eq!(view.withdraw_grace_period, 3600);
+    assert_eq!(view.min_cliff_duration, Some(60));
+    assert!(view.recipient_may_dispute);
+    assert_eq!(view.admin_cancel_delay, 120);
+
+    execute(GovAction::ResolveDispute(stream_id, true));
+    assert!(!client.is_disputed(&stream_id));
+    ctx.env.ledger().set_timestamp(1000);
+    execute(GovAction::ForceComplete(stream_id));
+    assert_eq!(
+        client.get_stream_state(&stream_id).status,
+        StreamStatus::Completed
+    );
+}
+
+#[test]
+fn test_governance_approves_milestones() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_milestone_stream();
+    let [a, b, _] = ctx.setup_governance();
+    assert!(ctx.client().try_approve_milestone(&stream_id, &0).is_err());
+
+    let proposal_id = ctx
+        .client()
+        .propose(&a, &GovAction::ApproveMilestone(stream_id, 0));
+    ctx.client().approve(&a, &proposal_id);
+    ctx.client().approve(&b, &proposal_id);
+    ctx.client().execute_proposal(&proposal_id);
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).milestones_approved,
+        1
+    );
+}
+
+#[test]
+fn test_set_governance_validates_threshold() {
+    let ctx = TestContext::setup();
+    let approvers = vec![&ctx.env, Address::generate(&ctx.env)];
+
+    assert!(ctx.client().try_set_governance(&approvers, &0, &1000).is_err());
+    assert!(ctx.client().try_set_governance(&approvers, &2, &1000).is_err());
+    assert_eq!(ctx.client().get_governance(), None);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — streams by status
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// Assert the status index lists exactly the streams in each status.
+    fn assert_status_index_consistent(&self, stream_count: u64) {
+        let statuses = [
+            StreamStatus::Active,
+            StreamStatus::Paused,
+            StreamStatus::Completed,
+            StreamStatus::Cancelled,
+        ];
+        for status in statuses {
+            let indexed: std::vec::Vec<u64> = self
+                .client()
+                .get_streams_by_status(&status, &0, &50)
+                .iter()
+                .collect();
+            let actual: std::vec::Vec<u64> = (0..stream_count)
+                .filter(|id| self.client().get_stream_state(id).status == status)
+                .collect();
+            assert_eq!(indexed, actual, "index mismatch for {status:?}");
+        }
+    }
+}
+
+#[test]
+fn test_streams_by_status_tracks_every_transition() {
+    let ctx = TestContext::setup();
+    let ids: std::vec::Vec<u64> = (0..5).map(|_| ctx.create_default_stream()).collect();
+    assert_eq!(
+        ctx.client()
+            .get_streams_by_status(&StreamStatus::Active, &0, &50)
+            .len(),
+        5
+    );
+
+    ctx.env.ledger().set_timestamp(200);
+    ctx.client().pause_stream(&ids[0]);
+    ctx.client().pause_stream(&ids[1]);
+    ctx.client().resume_stream(&ids[1]);
+    ctx.client().cancel_stream(&ids[2]);
+    ctx.assert_status_index_consistent(5);
+
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.client().withdraw(&ids[3]);
+    // Cancelled, then completed once drained
+    ctx.client().withdraw(&ids[2]);
+    ctx.assert_status_index_consistent(5);
+
+    assert_eq!(
+        ctx.client()
+            .get_streams_by_status(&StreamStatus::Completed, &0, &50),
+        vec![&ctx.env, ids[2], ids[3]]
+    );
+}
+
+#[test]
+fn test_archived_stream_leaves_status_index() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.client().withdraw(&stream_id);
+
+    ctx.client().archive_completed_stream(&stream_id);
+
+    assert!(ctx
+        .client()
+        .get_streams_by_status(&StreamStatus::Completed, &0, &50)
+        .is_empty());
+}
+
+#[test]
+fn test_streams_by_status_pages_in_id_order() {
+    let ctx = TestContext::setup();
+    let ids: std::vec::Vec<u64> = (0..6).map(|_| ctx.create_default_stream()).collect();
+    ctx.env.ledger().set_timestamp(100);
+    // Paused out of id order
+    for i in [4, 1, 5, 2] {
+        ctx.client().pause_stream(&ids[i]);
+    }
+    ctx.client().cancel_stream(&ids[3]);
+
+    let paused = StreamStatus::Paused;
+    let first_page = ctx.client().get_streams_by_status(&paused, &0, &3);
+    assert_eq!(first_page, vec![&ctx.env, ids[1], ids[2], ids[4]]);
+    let next = first_page.last().unwrap() + 1;
+    assert_eq!(
+        ctx.client().get_streams_by_status(&paused, &next, &3),
+        vec![&ctx.env, ids[5]]
+    );
+    assert!(ctx.client().get_streams_by_status(&paused, &99, &3).is_empty());
+    assert_eq!(
+        ctx.client().get_streams_by_status(&StreamStatus::Active, &0, &50),
+        vec![&ctx.env, ids[0]]
+    );
+    assert_eq!(
+        ctx.client().get_streams_by_status(&StreamStatus::Cancelled, &0, &50),
+        vec![&ctx.env, ids[3]]
+    );
+}
+
+#[test]
+#[should_panic(expected = "limit too large (max 50)")]
+fn test_streams_by_status_bounds_limit() {
+    let ctx = TestContext::setup();
+    ctx.client()
+        .get_streams_by_status(&StreamStatus::Active, &0, &51);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — get_stream_states
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_get_stream_states_matches_individual_fetches() {
+    let ctx = TestContext::setup();
+    let first = ctx.create_default_stream();
+    let second = ctx.create_cliff_stream();
+    let third = ctx.create_default_stream();
+    ctx.client().pause_stream(&second);
+
+    let states = ctx
+        .client()
+        .get_stream_states(&vec![&ctx.env, third, first, second]);
+
+    assert_eq!(states.len(), 3);
+    for (state, id) in states.iter().zip([third, first, second]) {
+        let single = ctx.client().get_stream_state(&id);
+        assert_eq!(state.stream_id, single.stream_id);
+        assert_eq!(state.status, single.status);
+        assert_eq!(state.cliff_time, single.cliff_time);
+    }
+}
+
+#[test]
+#[should_panic(expected = "stream not found")]
+fn test_get_stream_states_missing_id_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().get_stream_states(&vec![&ctx.env, stream_id, 99]);
+}
+
+#[test]
+#[should_panic(expected = "too many stream ids (max 50)")]
+fn test_get_stream_states_bounds_input() {
+    let ctx = TestContext::setup();
+    let mut ids = Vec::new(&ctx.env);
+    for id in 0..51u64 {
+        ids.push_back(id);
+    }
+    ctx.client().get_stream_states(&ids);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — get_stream_view
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// Check every computed field of `get_stream_view` against the matching
+    /// single-purpose entrypoint.
+    fn assert_view_consistent(&self, stream_id: u64) {
+        let view = self.client().get_stream_view(&stream_id);
+        let state = self.client().get_stream_state(&stream_id);
+        assert_eq!(view.stream.status, state.status);
+        assert_eq!(view.stream.withdrawn_amount, state.withdrawn_amount);
+        assert_eq!(view.accrued, self.client().calculate_accrued(&stream_id));
+        assert_eq!(view.seconds_remaining, self.client().time_remaining(&stream_id));
+        assert_eq!(view.refundable_if_cancelled_now, self.client().get_locked(&stream_id));
+        if state.status == StreamStatus::Active || state.status == StreamStatus::Cancelled {
+            assert_eq!(view.withdrawable, view.accrued - state.withdrawn_amount);
+        } else {
+            assert_eq!(view.withdrawable, 0);
+        }
+    }
+}
+
+#[test]
+fn test_stream_view_matches_entrypoints_over_time() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_cliff_stream();
+
+    for t in [0u64, 250, 499, 500, 750] {
+        ctx.env.ledger().set_timestamp(t);
+        ctx.assert_view_consistent(stream_id);
+    }
+
+    ctx.env.ledger().set_timestamp(600);
+    ctx.client().withdraw(&stream_id);
+    ctx.assert_view_consistent(stream_id);
+    let view = ctx.client().get_stream_view(&stream_id);
+    assert_eq!(view.withdrawable, 0);
+    assert_eq!(view.refundable_if_cancelled_now, 400);
+    assert_eq!(view.seconds_remaining, 400);
+
+    ctx.client().pause_stream(&stream_id);
+    ctx.env.ledger().set_timestamp(700);
+    ctx.assert_view_consistent(stream_id);
+
+    ctx.client().resume_stream(&stream_id);
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.assert_view_consistent(stream_id);
+    assert_eq!(ctx.client().get_stream_view(&stream_id).withdrawable, 400);
+}
+
+#[test]
+fn test_stream_view_after_cancel_and_completion() {
+    let ctx = TestContext::setup();
+    let cancelled = ctx.create_default_stream();
+    let completed = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().cancel_stream(&cancelled);
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.client().withdraw(&completed);
+
+    for t in [1000u64, 2000] {
+        ctx.env.ledger().set_timestamp(t);
+        ctx.assert_view_consistent(cancelled);
+        ctx.assert_view_consistent(completed);
+    }
+    let view = ctx.client().get_stream_view(&cancelled);
+    assert_eq!(view.withdrawable, 300);
+    assert_eq!(view.refundable_if_cancelled_now, 0);
+    assert_eq!(view.seconds_remaining, 0);
+    let view = ctx.client().get_stream_view(&completed);
+    assert_eq!(view.accrued, 1000);
+    assert_eq!(view.withdrawable, 0);
+}
+
+#[test]
+fn test_stream_view_locked_stream_has_no_refund() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().lock_stream(&stream_id);
+
+    ctx.env.ledger().set_timestamp(400);
+    let view = ctx.client().get_stream_view(&stream_id);
+    assert_eq!(view.accrued, 400);
+    assert_eq!(view.refundable_if_cancelled_now, 0);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — total withdrawable
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_total_withdrawable_sums_streams_across_statuses() {
+    let ctx = TestContext::setup();
+    let active = ctx.create_default_stream();
+    let paused = ctx.create_default_stream();
+    let cancelled = ctx.create_default_stream();
+    let completed = ctx.create_default_stream();
+    let scheduled = ctx.create_scheduled_stream();
+    let partly_withdrawn = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().pause_stream(&paused);
+    ctx.client().cancel_stream(&cancelled);
+    ctx.client().withdraw(&partly_withdrawn);
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.client().withdraw(&completed);
+    // Back to mid-stream for the others
+    ctx.env.ledger().set_timestamp(600);
+
+    let ids = [active, paused, cancelled, completed, scheduled, partly_withdrawn];
+    let expected: i128 = ids
+        .iter()
+        .map(|id| ctx.client().calculate_withdrawable(id))
+        .sum();
+    // active 600, cancelled 300, scheduled 600 (claimable since 500),
+    // partly withdrawn 300; paused and completed streams add nothing
+    assert_eq!(expected, 600 + 300 + 600 + 300);
+    assert_eq!(ctx.client().get_total_withdrawable(&ctx.recipient, &None), expected);
+}
+
+#[test]
+fn test_total_withdrawable_with_explicit_ids() {
+    let ctx = TestContext::setup();
+    let first = ctx.create_default_stream();
+    let second = ctx.create_default_stream();
+    let other_recipient = Address::generate(&ctx.env);
+    ctx.env.ledger().set_timestamp(0);
+    let foreign = ctx.client().create_stream(
+        &ctx.sender,
+        &other_recipient,
+        &1000_i128,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
+    );
+
+    ctx.env.ledger().set_timestamp(250);
+    let total = ctx
+        .client()
+        .get_total_withdrawable(&ctx.recipient, &Some(vec![&ctx.env, second, foreign, 99]));
+    assert_eq!(total, 250);
+    assert_eq!(ctx.client().get_total_withdrawable(&ctx.recipient, &None), 500);
+    assert_eq!(ctx.client().calculate_withdrawable(&first), 250);
+}
+
+#[test]
+fn test_total_withdrawable_zero_while_frozen() {
+    let ctx = TestContext::setup();
+    ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(400);
+
+    ctx.client().freeze_recipient(&ctx.recipient);
+    assert_eq!(ctx.client().get_total_withdrawable(&ctx.recipient, &None), 0);
+    ctx.client().unfreeze_recipient(&ctx.recipient);
+    assert_eq!(ctx.client().get_total_withdrawable(&ctx.recipient, &None), 400);
+}
+
+#[test]
+#[should_panic(expected = "too many stream ids (max 50)")]
+fn test_total_withdrawable_bounds_explicit_ids() {
+    let ctx = TestContext::setup();
+    let mut ids = Vec::new(&ctx.env);
+    for id in 0..51u64 {
+        ids.push_back(id);
+    }
+    ctx.client().get_total_withdrawable(&ctx.recipient, &Some(ids));
+}
+
+// ---------------------------------------------------------------------------
+// Tests — withdraw_all
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_withdraw_all_claims_every_stream_in_one_transfer() {
+    let ctx = TestContext::setup();
+    let full = ctx.create_default_stream();
+    let cliffed = ctx.create_cliff_stream();
+    let partly_withdrawn = ctx.create_default_stream();
+    let paused = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(200);
+    ctx.client().withdraw(&partly_withdrawn);
+    ctx.client().pause_stream(&paused);
+    ctx.env.ledger().set_timestamp(600);
+
+    let token_events = || {
+        ctx.env
+            .events()
+            .all()
+            .iter()
+            .filter(|(contract, _, _)| *contract == ctx.token_id)
+            .count()
+    };
+    let events_before = token_events();
+    let total = ctx.client().withdraw_all(&ctx.recipient);
+
+    // 600 + 600 (past the cliff) + 400 + nothing from the paused stream,
+    // in a single token transfer
+    assert_eq!(total, 1600);
+    assert_eq!(token_events(), events_before + 1);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 200 + 1600);
+    let withdrawn = |id: u64| ctx.client().get_stream_state(&id).withdrawn_amount;
+    assert_eq!(withdrawn(full), 600);
+    assert_eq!(withdrawn(cliffed), 600);
+    assert_eq!(withdrawn(partly_withdrawn), 600);
+    assert_eq!(withdrawn(paused), 0);
+
+
+    // Nothing left to claim is not an error
+    assert_eq!(ctx.client().withdraw_all(&ctx.recipient), 0);
+}
+
+#[test]
+fn test_withdraw_all_completes_drained_streams_and_takes_fee_once() {
+    let ctx = TestContext::setup();
+    let collector = Address::generate(&ctx.env);
+    let first = ctx.create_default_stream();
+    let second = ctx.create_default_stream();
+    ctx.client().set_withdrawal_fee(&100u32, &collector); // 1%
+
+    ctx.env.ledger().set_timestamp(1000);
+    assert_eq!(ctx.client().withdraw_all(&ctx.recipient), 1980);
+
+    assert_eq!(ctx.token().balance(&collector), 20);
+    assert_eq!(ctx.client().get_fees_collected(&ctx.token_id), 20);
+    assert_eq!(ctx.client().get_stream_state(&first).status, StreamStatus::Completed);
+    assert_eq!(ctx.client().get_stream_state(&second).status, StreamStatus::Completed);
+}
+
+#[test]
+fn test_withdraw_all_resumes_after_batch_limit() {
+    let ctx = TestContext::setup();
+    ctx.sac.mint(&ctx.sender, &100_000_i128);
+    for _ in 0..55 {
+        ctx.create_default_stream();
+    }
+    ctx.env.ledger().set_timestamp(10);
+    ctx.env.budget().reset_unlimited();
+
+    assert_eq!(ctx.client().withdraw_all(&ctx.recipient), 50 * 10);
+    assert_eq!(ctx.client().withdraw_all(&ctx.recipient), 5 * 10);
+    // Wrapped around to the start, where there is nothing new yet
+    assert_eq!(ctx.client().withdraw_all(&ctx.recipient), 0);
+    assert_eq!(ctx.client().get_stream_state(&54).withdrawn_amount, 10);
+}
+
+#[test]
+fn test_withdraw_all_requires_recipient_auth() {
+    let ctx = TestContext::setup();
+    ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(500);
+
+    ctx.mock_single_auth(&ctx.sender, "withdraw_all", (ctx.recipient.clone(),).into_val(&ctx.env));
+    assert!(ctx.client().try_withdraw_all(&ctx.recipient).is_err());
+    assert_eq!(ctx.token().balance(&ctx.recipient), 0);
+}
+
+#[test]
+fn test_withdraw_all_skips_everything_while_frozen() {
+    let ctx = TestContext::setup();
+    ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(500);
+    ctx.client().freeze_recipient(&ctx.recipient);
+
+    assert_eq!(ctx.client().withdraw_all(&ctx.recipient), 0);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 0);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — withdraw_receipt
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_withdraw_receipt_reports_completion() {
+    let ctx = TestContext::setup();
+    let collector = Address::generate(&ctx.env);
+    let stream_id = ctx.create_default_stream();
+    ctx.client().set_withdrawal_fee(&100u32, &collector); // 1%
+
+    ctx.env.ledger().set_timestamp(400);
+    let receipt = ctx.client().withdraw_receipt(&stream_id);
+    assert_eq!(
+        receipt,
+        WithdrawReceipt {
+            stream_id,
+            amount: 396,
+            fee: 4,
+            total_withdrawn: 400,
+            completed: false,
+        }
+    );
+
+    ctx.env.ledger().set_timestamp(1000);
+    let receipt = ctx.client().withdraw_receipt(&stream_id);
+    assert_eq!(receipt.amount, 594);
+    assert_eq!(receipt.fee, 6);
+    assert_eq!(receipt.total_withdrawn, 1000);
+    assert!(receipt.completed);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 396 + 594);
+}
+
+#[test]
+fn test_withdraw_receipt_completes_cancelled_stream() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().cancel_stream(&stream_id);
+
+    let receipt = ctx.client().withdraw_receipt(&stream_id);
+    assert_eq!(receipt.amount, 300);
+    assert_eq!(receipt.fee, 0);
+    assert!(receipt.completed);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — get_stream_summary
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_stream_summary_phases_and_progress() {
+    let ctx = TestContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+    let stream_id = ctx.client().create_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &3000_i128,
+        &3_i128,
+        &100u64,
+        &400u64,
+        &1100u64,
+        &Bytes::new(&ctx.env),
+        &None,
+    );
+    let summary = |t: u64| {
+        ctx.env.ledger().set_timestamp(t);
+        ctx.client().get_stream_summary(&stream_id)
+    };
+
+    assert_eq!(summary(50).phase, StreamPhase::NotStarted);
+    assert_eq!(summary(399).phase, StreamPhase::Cliff);
+    assert_eq!(summary(399).progress_bps, 0);
+
+    // Exactly at the cliff 300 of 1000 seconds have accrued
+    let at_cliff = summary(400);
+    assert_eq!(at_cliff.phase, StreamPhase::Streaming);
+    assert_eq!(at_cliff.progress_bps, 3000);
+
+    // 333 of 1000 seconds in: 3330 bps
+    ctx.env.ledger().set_timestamp(433);
+    ctx.client().withdraw(&stream_id);
+    let partway = summary(433);
+    assert_eq!(partway.progress_bps, 3330);
+    assert_eq!(partway.withdrawn_bps, 3330);
+    assert_eq!(summary(1099).progress_bps, 9990);
+
+    let at_end = summary(1100);
+    assert_eq!(at_end.phase, StreamPhase::Ended);
+    assert_eq!(at_end.progress_bps, 10_000);
+    assert_eq!(summary(5000).progress_bps, 10_000);
+
+    ctx.client().withdraw(&stream_id);
+    let done = summary(5000);
+    assert_eq!(done.phase, StreamPhase::Terminal);
+    assert_eq!(done.withdrawn_bps, 10_000);
+}
+
+#[test]
+fn test_stream_summary_after_cancel() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(250);
+    ctx.client().withdraw(&stream_id);
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().cancel_stream(&stream_id);
+
+    let summary = ctx.client().get_stream_summary(&stream_id);
+    assert_eq!(summary.phase, StreamPhase::Terminal);
+    assert_eq!(summary.progress_bps, 10_000);
+    assert_eq!(summary.withdrawn_bps, 6250);
+}
+
+#[test]
+fn test_stream_summary_cancelled_before_cliff() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_cliff_stream();
+
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().cancel_stream(&stream_id);
+
+    let summary = ctx.client().get_stream_summary(&stream_id);
+    assert_eq!(summary.phase, StreamPhase::Terminal);
+    assert_eq!(summary.progress_bps, 0);
+    assert_eq!(summary.withdrawn_bps, 0);
+}
+
+#[test]
+fn test_stream_summary_overfunded_uses_streamable_total() {
+    let ctx = TestContext::setup();
+    ctx.env.ledger().set_timestamp(0);
+    // 1000 streamable out of a 4000 deposit
+    let stream_id = ctx.client().create_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &4000_i128,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
+    );
+
+    ctx.env.ledger().set_timestamp(500);
+    assert_eq!(ctx.client().get_stream_summary(&stream_id).progress_bps, 5000);
+    ctx.env.ledger().set_timestamp(1000);
+    assert_eq!(ctx.client().get_stream_summary(&stream_id).progress_bps, 10_000);
+}
+
+#[test]
+fn test_stream_summary_max_rate_stream_does_not_overflow() {
+    let ctx = TestContext::setup();
+    ctx.sac.mint(&ctx.sender, &(i128::MAX - 10_000_i128));
+    let stream_id = ctx.create_max_rate_stream();
+
+    ctx.env.ledger().set_timestamp(1);
+    assert_eq!(ctx.client().get_stream_summary(&stream_id).progress_bps, 3333);
+    ctx.env.ledger().set_timestamp(3);
+    assert_eq!(ctx.client().get_stream_summary(&stream_id).progress_bps, 10_000);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — get_stream_hash_for_signing
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_stream_hash_is_sha256_of_creation_parameters() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_cliff_stream();
+
+    let expected = ctx.env.crypto().sha256(
+        &(
+            stream_id,
+            ctx.sender.clone(),
+            ctx.recipient.clone(),
+            1000_i128,
+            1_i128,
+            0u64,
+            500u64,
+            1000u64,
+        )
+            .to_xdr(&ctx.env),
+    );
+    let hash = ctx.client().get_stream_hash_for_signing(&stream_id);
+    assert_eq!(hash, BytesN::from(expected));
+}
+
+#[test]
+fn test_stream_hash_ignores_progress_but_not_parameters() {
+    let ctx = TestContext::setup();
+    let first = ctx.create_default_stream();
+    let second = ctx.create_default_stream();
+    let hash = |id: u64| ctx.client().get_stream_hash_for_signing(&id);
+    let before = hash(first);
+
+    // Withdrawals and pauses are not part of the signed parameters
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().withdraw(&first);
+    ctx.client().pause_stream(&first);
+    assert_eq!(hash(first), before);
+
+    // Otherwise identical streams differ by id
+    assert_ne!(hash(second), before);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — get_stats
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_stats_follow_stream_lifecycle() {
+    let ctx = TestContext::setup();
+    let stats = || ctx.client().get_stats();
+    assert_eq!(stats().total_streams, 0);
+
+    let withdrawn = ctx.create_default_stream();
+    let cancelled = ctx.create_default_stream();
+    let paused = ctx.create_default_stream();
+    assert_eq!(stats().total_streams, 3);
+    assert_eq!(stats().active_count, 3);
+    assert_eq!(stats().total_locked, 3000);
+    assert_eq!(stats().total_withdrawn_all_time, 0);
+
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().withdraw(&withdrawn);
+    assert_eq!(stats().total_locked, 2700);
+    assert_eq!(stats().total_withdrawn_all_time, 300);
+
+    // The unstreamed 700 goes back to the sender; the accrued 300 stays
+    ctx.client().cancel_stream(&cancelled);
+    ctx.client().pause_stream(&paused);
+    assert_eq!(stats().active_count, 1);
+    assert_eq!(stats().total_locked, 700 + 300 + 1000);
+
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.client().withdraw(&withdrawn);
+    ctx.client().withdraw(&cancelled);
+    assert_eq!(stats().active_count, 0);
+    assert_eq!(stats().total_locked, 1000);
+    assert_eq!(stats().total_withdrawn_all_time, 1300);
+
+    ctx.client().resume_stream(&paused);
+    assert_eq!(stats().active_count, 1);
+
+    // Archiving a completed stream does not change any total
+    let before = stats();
+    ctx.client().archive_completed_stream(&withdrawn);
+    assert_eq!(stats(), before);
+    assert_eq!(
+        ctx.token().balance(&ctx.contract_id),
+        stats().total_locked
+    );
+}
+
+#[test]
+fn test_stats_count_fees_as_withdrawn() {
+    let ctx = TestContext::setup();
+    let collector = Address::generate(&ctx.env);
+    let stream_id = ctx.create_default_stream();
+    ctx.client().set_withdrawal_fee(&100u32, &collector); // 1%
+
+    ctx.env.ledger().set_timestamp(500);
+    assert_eq!(ctx.client().withdraw(&stream_id), 495);
+
+    let stats = ctx.client().get_stats();
+    assert_eq!(stats.total_withdrawn_all_time, 500);
+    assert_eq!(stats.total_locked, 500);
+}
+
+#[test]
+#[should_panic(expected = "locked total overflow")]
+fn test_locked_total_overflow_panics() {
+    let ctx = TestContext::setup();
+    // As if earlier streams had already locked almost i128::MAX
+    ctx.env.as_contract(&ctx.contract_id, || {
+        ctx.env
+            .storage()
+            .instance()
+            .set(&DataKey::LockedTotal, &(i128::MAX - 999));
+    });
+    ctx.create_default_stream();
+}
+
+#[test]
+fn test_locked_total_tracks_deposits_near_i128_max() {
+    let ctx = TestContext::setup();
+    let whale = Address::generate(&ctx.env);
+    let deposit = i128::MAX - 1000;
+    ctx.sac.mint(&whale, &deposit);
+    ctx.client().create_stream(
+        &whale,
+        &ctx.recipient,
+        &deposit,
+        &(deposit / 1000),
+        &0u64,
+        &0u64,
+        &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
+    );
+    assert_eq!(ctx.client().get_stats().total_locked, deposit);
+}
+
+#[test]
+fn test_bulk_stats_over_mixed_statuses() {
+    let ctx = TestContext::setup();
+    let active = ctx.create_default_stream();
+    let paused = ctx.create_default_stream();
+    let cancelled = ctx.create_default_stream();
+    let completed = ctx.client().create_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &400_i128,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &400u64,
+        &Bytes::new(&ctx.env),
+        &None,
+    );
+
+    ctx.env.ledger().set_timestamp(200);
+    ctx.client().cancel_stream(&cancelled);
+    ctx.client().pause_stream(&paused);
+    ctx.env.ledger().set_timestamp(600);
+    ctx.client().withdraw(&completed);
+    assert_eq!(
+        ctx.client().get_stream_state(&completed).status,
+        StreamStatus::Completed
+    );
+
+    // The unknown id is skipped
+    let ids = vec![&ctx.env, active, paused, cancelled, completed, 99];
+    let stats = ctx.client().get_bulk_stats(&ids);
+    assert_eq!(
+        stats,
+        BulkStats {
+            count: 4,
+            total_deposit: 1000 + 1000 + 200 + 400,
+            total_accrued: 600 + 600 + 200 + 400,
+            total_withdrawn: 400,
+            total_remaining_in_contract: 1000 + 1000 + 200,
+        }
+    );
+}
+
+#[test]
+fn test_bulk_stats_caps_ids() {
+    let ctx = TestContext::setup();
+    let mut ids = Vec::new(&ctx.env);
+    for id in 0..200u64 {
+        ids.push_back(id);
+    }
+    assert_eq!(ctx.client().get_bulk_stats(&ids).count, 0);
+    ids.push_back(200);
+    assert!(ctx.client().try_get_bulk_stats(&ids).is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Tests — force_complete
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_force_complete_pays_recipient_remainder() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().withdraw(&stream_id);
+
+    ctx.env.ledger().set_timestamp(1000);
+    assert_eq!(ctx.client().force_complete(&stream_id), 600);
+
+    assert_eq!(ctx.token().balance(&ctx.recipient), 1000);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 0);
+    let stats = ctx.client().get_stats();
+    assert_eq!((stats.active_count, stats.total_locked), (0, 0));
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Completed
+    );
+}
+
+#[test]
+fn test_force_complete_takes_no_withdrawal_fee() {
+    let ctx = TestContext::setup();
+    let collector = Address::generate(&ctx.env);
+    ctx.client().set_withdrawal_fee(&100, &collector);
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().withdraw(&stream_id);
+    assert_eq!(ctx.token().balance(&collector), 4);
+
+    ctx.env.ledger().set_timestamp(1000);
+    assert_eq!(ctx.client().force_complete(&stream_id), 600);
+
+    assert_eq!(ctx.token().balance(&ctx.recipient), 396 + 600);
+    assert_eq!(ctx.token().balance(&collector), 4);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 0);
+}
+
+#[test]
+#[should_panic(expected = "stream has not ended")]
+fn test_force_complete_before_end_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(999);
+    ctx.client().force_complete(&stream_id);
+}
+
+#[test]
+#[should_panic(expected = "only active streams can be force-completed")]
+fn test_force_complete_paused_stream_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().pause_stream(&stream_id);
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.client().force_complete(&stream_id);
+}
+
+#[test]
+#[should_panic(expected = "stream is not fully accrued")]
+fn test_force_complete_unapproved_milestones_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_milestone_stream();
+    ctx.env.ledger().set_timestamp(u64::MAX);
+    ctx.client().force_complete(&stream_id);
+}
+
+#[test]
+fn test_force_complete_requires_admin_auth() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(1000);
+
+    ctx.mock_single_auth(&ctx.recipient, "force_complete", (stream_id,).into_val(&ctx.env));
+    assert!(ctx.client().try_force_complete(&stream_id).is_err());
+    assert_eq!(ctx.token().balance(&ctx.recipient), 0);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — harvest_yield
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_harvest_yield_pays_pro_rata_share_of_surplus() {
+    let ctx = TestContext::setup();
+    let small = ctx.create_default_stream();
+    let large = ctx.client().create_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &3000_i128,
+        &3_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
+    );
+    // Simulate the token rebasing the contract's balance upwards
+    ctx.sac.mint(&ctx.contract_id, &400_i128);
+
+    assert_eq!(ctx.client().harvest_yield(&large), 300);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 300);
+    // The stream's own funds are untouched
+    assert_eq!(ctx.client().get_stats().total_locked, 4000);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 4000 + 100);
+
+    // A quarter of the surplus, whichever stream harvests first
+    assert_eq!(ctx.client().harvest_yield(&small), 100);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 4000);
+}
+
+#[test]
+fn test_harvest_yield_twice_pays_only_new_surplus() {
+    let ctx = TestContext::setup();
+    let first = ctx.create_default_stream();
+    let second = ctx.create_default_stream();
+    ctx.sac.mint(&ctx.contract_id, &200_i128);
+
+    assert_eq!(ctx.client().harvest_yield(&first), 100);
+    assert_eq!(ctx.client().harvest_yield(&first), 0);
+
+    // The second stream's yield earned at its old balance survives a withdrawal
+    ctx.env.ledger().set_timestamp(500);
+    ctx.client().withdraw(&second);
+    ctx.sac.mint(&ctx.contract_id, &150_i128);
+    assert_eq!(ctx.client().harvest_yield(&second), 100 + 50);
+    assert_eq!(ctx.client().harvest_yield(&first), 100);
+    assert_eq!(ctx.client().harvest_yield(&second), 0);
+}
+
+#[test]
+fn test_harvest_yield_waits_while_frozen_or_disputed() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.sac.mint(&ctx.contract_id, &100_i128);
+
+    ctx.client().freeze_recipient(&ctx.recipient);
+    assert_eq!(ctx.client().harvest_yield(&stream_id), 0);
+    ctx.client().unfreeze_recipient(&ctx.recipient);
+
+    ctx.client().raise_dispute(&stream_id, &ctx.sender);
+    assert_eq!(ctx.client().harvest_yield(&stream_id), 0);
+    ctx.client().resolve_dispute(&stream_id, &true);
+
+    assert_eq!(ctx.client().harvest_yield(&stream_id), 100);
+}
+
+#[test]
+fn test_harvest_yield_without_surplus_pays_nothing() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    assert_eq!(ctx.client().harvest_yield(&stream_id), 0);
+
+    // A completed stream has nothing locked to earn on
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.client().withdraw(&stream_id);
+    ctx.sac.mint(&ctx.contract_id, &400_i128);
+    assert_eq!(ctx.client().harvest_yield(&stream_id), 0);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — pause limit
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// Default stream that cannot stay paused for more than 200 seconds.
+    fn create_max_pause_stream(&self) -> u64 {
+        self.env.ledger().set_timestamp(0);
+        self.client().create_stream_with_max_pause(
+            &self.sender,
+            &self.recipient,
+            &1000_i128,
+            &1_i128,
+            &0u64,
+            &0u64,
+            &1000u64,
+            &Bytes::new(&self.env),
+            &200u64,
+        )
+    }
+}
+
+#[test]
+fn test_recipient_resumes_after_pause_limit() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_max_pause_stream();
+    assert_eq!(ctx.client().get_max_pause_seconds(&stream_id), Some(200));
+
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().pause_stream(&stream_id);
+    ctx.env.ledger().set_timestamp(299);
+    assert!(ctx.client().try_resume_if_expired(&stream_id).is_err());
+
+    // No one's auth is needed
+    ctx.env.ledger().set_timestamp(350);
+    ctx.env.set_auths(&[]);
+    ctx.client().resume_if_expired(&stream_id);
+
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Active
+    );
+    // Accrual never stopped, so nothing is lost to the pause
+    assert!(ctx.withdraw_as(&ctx.recipient, stream_id));
+    assert_eq!(ctx.token().balance(&ctx.recipient), 350);
+}
+
+#[test]
+fn test_withdraw_runs_once_pause_limit_passes() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_max_pause_stream();
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().pause_stream(&stream_id);
+
+    ctx.env.ledger().set_timestamp(299);
+    assert_eq!(ctx.client().calculate_withdrawable(&stream_id), 0);
+    assert_eq!(ctx.client().withdraw_available(&stream_id), 0);
+
+    ctx.env.ledger().set_timestamp(300);
+    assert_eq!(ctx.client().calculate_withdrawable(&stream_id), 300);
+    assert_eq!(ctx.client().withdraw(&stream_id), 300);
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Active
+    );
+}
+
+#[test]
+fn test_pause_limit_restarts_with_each_pause() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_max_pause_stream();
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().pause_stream(&stream_id);
+    ctx.env.ledger().set_timestamp(150);
+    ctx.client().resume_stream(&stream_id);
+    ctx.env.ledger().set_timestamp(250);
+    ctx.client().pause_stream(&stream_id);
+
+    ctx.env.ledger().set_timestamp(400);
+    assert!(ctx.client().try_resume_if_expired(&stream_id).is_err());
+    ctx.env.ledger().set_timestamp(450);
+    ctx.client().resume_if_expired(&stream_id);
+}
+
+#[test]
+#[should_panic(expected = "pause has not expired")]
+fn test_resume_if_expired_without_limit_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().pause_stream(&stream_id);
+    ctx.env.ledger().set_timestamp(999);
+    ctx.client().resume_if_expired(&stream_id);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — scheduled status
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_future_stream_is_scheduled_until_start() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_future_stream();
+    let status = || ctx.client().get_stream_state(&stream_id).status;
+
+    assert_eq!(status(), StreamStatus::Scheduled);
+    assert_eq!(
+        ctx.client()
+            .get_streams_by_status(&StreamStatus::Scheduled, &0, &50),
+        vec![&ctx.env, stream_id]
+    );
+    ctx.env.ledger().set_timestamp(99);
+    assert_eq!(status(), StreamStatus::Scheduled);
+
+    // Reported as started from start_time on
+    ctx.env.ledger().set_timestamp(100);
+    assert_eq!(status(), StreamStatus::Active);
+    assert!(ctx
+        .client()
+        .get_streams_by_status(&StreamStatus::Scheduled, &0, &50)
+        .is_empty());
+    assert_eq!(
+        ctx.client()
+            .get_streams_by_status(&StreamStatus::Active, &0, &50),
+        vec![&ctx.env, stream_id]
+    );
+    assert_eq!(ctx.client().get_stats().active_count, 1);
+
+    ctx.env.ledger().set_timestamp(600);
+    assert_eq!(ctx.client().withdraw(&stream_id), 500);
+}
+
+#[test]
+fn test_withdraw_promotes_scheduled_stream() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_future_stream();
+
+    ctx.env.ledger().set_timestamp(400);
+    assert_eq!(ctx.client().withdraw(&stream_id), 300);
+    // Stamped at start_time, not at the withdrawal
+    assert_eq!(
+        ctx.client().get_audit_trail(&stream_id),
+        vec![
+            &ctx.env,
+            (StreamStatus::Scheduled, 0u64),
+            (StreamStatus::Active, 100u64),
+        ]
+    );
+    assert_eq!(ctx.client().get_stats().active_count, 1);
+}
+
+#[test]
+fn test_views_do_not_persist_scheduled_start() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_future_stream();
+    ctx.env.ledger().set_timestamp(400);
+
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Active
+    );
+    assert_eq!(
+        ctx.client().get_audit_trail(&stream_id),
+        vec![
+            &ctx.env,
+            (StreamStatus::Scheduled, 0u64),
+            (StreamStatus::Active, 100u64),
+        ]
+    );
+    ctx.env.as_contract(&ctx.contract_id, || {
+        let stream: crate::Stream = ctx
+            .env
+            .storage()
+            .persistent()
+            .get(&DataKey::Stream(stream_id))
+            .unwrap();
+        assert_eq!(stream.status, StreamStatus::Scheduled);
+    });
+
+    // Pausing persists the start before the pause
+    ctx.client().pause_stream(&stream_id);
+    assert_eq!(
+        ctx.client().get_audit_trail(&stream_id),
+        vec![
+            &ctx.env,
+            (StreamStatus::Scheduled, 0u64),
+            (StreamStatus::Active, 100u64),
+            (StreamStatus::Paused, 400u64),
+        ]
+    );
+    assert_eq!(ctx.client().get_stats().active_count, 0);
+    assert!(ctx
+        .client()
+        .get_streams_by_status(&StreamStatus::Active, &0, &50)
+        .is_empty());
+}
+
+#[test]
+fn test_cancel_scheduled_stream_refunds_full_deposit() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_future_stream();
+    ctx.env.ledger().set_timestamp(50);
+
+    ctx.client().cancel_stream(&stream_id);
+
+    assert_eq!(ctx.token().balance(&ctx.sender), 10_000);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 0);
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Cancelled
+    );
+}
+
+#[test]
+fn test_resume_before_start_returns_to_scheduled() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_future_stream();
+    let status = || ctx.client().get_stream_state(&stream_id).status;
+
+    ctx.client().pause_stream(&stream_id);
+    assert_eq!(status(), StreamStatus::Paused);
+    ctx.env.ledger().set_timestamp(50);
+    ctx.client().resume_stream(&stream_id);
+    assert_eq!(status(), StreamStatus::Scheduled);
+
+    ctx.client().pause_stream(&stream_id);
+    ctx.env.ledger().set_timestamp(150);
+    ctx.client().resume_stream(&stream_id);
+    assert_eq!(status(), StreamStatus::Active);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — audit trail
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_audit_trail_records_each_transition() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().pause_stream(&stream_id);
+    ctx.env.ledger().set_timestamp(200);
+    ctx.client().resume_stream(&stream_id);
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().cancel_stream(&stream_id);
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().withdraw(&stream_id);
+
+    assert_eq!(
+        ctx.client().get_audit_trail(&stream_id),
+        vec![
+            &ctx.env,
+            (StreamStatus::Active, 0u64),
+            (StreamStatus::Paused, 100u64),
+            (StreamStatus::Active, 200u64),
+            (StreamStatus::Cancelled, 300u64),
+            (StreamStatus::Completed, 400u64),
+        ]
+    );
+
+    // Kept after archiving
+    ctx.client().archive_completed_stream(&stream_id);
+    assert_eq!(ctx.client().get_audit_trail(&stream_id).len(), 5);
+}
+
+#[test]
+fn test_audit_trail_records_completion_by_withdrawal() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.client().withdraw(&stream_id);
+
+    assert_eq!(
+        ctx.client().get_audit_trail(&stream_id),
+        vec![
+            &ctx.env,
+            (StreamStatus::Active, 0u64),
+            (StreamStatus::Completed, 1000u64),
+        ]
+    );
+    assert!(ctx.client().get_audit_trail(&99).is_empty());
+}
+
+#[test]
+fn test_audit_trail_keeps_latest_entries() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.budget().reset_unlimited();
+    for t in 1..=30u64 {
+        ctx.env.ledger().set_timestamp(t);
+        ctx.client().pause_stream(&stream_id);
+        ctx.client().resume_stream(&stream_id);
+    }
+
+    // 61 transitions, of which the oldest 11 were dropped
+    let trail = ctx.client().get_audit_trail(&stream_id);
+    assert_eq!(trail.len(), 50);
+    assert_eq!(trail.get(0), Some((StreamStatus::Paused, 6u64)));
+    assert_eq!(trail.last(), Some((StreamStatus::Active, 30u64)));
+}
+
+// ---------------------------------------------------------------------------
+// Tests — increase_deposit_and_extend
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_increase_deposit_and_extend() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(400);
+
+    ctx.client().increase_deposit_and_extend(&stream_id, &500, &1500);
+
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!((state.deposit_amount, state.end_time), (1500, 1500));
+    assert_eq!(ctx.token().balance(&ctx.sender), 10_000 - 1500);
+    assert_eq!(ctx.client().get_stats().total_locked, 1500);
+
+    ctx.env.ledger().set_timestamp(1500);
+    assert_eq!(ctx.client().withdraw(&stream_id), 1500);
+}
+
+#[test]
+fn test_increase_deposit_and_extend_emits_event() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.client().increase_deposit_and_extend(&stream_id, &500, &1500);
+
+    let (_, topics, data) = ctx.env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("extended"), stream_id).into_val(&ctx.env)
+    );
+    let (added, new_deposit, new_end): (i128, i128, u64) = data.into_val(&ctx.env);
+    assert_eq!((added, new_deposit, new_end), (500, 1500, 1500));
+}
+
+#[test]
+#[should_panic(expected = "deposit_amount must cover total streamable amount (rate * duration)")]
+fn test_increase_deposit_and_extend_uncovered_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().increase_deposit_and_extend(&stream_id, &499, &1500);
+}
+
+#[test]
+#[should_panic(expected = "only linear streams can be extended")]
+fn test_increase_deposit_and_extend_recurring_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_recurring_stream();
+    let end_time = ctx.client().get_stream_state(&stream_id).end_time;
+    ctx.client()
+        .increase_deposit_and_extend(&stream_id, &1000, &(end_time + 1000));
+}
+
+#[test]
+#[should_panic(expected = "new_end_time must be after end_time")]
+fn test_increase_deposit_and_extend_earlier_end_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().increase_deposit_and_extend(&stream_id, &500, &1000);
+}
+
+#[test]
+fn test_increase_deposit_and_extend_requires_sender_auth() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    let args = (stream_id, 500_i128, 1500u64).into_val(&ctx.env);
+    ctx.mock_single_auth(&ctx.recipient, "increase_deposit_and_extend", args);
+    assert!(ctx
+        .client()
+        .try_increase_deposit_and_extend(&stream_id, &500, &1500)
+        .is_err());
+    assert_eq!(ctx.client().get_stream_state(&stream_id).end_time, 1000);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — reschedule_stream
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// 1000 tokens at 1/s over 100..1100 with a cliff at 300, created at 0.
+    fn create_future_stream(&self) -> u64 {
+        self.env.ledger().set_timestamp(0);
+        self.client().create_stream(
+            &self.sender,
+            &self.recipient,
+            &1000_i128,
+            &1_i128,
+            &100u64,
+            &300u64,
+            &1100u64,
+            &Bytes::new(&self.env),
+            &None,
+        )
+    }
+}
+
+#[test]
+fn test_reschedule_shifts_whole_schedule() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_future_stream();
+    ctx.env.ledger().set_timestamp(50);
+
+    ctx.client().reschedule_stream(&stream_id, &500, &700, &1500);
+
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(
+        (state.start_time, state.cliff_time, state.end_time),
+        (500, 700, 1500)
+    );
+    let event = ctx.env.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        (symbol_short!("resched"), stream_id).into_val(&ctx.env)
+    );
+
+    // Accrues on the new schedule only
+    ctx.env.ledger().set_timestamp(699);
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), 0);
+    ctx.env.ledger().set_timestamp(1500);
+    assert_eq!(ctx.client().withdraw(&stream_id), 1000);
+}
+
+#[test]
+#[should_panic(expected = "stream has already started")]
+fn test_reschedule_after_start_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_future_stream();
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().reschedule_stream(&stream_id, &500, &700, &1500);
+}
+
+#[test]
+#[should_panic(expected = "deposit_amount must cover total streamable amount (rate * duration)")]
+fn test_reschedule_beyond_deposit_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_future_stream();
+    ctx.client().reschedule_stream(&stream_id, &500, &500, &1501);
+}
+
+#[test]
+#[should_panic(expected = "cliff_time must be within [start_time, end_time]")]
+fn test_reschedule_cliff_before_start_panics() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_future_stream();
+    ctx.client().reschedule_stream(&stream_id, &500, &400, &1500);
+}
+
+#[test]
+fn test_reschedule_requires_sender_auth() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_future_stream();
+
+    let args = (stream_id, 500u64, 700u64, 1500u64).into_val(&ctx.env);
+    ctx.mock_single_auth(&ctx.recipient, "reschedule_stream", args);
+    assert!(ctx
+        .client()
+        .try_reschedule_stream(&stream_id, &500, &700, &1500)
+        .is_err());
+    assert_eq!(ctx.client().get_stream_state(&stream_id).start_time, 100);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — amend_stream
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_amend_raise_mid_stream() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().withdraw(&stream_id);
+
+    ctx.client().amend_stream(&stream_id, &2, &1000);
+
+    // The sender pays in the extra 2 * 600 - 600
+    assert_eq!(ctx.token().balance(&ctx.sender), 10_000 - 1000 - 600);
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.accrued_checkpoint, 400);
+    assert_eq!(state.start_time, 400);
+    assert_eq!(state.deposit_amount, 1600);
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), 400);
+
+    ctx.env.ledger().set_timestamp(700);
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), 400 + 600);
+    ctx.env.ledger().set_timestamp(1000);
+    assert_eq!(ctx.client().withdraw(&stream_id), 1200);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 1600);
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Completed
+    );
+}
+
+#[test]
+fn test_amend_lower_mid_stream_refunds_excess() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.client().create_stream(
+        &ctx.sender,
+        &ctx.recipient,
+        &2000_i128,
+        &2_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &Bytes::new(&ctx.env),
+        &None,
+    );
+    ctx.env.ledger().set_timestamp(400);
+
+    ctx.client().amend_stream(&stream_id, &1, &1200);
+
+    // 800 accrued at the old rate, then 1 per second for 800 seconds
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), 800);
+    assert_eq!(ctx.client().get_stream_state(&stream_id).deposit_amount, 1600);
+    assert_eq!(ctx.token().balance(&ctx.sender), 10_000 - 2000 + 400);
+
+    ctx.env.ledger().set_timestamp(800);
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), 1200);
+    ctx.env.ledger().set_timestamp(1200);
+    assert_eq!(ctx.client().withdraw(&stream_id), 1600);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 0);
+}
+
+#[test]
+fn test_amend_emits_rate_change_event() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(400);
+
+    ctx.client().amend_stream(&stream_id, &2, &1000);
+
+    let (_, topics, data) = ctx.env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("amended"), stream_id).into_val(&ctx.env)
+    );
+    let (old_rate, new_rate, new_end, delta): (i128, i128, u64, i128) =
+        data.into_val(&ctx.env);
+    assert_eq!((old_rate, new_rate, new_end, delta), (1, 2, 1000, 600));
+}
+
+#[test]
+fn test_amend_before_cliff_keeps_cliff() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_cliff_stream();
+    ctx.env.ledger().set_timestamp(200);
+
+    ctx.client().amend_stream(&stream_id, &2, &1000);
+
+    // 200 earned before the amendment stays behind the cliff
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), 0);
+    ctx.env.ledger().set_timestamp(499);
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), 0);
+    ctx.env.ledger().set_timestamp(500);
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), 200 + 600);
+}
+
+#[test]
+fn test_amend_cancel_refunds_only_unstreamed() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().amend_stream(&stream_id, &2, &1000);
+
+    ctx.env.ledger().set_timestamp(500);
+    ctx.client().cancel_stream(&stream_id);
+
+    assert_eq!(ctx.client().get_stream_state(&stream_id).deposit_amount, 600);
+    assert_eq!(ctx.client().withdraw(&stream_id), 600);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 0);
+}
+
+#[test]
+fn test_amend_requires_both_signatures() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(400);
+
+    let args = (stream_id, 2_i128, 1000u64).into_val(&ctx.env);
+    ctx.mock_single_auth(&ctx.sender, "amend_stream", args);
+    assert!(ctx.client().try_amend_stream(&stream_id, &2, &1000).is_err());
+
+    let args = (stream_id, 2_i128, 1000u64).into_val(&ctx.env);
+    ctx.mock_single_auth(&ctx.recipient, "amend_stream", args);
+    assert!(ctx.client().try_amend_stream(&stream_id, &2, &1000).is_err());
+
+    assert_eq!(ctx.client().get_stream_state(&stream_id).rate_per_second, 1);
+}
+
+#[test]
+#[should_panic(expected = "only linear streams can be amended")]
+fn test_amend_rejects_milestone_stream() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_milestone_stream();
+    ctx.client().amend_stream(&stream_id, &2, &1000);
+}
+
+#[test]
+#[should_panic(expected = "new_end_time must be in the future")]
+fn test_amend_rejects_past_end_time() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().amend_stream(&stream_id, &2, &400);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — reentrancy
+// ---------------------------------------------------------------------------
+
+#[contracttype]
+enum ReentrantKey {
+    Target,
+    Reentered,
+}
+
+/// Token that, whenever the stream contract pays out of its balance, tries
+/// to call back into `withdraw` and records whether the call went through.
+#[contract]
+struct ReentrantToken;
+
+#[contractimpl]
+impl ReentrantToken {
+    pub fn set_target(env: Env, target: Address) {
+        env.storage().instance().set(&ReentrantKey::Target, &target);
+    }
+
+    pub fn transfer(env: Env, from: Address, _to: Address, _amount: i128) {
+        let target: Address = env.storage().instance().get(&ReentrantKey::Target).unwrap();
+        if from == target {
+            let reentered = FluxoraStreamClient::new(&env, &target)
+                .try_withdraw(&0)
+                .is_ok();
+            env.storage().instance().set(&ReentrantKey::Reentered, &reentered);
+        }
+    }
+
+    pub fn reentered(env: Env) -> Option<bool> {
+        env.storage().instance().get(&ReentrantKey::Reentered)
+    }
+}
+
+#[test]
+fn test_reentrant_token_cannot_withdraw_twice() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, FluxoraStream);
+    let token_id = env.register_contract(None, ReentrantToken);
+    let token = ReentrantTokenClient::new(&env, &token_id);
+    token.set_target(&contract_id);
+    let client = FluxoraStreamClient::new(&env, &contract_id);
+    client.init(&token_id, &Address::generate(&env));
+
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let stream_id = client.create_stream(
+        &sender,
+        &recipient,
+        &1000_i128,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &Bytes::new(&env),
+        &None,
+    );
+
+    env.ledger().set_timestamp(400);
+    assert_eq!(client.withdraw(&stream_id), 400);
+    assert_eq!(token.reentered(), Some(false));
+    assert_eq!(client.get_stream_state(&stream_id).withdrawn_amount, 400);
+
+    client.cancel_stream(&stream_id);
+    assert_eq!(token.reentered(), Some(false));
+    assert_eq!(
+        client.get_stream_state(&stream_id).status,
+        StreamStatus::Cancelled
+    );
+}
+
+#[test]
+fn test_reentrant_token_cannot_withdraw_during_co_cancel() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, FluxoraStream);
+    let token_id = env.register_contract(None, ReentrantToken);
+    let token = ReentrantTokenClient::new(&env, &token_id);
+    token.set_target(&contract_id);
+    let client = FluxoraStreamClient::new(&env, &contract_id);
+    client.init(&token_id, &Address::generate(&env));
+
+    let stream_id = client.create_stream(
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &1000_i128,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &Bytes::new(&env),
+        &None,
+    );
+    client.propose_co_cancel(&stream_id, &600_i128);
+
+    env.ledger().set_timestamp(400);
+    client.confirm_co_cancel(&stream_id);
+    assert_eq!(token.reentered(), Some(false));
+    let stream = client.get_stream_state(&stream_id);
+    assert_eq!(stream.status, StreamStatus::Cancelled);
+    assert_eq!(stream.withdrawn_amount, 600);
+    assert_eq!(client.get_co_cancel_proposal(&stream_id), None);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — data version and migration
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// Rewrite storage as a version-1 deployment holding one stream (id 0,
+    /// 1000 tokens over 0..1000) in the original layout, funded as if it had
+    /// been created then.
+    fn write_v1_stream(&self, status: StreamStatus, withdrawn_amount: i128) {
+        self.sac.mint(&self.contract_id, &(1000 - withdrawn_amount));
+        self.env.as_contract(&self.contract_id, || {
+            let storage = self.env.storage();
+            storage.instance().remove(&DataKey::DataVersion);
+            storage.instance().set(
+                &DataKey::Config,
+                &ConfigV1 {
+                    token: self.token_id.clone(),
+                    admin: self.admin.clone(),
+                },
+            );
+            storage.instance().set(&DataKey::NextStreamId, &1u64);
+            storage.persistent().set(
+                &DataKey::Stream(0),
+                &StreamV1 {
+                    stream_id: 0,
+                    sender: self.sender.clone(),
+                    recipient: self.recipient.clone(),
+                    deposit_amount: 1000,
+                    rate_per_second: 1,
+                    start_time: 0,
+                    cliff_time: 0,
+                    end_time: 1000,
+                    withdrawn_amount,
+                    status,
+                },
+            );
+        });
+    }
+}
+
+#[test]
+fn test_init_writes_current_data_version() {
+    let ctx = TestContext::setup();
+    assert_eq!(ctx.client().get_version(), DATA_VERSION);
+}
+
+#[test]
+fn test_migrate_upgrades_v1_stream() {
+    let ctx = TestContext::setup();
+    ctx.write_v1_stream(StreamStatus::Active, 200);
+    assert_eq!(ctx.client().get_version(), 1);
+
+    ctx.client().migrate();
+
+    assert_eq!(ctx.client().get_version(), DATA_VERSION);
+    let state = ctx.client().get_stream_state(&0);
+    assert_eq!(state.deposit_amount, 1000);
+    assert_eq!(state.withdrawn_amount, 200);
+    assert_eq!(state.refund_address, ctx.sender);
+    assert_eq!(state.category, StreamCategory::Other);
+    assert!(state.memo.is_empty());
+    assert!(state.milestones.is_empty());
+    assert_eq!(state.withdraw_schedule, None);
+    assert_eq!(ctx.client().get_config().admin_cancel_delay, 24 * 60 * 60);
+    assert_eq!(
+        ctx.client().get_streams_by_recipient(&ctx.recipient),
+        vec![&ctx.env, 0u64]
+    );
+    assert_eq!(
+        ctx.client()
+            .get_streams_by_status(&StreamStatus::Active, &0, &50),
+        vec![&ctx.env, 0u64]
+    );
+
+    // Ordinary entrypoints work on the migrated stream.
+    ctx.env.ledger().set_timestamp(500);
+    assert_eq!(ctx.client().withdraw(&0), 300);
+    ctx.client().cancel_stream(&0);
+    assert_eq!(ctx.token().balance(&ctx.sender), 10_000 + 500);
+    let next = ctx.create_default_stream();
+    assert_eq!(next, 1);
+}
+
+#[test]
+fn test_migrate_twice_is_noop() {
+    let ctx = TestContext::setup();
+    ctx.write_v1_stream(StreamStatus::Active, 0);
+    ctx.client().migrate();
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().withdraw(&0);
+
+    ctx.client().migrate();
+
+    assert_eq!(ctx.client().get_version(), DATA_VERSION);
+    assert_eq!(ctx.client().get_stream_state(&0).withdrawn_amount, 100);
+    assert_eq!(
+        ctx.client().get_streams_by_sender(&ctx.sender),
+        vec![&ctx.env, 0u64]
+    );
+}
+
+#[test]
+fn test_migrate_on_current_data_changes_nothing() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.client().migrate();
+
+    assert_eq!(ctx.client().get_version(), DATA_VERSION);
+    assert_eq!(
+        ctx.client().get_streams_by_sender(&ctx.sender),
+        vec![&ctx.env, stream_id]
+    );
+}
+
+#[test]
+fn test_migrate_v2_seeds_stats() {
+    let ctx = TestContext::setup();
+    let completed = ctx.create_default_stream();
+    ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.client().withdraw(&completed);
+    ctx.client().archive_completed_stream(&completed);
+    let expected = ctx.client().get_stats();
+
+    ctx.env.as_contract(&ctx.contract_id, || {
+        let storage = ctx.env.storage().instance();
+        storage.set(&DataKey::DataVersion, &2u32);
+        storage.remove(&DataKey::ActiveCount);
+        storage.remove(&DataKey::LockedTotal);
+        storage.remove(&DataKey::TotalWithdrawn);
+    });
+    assert_eq!(ctx.client().get_stats().total_locked, 0);
+
+    ctx.client().migrate();
+
+    assert_eq!(ctx.client().get_stats(), expected);
+    assert_eq!(expected.active_count, 1);
+    assert_eq!(expected.total_locked, 1000);
+    assert_eq!(expected.total_withdrawn_all_time, 1000);
+}
+
+#[test]
+fn test_migrate_v2_stream_gets_no_checkpoint() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_cliff_stream();
+    let s = ctx.client().get_stream_state(&stream_id);
+    ctx.env.as_contract(&ctx.contract_id, || {
+        let old = StreamV2 {
+            stream_id,
+            sender: s.sender,
+            recipient: s.recipient,
+            deposit_amount: s.deposit_amount,
+            rate_per_second: s.rate_per_second,
+            start_time: s.start_time,
+            cliff_time: s.cliff_time,
+            end_time: s.end_time,
+            withdrawn_amount: s.withdrawn_amount,
+            status: s.status,
+            memo: s.memo,
+            external_ref: s.external_ref,
+            refund_address: s.refund_address,
+            immutable: s.immutable,
+            step_seconds: s.step_seconds,
+            category: s.category,
+            delegate: s.delegate,
+            delegate_can_cancel: s.delegate_can_cancel,
+            milestones: s.milestones,
+            milestones_approved: s.milestones_approved,
+            withdraw_schedule: s.withdraw_schedule,
+            last_withdrawn_at: s.last_withdrawn_at,
+        };
+        let storage = ctx.env.storage();
+        storage.persistent().set(&DataKey::Stream(stream_id), &old);
+        storage.instance().set(&DataKey::DataVersion, &3u32);
+    });
+
+    ctx.client().migrate();
+
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.accrued_checkpoint, 0);
+    assert_eq!(state.cliff_time, 500);
+    ctx.env.ledger().set_timestamp(600);
+    assert_eq!(ctx.client().withdraw(&stream_id), 600);
+}
+
+#[test]
+fn test_migrate_v4_moves_settings_into_config() {
+    let ctx = TestContext::setup();
+    let collector = Address::generate(&ctx.env);
+    ctx.env.as_contract(&ctx.contract_id, || {
+        let storage = ctx.env.storage().instance();
+        storage.set(
+            &DataKey::Config,
+            &ConfigV2 {
+                token: ctx.token_id.clone(),
+                admin: ctx.admin.clone(),
+                admin_cancel_delay: 120,
+            },
+        );
+        storage.set(&DataKey::FeeBps, &25u32);
+        storage.set(&DataKey::FeeCollector, &collector);
+        storage.set(&DataKey::MinDeposit, &100i128);
+        storage.set(&DataKey::WithdrawGracePeriod, &3600u64);
+        storage.set(&DataKey::RecipientMayDispute, &true);
+        storage.set(&DataKey::DataVersion, &4u32);
+    });
+
+    ctx.client().migrate();
+
+    let config = ctx.client().get_config();
+    assert_eq!(config.admin, ctx.admin);
+    assert_eq!(config.admin_cancel_delay, 120);
+    assert_eq!(config.fee_bps, 25);
+    assert_eq!(config.fee_collector, Some(collector));
+    assert_eq!(config.min_deposit, Some(100));
+    assert_eq!(config.min_cliff_duration, None);
+    assert_eq!(config.withdraw_grace_period, 3600);
+    assert_eq!(config.compliance_hook, None);
+    assert!(config.recipient_may_dispute);
+    ctx.env.as_contract(&ctx.contract_id, || {
+        assert!(!ctx.env.storage().instance().has(&DataKey::FeeBps));
+        assert!(!ctx.env.storage().instance().has(&DataKey::MinDeposit));
+    });
+}
+
+#[test]
+fn test_migrate_v1_cancelled_stream_has_nothing_left() {
+    let ctx = TestContext::setup();
+    ctx.write_v1_stream(StreamStatus::Cancelled, 300);
+
+    ctx.client().migrate();
+
+    let state = ctx.client().get_stream_state(&0);
+    assert_eq!(state.deposit_amount, 300);
+    assert_eq!(ctx.client().calculate_accrued(&0), 300);
+}
+
+#[test]
+#[should_panic]
+fn test_migrate_requires_admin_auth() {
+    let ctx = TestContext::setup();
+    ctx.write_v1_stream(StreamStatus::Active, 0);
+    ctx.env.mock_auths(&[]);
+    ctx.client().migrate();
+}
+
+// ---------------------------------------------------------------------------
+// Tests — pause_all / resume_all
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_pause_all_and_resume_all_skip_other_states() {
+    let ctx = TestContext::setup();
+    let active = ctx.create_default_stream();
+    let already_paused = ctx.create_default_stream();
+    let cancelled = ctx.create_default_stream();
+    let completed = ctx.create_default_stream();
+    let locked = ctx.create_default_stream();
+
+    ctx.client().pause_stream(&already_paused);
+    ctx.client().cancel_stream(&cancelled);
+    ctx.client().lock_stream(&locked);
+    ctx.env.ledger().set_timestamp(1000);
+    ctx.client().withdraw(&completed);
+    let status = |id: u64| ctx.client().get_stream_state(&id).status;
+
+    assert_eq!(ctx.client().pause_all(&ctx.sender), 1);
+    assert_eq!(status(active), StreamStatus::Paused);
+    assert_eq!(status(already_paused), StreamStatus::Paused);
+    assert_eq!(status(cancelled), StreamStatus::Cancelled);
+    assert_eq!(status(completed), StreamStatus::Completed);
+    assert_eq!(status(locked), StreamStatus::Active);
+
+    assert_eq!(ctx.client().resume_all(&ctx.sender), 2);
+    assert_eq!(status(active), StreamStatus::Active);
+    assert_eq!(status(already_paused), StreamStatus::Active);
+    assert_eq!(status(cancelled), StreamStatus::Cancelled);
+    assert_eq!(status(completed), StreamStatus::Completed);
+    assert_eq!(status(locked), StreamStatus::Active);
+
+    // Nothing left to resume is not an error
+    assert_eq!(ctx.client().resume_all(&ctx.sender), 0);
+}
+
+#[test]
+fn test_pause_all_resumes_after_batch_limit() {
+    let ctx = TestContext::setup();
+    ctx.sac.mint(&ctx.sender, &100_000_i128);
+    for _ in 0..55 {
+        ctx.create_default_stream();
+    }
+    ctx.env.budget().reset_unlimited();
+
+    assert_eq!(ctx.client().pause_all(&ctx.sender), 50);
+    assert_eq!(ctx.client().get_stream_state(&54).status, StreamStatus::Active);
+    assert_eq!(ctx.client().pause_all(&ctx.sender), 5);
+    assert_eq!(ctx.client().pause_all(&ctx.sender), 0);
+
+    // resume_all keeps its own cursor
+    assert_eq!(ctx.client().resume_all(&ctx.sender), 50);
+    assert_eq!(ctx.client().resume_all(&ctx.sender), 5);
+    assert_eq!(ctx.client().get_stream_state(&54).status, StreamStatus::Active);
+}
+
+#[test]
+fn test_pause_all_requires_sender_auth() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.mock_single_auth(&ctx.recipient, "pause_all", (ctx.sender.clone(),).into_val(&ctx.env));
+    assert!(ctx.client().try_pause_all(&ctx.sender).is_err());
+    assert_eq!(ctx.client().get_stream_state(&stream_id).status, StreamStatus::Active);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — cancellation notice
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// Default stream whose cancellation takes effect 200 seconds after notice.
+    fn create_cancel_notice_stream(&self) -> u64 {
+        self.env.ledger().set_timestamp(0);
+        self.client().create_stream_with_cancel_notice(
+            &self.sender,
+            &self.recipient,
+            &1000_i128,
+            &1_i128,
+            &0u64,
+            &0u64,
+            &1000u64,
+            &Bytes::new(&self.env),
+            &200u64,
+        )
+    }
+}
+
+#[test]
+fn test_cancel_with_notice_keeps_accruing_until_finalized() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_cancel_notice_stream();
+    let sender_before = ctx.token().balance(&ctx.sender);
+
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().cancel_stream(&stream_id);
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Active
+    );
+    assert_eq!(ctx.client().get_cancel_effective_at(&stream_id), Some(300));
+    assert_eq!(ctx.token().balance(&ctx.sender), sender_before);
+
+    // Anyone may finalise, but not before the notice has run
+    ctx.env.set_auths(&[]);
+    ctx.env.ledger().set_timestamp(299);
+    assert!(ctx.client().try_finalize_cancel(&stream_id).is_err());
+    ctx.env.ledger().set_timestamp(300);
+    assert_eq!(ctx.client().finalize_cancel(&stream_id), 700);
+
+    let state = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(state.status, StreamStatus::Cancelled);
+    assert_eq!(ctx.token().balance(&ctx.sender), sender_before + 700);
+    assert_eq!(ctx.client().get_cancel_effective_at(&stream_id), None);
+
+    // Everything accrued through the notice window is owed to the recipient
+    ctx.env.mock_all_auths();
+    ctx.env.ledger().set_timestamp(500);
+    assert_eq!(ctx.client().withdraw(&stream_id), 300);
+}
+
+#[test]
+fn test_recipient_withdraws_during_notice() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_cancel_notice_stream();
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().cancel_stream(&stream_id);
+
+    ctx.env.ledger().set_timestamp(250);
+    assert_eq!(ctx.client().withdraw(&stream_id), 250);
+    ctx.env.ledger().set_timestamp(320);
+    assert_eq!(ctx.client().finalize_cancel(&stream_id), 680);
+    assert_eq!(ctx.client().withdraw(&stream_id), 70);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 320);
+}
+
+#[test]
+fn test_withdraw_cancel_notice() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_cancel_notice_stream();
+    assert!(ctx.client().try_withdraw_cancel_notice(&stream_id).is_err());
+
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().cancel_stream(&stream_id);
+    assert!(ctx.client().try_cancel_stream(&stream_id).is_err());
+    ctx.client().withdraw_cancel_notice(&stream_id);
+    assert_eq!(ctx.client().get_cancel_effective_at(&stream_id), None);
+
+    ctx.env.ledger().set_timestamp(400);
+    assert!(ctx.client().try_finalize_cancel(&stream_id).is_err());
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Active
+    );
+
+    // A fresh notice starts a fresh period
+    ctx.client().cancel_stream(&stream_id);
+    assert_eq!(ctx.client().get_cancel_effective_at(&stream_id), Some(600));
+}
+
+#[test]
+fn test_cancel_without_notice_settles_immediately() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().cancel_stream(&stream_id);
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Cancelled
+    );
+    assert!(ctx.client().try_finalize_cancel(&stream_id).is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Tests — application reference hash
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_set_and_get_reference_hash() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    assert_eq!(ctx.client().get_reference_hash(&stream_id), None);
+
+    let invoice = BytesN::from_array(&ctx.env, &[7u8; 32]);
+    ctx.client().set_reference_hash(&stream_id, &invoice);
+    assert_eq!(ctx.client().get_reference_hash(&stream_id), Some(invoice));
+
+    // A later reference replaces the earlier one
+    let replaced = BytesN::from_array(&ctx.env, &[8u8; 32]);
+    ctx.client().set_reference_hash(&stream_id, &replaced);
+    assert_eq!(ctx.client().get_reference_hash(&stream_id), Some(replaced));
+    assert_eq!(ctx.client().get_reference_hash(&(stream_id + 1)), None);
+}
+
+#[test]
+fn test_set_reference_hash_requires_sender() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let reference = BytesN::from_array(&ctx.env, &[7u8; 32]);
+
+    ctx.env.set_auths(&[]);
+    assert!(ctx.client().try_set_reference_hash(&stream_id, &reference).is_err());
+    assert_eq!(ctx.client().get_reference_hash(&stream_id), None);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — disputes
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_dispute_holds_withdrawals_until_allowed() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(200);
+    ctx.client().raise_dispute(&stream_id, &ctx.sender);
+    let (_, topics, data) = ctx.env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("disputed"), stream_id).into_val(&ctx.env)
+    );
+    let raised_by: Address = data.into_val(&ctx.env);
+    assert_eq!(raised_by, ctx.sender);
+    assert!(ctx.client().is_disputed(&stream_id));
+    assert!(ctx.client().try_raise_dispute(&stream_id, &ctx.sender).is_err());
+
+    ctx.env.ledger().set_timestamp(500);
+    assert!(ctx.client().try_withdraw(&stream_id).is_err());
+    assert_eq!(ctx.client().withdraw_available(&stream_id), 0);
+    assert_eq!(ctx.client().calculate_withdrawable(&stream_id), 0);
+    // Accrual carries on through the dispute
+    assert_eq!(ctx.client().calculate_accrued(&stream_id), 500);
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Active
+    );
+
+    ctx.client().resolve_dispute(&stream_id, &true);
+    let (_, topics, data) = ctx.env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("resolved"), stream_id).into_val(&ctx.env)
+    );
+    let allowed: bool = data.into_val(&ctx.env);
+    assert!(allowed);
+    assert!(!ctx.client().is_disputed(&stream_id));
+
+    ctx.env.ledger().set_timestamp(600);
+    assert_eq!(ctx.client().withdraw(&stream_id), 600);
+}
+
+#[test]
+fn test_resolve_dispute_against_recipient_cancels() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let sender_before = ctx.token().balance(&ctx.sender);
+
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().raise_dispute(&stream_id, &ctx.sender);
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().resolve_dispute(&stream_id, &false);
+
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Cancelled
+    );
+    assert_eq!(ctx.token().balance(&ctx.sender), sender_before + 600);
+    assert!(ctx.client().try_resolve_dispute(&stream_id, &true).is_err());
+}
+
+#[test]
+fn test_recipient_disputes_only_when_allowed() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let stranger = Address::generate(&ctx.env);
+    assert!(!ctx.client().get_recipient_may_dispute());
+    assert!(ctx
+        .client()
+        .try_raise_dispute(&stream_id, &ctx.recipient)
+        .is_err());
+    assert!(ctx.client().try_raise_dispute(&stream_id, &stranger).is_err());
+
+    ctx.client().set_recipient_may_dispute(&true);
+    ctx.client().raise_dispute(&stream_id, &ctx.recipient);
+    assert!(ctx.client().is_disputed(&stream_id));
+}
+
+#[test]
+fn test_resolve_dispute_requires_admin() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().raise_dispute(&stream_id, &ctx.sender);
+
+    ctx.env.set_auths(&[]);
+    assert!(ctx.client().try_resolve_dispute(&stream_id, &true).is_err());
+    assert!(ctx.client().is_disputed(&stream_id));
+}
+
+// ---------------------------------------------------------------------------
+// Tests — mutual consent
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// Like `setup`, but with auth enforced and nothing signed: each test
+    /// mocks exactly the signatures it means to provide.
+    fn setup_strict() -> Self {
+        let ctx = Self::setup();
+        ctx.env.set_auths(&[]);
+        ctx
+    }
+
+    /// Mock signatures from both `a` and `b` for the same root `invoke`.
+    ///
+    /// Each `MockAuth` authorizes one address for a tree of invocations, so
+    /// a call needing two signers takes one entry per signer with the same
+    /// root. Any call the contract makes on a signer's behalf, such as the
+    /// token transfer pulling a deposit, goes in `invoke.sub_invokes`.
+    fn require_both_auths(&self, a: &Address, b: &Address, invoke: &MockAuthInvoke) {
+        self.env.mock_auths(&[
+            MockAuth { address: a, invoke },
+            MockAuth { address: b, invoke },
+        ]);
+    }
+}
+
+#[test]
+fn test_mutual_consent_stream_needs_both_signatures() {
+    let ctx = TestContext::setup_strict();
+    ctx.env.ledger().set_timestamp(0);
+    let memo = Bytes::new(&ctx.env);
+    let args: Vec<Val> = (
+        ctx.sender.clone(),
+        ctx.recipient.clone(),
+        1000_i128,
+        1_i128,
+        0u64,
+        0u64,
+        1000u64,
+        memo.clone(),
+    )
+        .into_val(&ctx.env);
+    let deposit = MockAuthInvoke {
+        contract: &ctx.token_id,
+        fn_name: "transfer",
+        args: (ctx.sender.clone(), ctx.contract_id.clone(), 1000_i128).into_val(&ctx.env),
+        sub_invokes: &[],
+    };
+    let create = MockAuthInvoke {
+        contract: &ctx.contract_id,
+        fn_name: "create_stream_mutual_consent",
+        args,
+        sub_invokes: &[deposit],
+    };
+    let create_as = |signer: &Address| {
+        ctx.env.mock_auths(&[MockAuth {
+            address: signer,
+            invoke: &create,
+        }]);
+        ctx.client().try_create_stream_mutual_consent(
+            &ctx.sender,
+            &ctx.recipient,
+            &1000_i128,
+            &1_i128,
+            &0u64,
+            &0u64,
+            &1000u64,
+            &memo,
+        )
+    };
+
+    assert!(create_as(&ctx.sender).is_err());
+    assert!(create_as(&ctx.recipient).is_err());
+
+    ctx.require_both_auths(&ctx.sender, &ctx.recipient, &create);
+    let stream_id = ctx.client().create_stream_mutual_consent(
+        &ctx.sender,
+        &ctx.recipient,
+        &1000_i128,
+        &1_i128,
+        &0u64,
+        &0u64,
+        &1000u64,
+        &memo,
+    );
+    let stream = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(stream.recipient, ctx.recipient);
+    assert_eq!(stream.deposit_amount, 1000);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — arbitration
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// Arbitrate a default stream at t=400 after the recipient withdrew 100,
+    /// returning what the recipient and sender gained from the arbitration.
+    fn arbitrate_default_stream(&self, recipient_share_bps: u32) -> (i128, i128) {
+        let stream_id = self.create_default_stream();
+        self.env.ledger().set_timestamp(100);
+        self.client().withdraw(&stream_id);
+
+        self.client().set_admin_cancel_delay(&300);
+        self.client().admin_request_cancel(&stream_id);
+
+        let recipient_before = self.token().balance(&self.recipient);
+        let sender_before = self.token().balance(&self.sender);
+        self.env.ledger().set_timestamp(400);
+        self.client()
+            .arbitrate_stream(&stream_id, &recipient_share_bps);
+
+        let stream = self.client().get_stream_state(&stream_id);
+        assert_eq!(stream.status, StreamStatus::Arbitrated);
+        assert_eq!(self.client().calculate_withdrawable(&stream_id), 0);
+        assert_eq!(self.token().balance(&self.contract_id), 0);
+        (
+            self.token().balance(&self.recipient) - recipient_before,
+            self.token().balance(&self.sender) - sender_before,
+        )
+    }
+}
+
+#[test]
+fn test_arbitrate_splits_remaining_balance() {
+    assert_eq!(TestContext::setup().arbitrate_default_stream(0), (0, 900));
+    assert_eq!(TestContext::setup().arbitrate_default_stream(10_000), (900, 0));
+    // 37.5% of 900 is 337.5; the recipient's share rounds down
+    assert_eq!(TestContext::setup().arbitrate_default_stream(3_750), (337, 563));
+}
+
+#[test]
+fn test_arbitrate_rejects_bad_share_and_ended_streams() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().set_admin_cancel_delay(&0);
+    ctx.client().admin_request_cancel(&stream_id);
+    assert!(ctx.client().try_arbitrate_stream(&stream_id, &10_001).is_err());
+
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().cancel_stream(&stream_id);
+    assert!(ctx.client().try_arbitrate_stream(&stream_id, &5_000).is_err());
+}
+
+#[test]
+fn test_arbitrate_waits_for_admin_cancel_delay() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    assert!(ctx.client().try_arbitrate_stream(&stream_id, &5_000).is_err());
+
+    ctx.client().set_admin_cancel_delay(&300);
+    ctx.client().admin_request_cancel(&stream_id);
+    ctx.env.ledger().set_timestamp(299);
+    assert!(ctx.client().try_arbitrate_stream(&stream_id, &5_000).is_err());
+
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().arbitrate_stream(&stream_id, &5_000);
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Arbitrated
+    );
+}
+
+#[test]
+fn test_governance_arbitrates_stream() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let [a, b, _] = ctx.setup_governance();
+    assert!(ctx.client().try_admin_request_cancel(&stream_id).is_err());
+    let proposal_id = ctx
+        .client()
+        .propose(&a, &GovAction::ArbitrateStream(stream_id, 5_000));
+    ctx.client().approve(&a, &proposal_id);
+    ctx.client().approve(&b, &proposal_id);
+
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().execute_proposal(&proposal_id);
+
+    assert_eq!(ctx.token().balance(&ctx.recipient), 500);
+    assert_eq!(ctx.token().balance(&ctx.sender), 9500);
+}
+
+#[test]
+fn test_arbitrate_disputed_cancelled_stream() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().raise_dispute(&stream_id, &ctx.sender);
+    ctx.client().cancel_stream(&stream_id);
+    ctx.client().set_admin_cancel_delay(&100);
+    ctx.client().admin_request_cancel(&stream_id);
+
+    // What the cancellation left the recipient is split instead
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().arbitrate_stream(&stream_id, &5_000);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 150);
+    assert!(!ctx.client().is_disputed(&stream_id));
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 0);
+}
+
+#[test]
+fn test_arbitrate_requires_admin() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.set_auths(&[]);
+    assert!(ctx.client().try_arbitrate_stream(&stream_id, &5_000).is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Tests — create_stream_v2
+// ---------------------------------------------------------------------------
+
+impl<'a> TestContext<'a> {
+    /// Parameters of the default stream.
+    fn default_params(&self) -> StreamParameters {
+        StreamParameters {
+            recipient: self.recipient.clone(),
+            deposit_amount: 1000,
+            rate_per_second: 1,
+            start_time: 0,
+            cliff_time: 0,
+            end_time: 1000,
+        }
+    }
+}
+
+#[test]
+fn test_create_stream_v2_matches_create_stream() {
+    let ctx = TestContext::setup();
+    let legacy_id = ctx.create_default_stream();
+    let stream_id = ctx
+        .client()
+        .create_stream_v2(&ctx.sender, &ctx.default_params());
+
+    let legacy = ctx.client().get_stream_state(&legacy_id);
+    let stream = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(stream.stream_id, legacy_id + 1);
+    assert_eq!(stream.recipient, legacy.recipient);
+    assert_eq!(stream.deposit_amount, legacy.deposit_amount);
+    assert_eq!(stream.rate_per_second, legacy.rate_per_second);
+    assert_eq!(
+        (stream.start_time, stream.cliff_time, stream.end_time),
+        (legacy.start_time, legacy.cliff_time, legacy.end_time)
+    );
+    assert_eq!(stream.status, StreamStatus::Active);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 2000);
+}
+
+#[test]
+#[should_panic(expected = "deposit_amount must cover total streamable amount")]
+fn test_create_stream_v2_validates_like_create_stream() {
+    let ctx = TestContext::setup();
+    let params = StreamParameters {
+        deposit_amount: 999,
+        ..ctx.default_params()
+    };
+    ctx.client().create_stream_v2(&ctx.sender, &params);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — advances
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_approved_advance_pays_early_and_reduces_later_withdrawals() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().request_advance(&stream_id, &300);
+    assert_eq!(ctx.client().get_advance_request(&stream_id), Some(300));
+    ctx.client().approve_advance(&stream_id);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 300);
+    assert_eq!(ctx.client().get_advance_request(&stream_id), None);
+
+    // Nothing more until accrual passes the advance
+    ctx.env.ledger().set_timestamp(250);
+    assert_eq!(ctx.client().calculate_withdrawable(&stream_id), 0);
+    assert_eq!(ctx.client().withdraw_available(&stream_id), 0);
+    assert!(ctx.client().try_withdraw(&stream_id).is_err());
+
+    ctx.env.ledger().set_timestamp(500);
+    assert_eq!(ctx.client().calculate_withdrawable(&stream_id), 200);
+    ctx.env.ledger().set_timestamp(1000);
+    assert_eq!(ctx.client().withdraw(&stream_id), 700);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 1000);
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Completed
+    );
+}
+
+#[test]
+fn test_unapproved_advance_pays_nothing() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().request_advance(&stream_id, &300);
+
+    ctx.env.ledger().set_timestamp(200);
+    assert_eq!(ctx.token().balance(&ctx.recipient), 0);
+    assert_eq!(ctx.client().calculate_withdrawable(&stream_id), 200);
+    assert_eq!(ctx.client().get_stream_state(&stream_id).withdrawn_amount, 0);
+
+    // Only the sender can approve
+    ctx.env.set_auths(&[]);
+    assert!(ctx.client().try_approve_advance(&stream_id).is_err());
+}
+
+#[test]
+fn test_cancel_after_advance_refunds_only_unpaid_balance() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let sender_before = ctx.token().balance(&ctx.sender);
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().request_advance(&stream_id, &300);
+    ctx.client().approve_advance(&stream_id);
+
+    ctx.env.ledger().set_timestamp(200);
+    ctx.client().cancel_stream(&stream_id);
+    assert_eq!(ctx.token().balance(&ctx.sender), sender_before + 700);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 0);
+    assert_eq!(ctx.client().calculate_withdrawable(&stream_id), 0);
+}
+
+#[test]
+fn test_advance_must_leave_part_of_the_stream() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    assert!(ctx.client().try_request_advance(&stream_id, &0).is_err());
+    assert!(ctx.client().try_request_advance(&stream_id, &1000).is_err());
+    assert!(ctx.client().try_approve_advance(&stream_id).is_err());
+    ctx.client().request_advance(&stream_id, &999);
+
+    // The recipient withdrew into the request before it was approved
+    ctx.env.ledger().set_timestamp(10);
+    ctx.client().withdraw(&stream_id);
+    assert!(ctx.client().try_approve_advance(&stream_id).is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Tests — settlement_amounts
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_settlement_amounts_cases() {
+    let split = |deposit, accrued, withdrawn| {
+        let settlement = settlement_amounts(deposit, accrued, withdrawn);
+        (settlement.refund_to_sender, settlement.claimable_by_recipient)
+    };
+    // Nothing accrued: everything goes back
+    assert_eq!(split(1000, 0, 0), (1000, 0));
+    // Part accrued, some of it withdrawn
+    assert_eq!(split(1000, 300, 0), (700, 300));
+    assert_eq!(split(1000, 300, 100), (700, 200));
+    assert_eq!(split(1000, 300, 300), (700, 0));
+    // Fully accrued
+    assert_eq!(split(1000, 1000, 400), (0, 600));
+    assert_eq!(split(1000, 1000, 1000), (0, 0));
+    // Accrual reported beyond the deposit is capped
+    assert_eq!(split(1000, 5000, 0), (0, 1000));
+    // Withdrawn ahead of accrual is not refunded again
+    assert_eq!(split(1000, 200, 300), (700, 0));
+    assert_eq!(split(1000, 0, 1000), (0, 0));
+    assert_eq!(split(0, 0, 0), (0, 0));
+}
+
+#[test]
+fn test_settlement_amounts_conserve_the_deposit() {
+    for deposit in [0_i128, 1, 7, 1000, i128::MAX] {
+        for accrued in [0_i128, 1, 3, 500, 1000, i128::MAX] {
+            for withdrawn in [0_i128, 1, 2, 500, 999, 1000] {
+                if withdrawn > deposit {
+                    continue;
+                }
+                let settlement = settlement_amounts(deposit, accrued, withdrawn);
+                assert!(settlement.refund_to_sender >= 0);
+                assert!(settlement.claimable_by_recipient >= 0);
+                assert_eq!(
+                    settlement.refund_to_sender + settlement.claimable_by_recipient + withdrawn,
+                    deposit,
+                    "tokens not conserved for {:?}",
+                    (deposit, accrued, withdrawn)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_cancel_and_view_agree_on_refund() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    let sender_before = ctx.token().balance(&ctx.sender);
+    ctx.env.ledger().set_timestamp(350);
+    ctx.client().withdraw(&stream_id);
+
+    let refundable = ctx
+        .client()
+        .get_stream_view(&stream_id)
+        .refundable_if_cancelled_now;
+    assert_eq!(refundable, 650);
+    assert_eq!(ctx.client().get_locked(&stream_id), 650);
+    ctx.client().cancel_stream(&stream_id);
+    assert_eq!(ctx.token().balance(&ctx.sender), sender_before + refundable);
+}
+
+// ---------------------------------------------------------------------------
+// Tests — reclaim_after_expiry
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_sender_reclaims_unclaimed_accrual_after_grace_period() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(300);
+    ctx.client().withdraw(&stream_id);
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().cancel_stream(&stream_id);
+    let sender_before = ctx.token().balance(&ctx.sender);
+
+    let grace = ctx.client().get_withdraw_grace_period();
+    assert_eq!(grace, 7_776_000);
+    ctx.env.ledger().set_timestamp(400 + grace - 1);
+    assert!(ctx.client().try_reclaim_after_expiry(&stream_id).is_err());
+
+    ctx.env.ledger().set_timestamp(400 + grace);
+    assert_eq!(ctx.client().reclaim_after_expiry(&stream_id), 100);
+    let (_, topics, data) = ctx.env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("reclaimed"), stream_id).into_val(&ctx.env)
+    );
+    let amount: i128 = data.into_val(&ctx.env);
+    assert_eq!(amount, 100);
+
+    assert_eq!(ctx.token().balance(&ctx.sender), sender_before + 100);
+    assert_eq!(ctx.token().balance(&ctx.contract_id), 0);
+    let stream = ctx.client().get_stream_state(&stream_id);
+    assert_eq!(stream.status, StreamStatus::Cancelled);
+    assert_eq!(ctx.client().calculate_withdrawable(&stream_id), 0);
+    assert!(ctx.client().try_withdraw(&stream_id).is_err());
+    assert!(ctx.client().try_reclaim_after_expiry(&stream_id).is_err());
+}
+
+#[test]
+fn test_reclaim_uses_admin_grace_period() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().set_withdraw_grace_period(&100);
+    assert_eq!(ctx.client().get_withdraw_grace_period(), 100);
+
+    ctx.env.ledger().set_timestamp(200);
+    // Only cancelled streams can be reclaimed
+    assert!(ctx.client().try_reclaim_after_expiry(&stream_id).is_err());
+    ctx.client().cancel_stream(&stream_id);
+    ctx.env.ledger().set_timestamp(299);
+    assert!(ctx.client().try_reclaim_after_expiry(&stream_id).is_err());
+    ctx.env.ledger().set_timestamp(300);
+    assert_eq!(ctx.client().reclaim_after_expiry(&stream_id), 200);
+}
+
+#[test]
+fn test_reclaim_requires_sender() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.client().set_withdraw_grace_period(&0);
+    ctx.env.ledger().set_timestamp(200);
+    ctx.client().cancel_stream(&stream_id);
+
+    ctx.env.set_auths(&[]);
+    assert!(ctx.client().try_reclaim_after_expiry(&stream_id).is_err());
+    assert!(ctx.client().try_set_withdraw_grace_period(&0).is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Tests — get_config
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_config_reflects_admin_settings() {
+    let ctx = TestContext::setup();
+    let config = ctx.client().get_config();
+    assert_eq!(
+        config,
+        Config {
+            token: config.token.clone(),
+            admin: config.admin.clone(),
+            admin_cancel_delay: config.admin_cancel_delay,
+            fee_bps: 0,
+            fee_collector: None,
+            min_deposit: None,
+            min_cliff_duration: None,
+            withdraw_grace_period: 7_776_000,
+            compliance_hook: None,
+            recipient_may_dispute: false,
+        }
+    );
+
+    let collector = Address::generate(&ctx.env);
+    ctx.client().set_withdrawal_fee(&25, &collector);
+    ctx.client().set_min_deposit(&Some(100));
+    ctx.client().set_min_cliff_duration(&Some(60));
+    ctx.client().set_withdraw_grace_period(&3600);
+    let hook = ctx.install_hook(HookMode::Allow).address;
+    ctx.client().set_recipient_may_dispute(&true);
+    assert_eq!(
+        ctx.client().get_config(),
+        Config {
+            token: config.token,
+            admin: config.admin,
+            admin_cancel_delay: config.admin_cancel_delay,
+            fee_bps: 25,
+            fee_collector: Some(collector),
+            min_deposit: Some(100),
+            min_cliff_duration: Some(60),
+            withdraw_grace_period: 3600,
+            compliance_hook: Some(hook),
+            recipient_may_dispute: true,
+        }
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Tests — calculate_accrued_with_pauses
+// ---------------------------------------------------------------------------
+
+/// Accrual of 1000 at rate 1 over 0..1000 with a cliff at 100, given the
+/// paused time.
+fn accrued_with_pauses(now: u64, total_paused: u64, paused_since: Option<u64>) -> i128 {
+    calculate_accrued_with_pauses(0, 100, 1000, 1, 1000, now, total_paused, paused_since)
+}
+
+#[test]
+fn test_accrual_without_pauses_matches_calculate_accrued_amount() {
+    for (start, cliff, end, rate, deposit) in accrual_schedules() {
+        for now in accrual_probe_times(start, cliff, end) {
+            assert_eq!(
+                calculate_accrued_with_pauses(start, cliff, end, rate, deposit, now, 0, None),
+                calculate_accrued_amount(start, cliff, end, rate, deposit, now),
+            );
+        }
+    }
+}
+
+#[test]
+fn test_pause_before_cliff() {
+    // Paused from 20 to 70: the cliff still gates at 100
+    assert_eq!(accrued_with_pauses(99, 50, None), 0);
+    assert_eq!(accrued_with_pauses(100, 50, None), 50);
+    assert_eq!(accrued_with_pauses(1000, 50, None), 950);
+}
+
+#[test]
+fn test_pause_spanning_cliff() {
+    // Still paused since 80
+    assert_eq!(accrued_with_pauses(99, 0, Some(80)), 0);
+    assert_eq!(accrued_with_pauses(150, 0, Some(80)), 80);
+    // The same pause once it ended at 150
+    assert_eq!(accrued_with_pauses(400, 70, None), 330);
+}
+
+#[test]
+fn test_pause_spanning_end() {
+    // A pause still running at the end counts only up to the end
+    assert_eq!(accrued_with_pauses(1000, 0, Some(900)), 900);
+    assert_eq!(accrued_with_pauses(5000, 0, Some(900)), 900);
+    assert_eq!(accrued_with_pauses(5000, 30, Some(900)), 870);
+}
+
+#[test]
+fn test_pause_longer_than_stream() {
+    assert_eq!(accrued_with_pauses(1000, 5000, None), 0);
+    assert_eq!(accrued_with_pauses(u64::MAX, u64::MAX, Some(0)), 0);
+    // An in-progress pause said to start before the stream counts from start
+    assert_eq!(accrued_with_pauses(500, 0, Some(0)), 0);
+}
+
+#[test]
+fn test_accrual_with_pauses_stays_within_deposit_bounds() {
+    for (start, cliff, end, rate, deposit) in accrual_schedules() {
+        for now in accrual_probe_times(start, cliff, end) {
+            let unpaused = calculate_accrued_amount(start, cliff, end, rate, deposit, now);
+            let pauses = [(0, Some(start)), (1, None), (end - start, None), (7, Some(end))];
+            for (paused, since) in pauses {
+                let accrued = calculate_accrued_with_pauses(
+                    start, cliff, end, rate, deposit, now, paused, since,
+                );
+                assert!((0..=deposit).contains(&accrued));
+                assert!(accrued <= unpaused);
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests — auto-resume
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_anyone_triggers_auto_resume_once_due() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(100);
+    assert!(ctx
+        .client()
+        .try_pause_with_auto_resume(&stream_id, &100)
+        .is_err());
+    ctx.client().pause_with_auto_resume(&stream_id, &400);
+    assert_eq!(ctx.client().get_auto_resume_time(&stream_id), Some(400));
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Paused
+    );
+
+    ctx.env.set_auths(&[]);
+    ctx.env.ledger().set_timestamp(399);
+    assert!(ctx.client().try_trigger_auto_resume(&stream_id).is_err());
+    ctx.env.ledger().set_timestamp(400);
+    ctx.client().trigger_auto_resume(&stream_id);
+
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Active
+    );
+    assert_eq!(ctx.client().get_auto_resume_time(&stream_id), None);
+    assert!(ctx.client().try_trigger_auto_resume(&stream_id).is_err());
+}
+
+#[test]
+fn test_manual_resume_clears_auto_resume() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.ledger().set_timestamp(100);
+    ctx.client().pause_with_auto_resume(&stream_id, &400);
+
+    ctx.env.ledger().set_timestamp(200);
+    ctx.client().resume_stream(&stream_id);
+    assert_eq!(ctx.client().get_auto_resume_time(&stream_id), None);
+
+    // A later plain pause is not ended by the old schedule
+    ctx.client().pause_stream(&stream_id);
+    ctx.env.ledger().set_timestamp(500);
+    assert!(ctx.client().try_trigger_auto_resume(&stream_id).is_err());
+    assert_eq!(
+        ctx.client().get_stream_state(&stream_id).status,
+        StreamStatus::Paused
+    );
+}
+
+#[test]
+fn test_pause_with_auto_resume_requires_sender_or_admin() {
+    let ctx = TestContext::setup();
+    let stream_id = ctx.create_default_stream();
+    ctx.env.set_auths(&[]);
+    assert!(ctx
+        .client()
+        .try_pause_with_auto_resume(&stream_id, &400)
+        .is_err());
+    assert_eq!(ctx.client().get_auto_resume_time(&stream_id), None);
+}